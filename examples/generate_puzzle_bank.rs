@@ -0,0 +1,52 @@
+//! Generates `assets/puzzle_bank.bin`, the puzzle bank embedded in the app
+//!
+//! Usage: cargo run --release --example generate_puzzle_bank [puzzles-per-tier]
+
+#![allow(dead_code)]
+
+#[path = "../src/constraints.rs"]
+mod constraints;
+#[path = "../src/game_logic.rs"]
+mod game_logic;
+#[path = "../src/puzzle_bank.rs"]
+mod puzzle_bank;
+#[path = "../src/storage.rs"]
+mod storage;
+
+use game_logic::{Difficulty, GenerationOptions, SudokuGame};
+use std::collections::HashSet;
+
+const DEFAULT_PUZZLES_PER_TIER: usize = 2000;
+
+fn main() {
+    let per_tier = std::env::args()
+        .nth(1)
+        .and_then(|arg| arg.parse().ok())
+        .unwrap_or(DEFAULT_PUZZLES_PER_TIER);
+
+    let mut seen = HashSet::new();
+    let mut tiers = Vec::new();
+
+    for difficulty in Difficulty::all() {
+        let mut puzzles = Vec::with_capacity(per_tier);
+        while puzzles.len() < per_tier {
+            let game = SudokuGame::generate_random_puzzle(
+                difficulty.clone(),
+                &GenerationOptions::default(),
+            );
+
+            // Only keep puzzles whose givens actually fall in the requested tier
+            if game.estimated_difficulty() == difficulty
+                && seen.insert(game.initial_grid.clone())
+            {
+                puzzles.push(game.initial_grid);
+            }
+        }
+        println!("{:?}: {} puzzles", difficulty, puzzles.len());
+        tiers.push(puzzles);
+    }
+
+    let bytes = puzzle_bank::encode_bank(&tiers);
+    std::fs::write("assets/puzzle_bank.bin", &bytes).expect("failed to write puzzle bank");
+    println!("Wrote {} bytes to assets/puzzle_bank.bin", bytes.len());
+}
@@ -0,0 +1,488 @@
+//! Trains `assets/digit_classifier.bin`, the digit classifier used by photo import
+//!
+//! Usage: cargo run --release --example train_digit_classifier <font.ttf>...
+//!
+//! Digits are rendered from the given TrueType fonts with random size,
+//! position, slant, stroke weight, blur, contrast and noise, so the network
+//! sees printed digits roughly as they come out of a photographed cell.
+
+#![allow(dead_code)]
+
+#[path = "../src/digit_recognition.rs"]
+mod digit_recognition;
+
+use digit_recognition::{CLASSES, GLYPH_SIDE, Layer, Network};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+const HIDDEN: usize = 64;
+const SAMPLES_PER_EPOCH: usize = 40_000;
+const EPOCHS: usize = 30;
+const BATCH: usize = 32;
+const OUTPUT: &str = "assets/digit_classifier.bin";
+
+// A glyph outline as closed polygons in font units, y pointing up
+type Outline = Vec<Vec<(f32, f32)>>;
+
+struct Font {
+    // Outlines of the digits 1 to 9
+    digits: Vec<Outline>,
+}
+
+fn main() {
+    let fonts: Vec<Font> = std::env::args()
+        .skip(1)
+        .filter_map(|path| {
+            let font = std::fs::read(&path).ok().and_then(|bytes| parse_font(&bytes));
+            if font.is_none() {
+                eprintln!("Skipping {path}: not a TrueType font with simple digit outlines");
+            }
+            font
+        })
+        .collect();
+    if fonts.is_empty() {
+        eprintln!("Usage: train_digit_classifier <font.ttf>...");
+        std::process::exit(1);
+    }
+
+    let mut rng = StdRng::seed_from_u64(41);
+    let mut network = Network {
+        hidden: random_layer(GLYPH_SIDE * GLYPH_SIDE, HIDDEN, &mut rng),
+        output: random_layer(HIDDEN, CLASSES, &mut rng),
+    };
+    let mut velocity = Network {
+        hidden: zero_layer(GLYPH_SIDE * GLYPH_SIDE, HIDDEN),
+        output: zero_layer(HIDDEN, CLASSES),
+    };
+
+    for epoch in 0..EPOCHS {
+        let rate = 0.02 * (1.0 - epoch as f32 / EPOCHS as f32) + 0.002;
+        let mut correct = 0;
+        let mut samples = Vec::with_capacity(BATCH);
+        for _ in 0..SAMPLES_PER_EPOCH {
+            let digit = rng.gen_range(0..CLASSES);
+            let font = &fonts[rng.gen_range(0..fonts.len())];
+            let Some(glyph) = sample(font, digit, &mut rng) else {
+                continue;
+            };
+            samples.push((glyph, digit));
+            if samples.len() == BATCH {
+                correct += train_batch(&mut network, &mut velocity, &samples, rate);
+                samples.clear();
+            }
+        }
+        println!("Epoch {}: {:.1}% correct", epoch + 1, 100.0 * correct as f32 / SAMPLES_PER_EPOCH as f32);
+    }
+
+    // Check the rounded weights on fresh samples
+    let network = digit_recognition::decode_network(&digit_recognition::encode_network(&network))
+        .expect("the encoded network decodes");
+    let mut correct = 0;
+    let mut total = 0;
+    for _ in 0..10_000 {
+        let digit = rng.gen_range(0..CLASSES);
+        let font = &fonts[rng.gen_range(0..fonts.len())];
+        if let Some(glyph) = sample(font, digit, &mut rng) {
+            let probabilities = network.probabilities(&glyph);
+            let best = (0..CLASSES).max_by(|&a, &b| probabilities[a].total_cmp(&probabilities[b]));
+            correct += usize::from(best == Some(digit));
+            total += 1;
+        }
+    }
+    println!("Held-out accuracy: {:.2}%", 100.0 * correct as f32 / total as f32);
+
+    let bytes = digit_recognition::encode_network(&network);
+    std::fs::write(OUTPUT, &bytes).expect("failed to write the classifier");
+    println!("Wrote {} bytes to {OUTPUT}", bytes.len());
+}
+
+fn random_layer(inputs: usize, outputs: usize, rng: &mut StdRng) -> Layer {
+    let bound = (6.0 / (inputs + outputs) as f32).sqrt();
+    Layer {
+        inputs,
+        outputs,
+        weights: (0..inputs * outputs).map(|_| rng.gen_range(-bound..bound)).collect(),
+        biases: vec![0.0; outputs],
+    }
+}
+
+fn zero_layer(inputs: usize, outputs: usize) -> Layer {
+    Layer {
+        inputs,
+        outputs,
+        weights: vec![0.0; inputs * outputs],
+        biases: vec![0.0; outputs],
+    }
+}
+
+// One step of SGD with momentum on a batch, returning how many it got right
+fn train_batch(network: &mut Network, velocity: &mut Network, samples: &[(Vec<f32>, usize)], rate: f32) -> usize {
+    let mut gradient = Network {
+        hidden: zero_layer(network.hidden.inputs, HIDDEN),
+        output: zero_layer(HIDDEN, CLASSES),
+    };
+    let mut correct = 0;
+
+    for (input, digit) in samples {
+        let hidden: Vec<f32> = network.hidden.forward(input).into_iter().map(|x| x.max(0.0)).collect();
+        let mut delta = digit_recognition::softmax(&network.output.forward(&hidden));
+        let best = (0..CLASSES).max_by(|&a, &b| delta[a].total_cmp(&delta[b]));
+        correct += usize::from(best == Some(*digit));
+        delta[*digit] -= 1.0;
+
+        let mut hidden_delta = vec![0.0; HIDDEN];
+        for (class, &error) in delta.iter().enumerate() {
+            let row = class * HIDDEN;
+            for unit in 0..HIDDEN {
+                gradient.output.weights[row + unit] += error * hidden[unit];
+                hidden_delta[unit] += error * network.output.weights[row + unit];
+            }
+            gradient.output.biases[class] += error;
+        }
+        for unit in 0..HIDDEN {
+            if hidden[unit] <= 0.0 {
+                continue;
+            }
+            let row = unit * network.hidden.inputs;
+            for (index, &x) in input.iter().enumerate() {
+                gradient.hidden.weights[row + index] += hidden_delta[unit] * x;
+            }
+            gradient.hidden.biases[unit] += hidden_delta[unit];
+        }
+    }
+
+    let scale = rate / samples.len() as f32;
+    for (layer, (velocity, gradient)) in [&mut network.hidden, &mut network.output]
+        .into_iter()
+        .zip([(&mut velocity.hidden, &gradient.hidden), (&mut velocity.output, &gradient.output)])
+    {
+        let pairs = layer
+            .weights
+            .iter_mut()
+            .zip(velocity.weights.iter_mut().zip(&gradient.weights))
+            .chain(layer.biases.iter_mut().zip(velocity.biases.iter_mut().zip(&gradient.biases)));
+        for (value, (speed, step)) in pairs {
+            *speed = 0.9 * *speed - scale * step;
+            *value += *speed;
+        }
+    }
+    correct
+}
+
+// Renders a digit into a randomly sized cell the way a photo might show it and
+// turns it into the network's input
+fn sample(font: &Font, digit: usize, rng: &mut StdRng) -> Option<Vec<f32>> {
+    let side = rng.gen_range(24..80usize);
+    let outline = &font.digits[digit];
+
+    // Font units to cell pixels: scale to a digit height, slant, rotate and
+    // shift off center a little
+    let stretch = rng.gen_range(0.8..1.2);
+    let shear = rng.gen_range(-0.12..0.12);
+    let angle = rng.gen_range(-0.08f32..0.08);
+    let (sin, cos) = angle.sin_cos();
+    let (min_x, max_x) = bounds(outline.iter().flatten().map(|point| point.0));
+    let (min_y, max_y) = bounds(outline.iter().flatten().map(|point| point.1));
+    let (center_x, center_y) = ((min_x + max_x) / 2.0, (min_y + max_y) / 2.0);
+    let scale = side as f32 * rng.gen_range(0.45..0.9) / (max_y - min_y);
+    let shift_x = side as f32 * (0.5 + rng.gen_range(-0.06..0.06));
+    let shift_y = side as f32 * (0.5 + rng.gen_range(-0.06..0.06));
+    let transform = |(x, y): (f32, f32)| {
+        let x = (x - center_x) * scale * stretch;
+        let y = -(y - center_y) * scale;
+        let x = x - shear * y;
+        (cos * x - sin * y + shift_x, sin * x + cos * y + shift_y)
+    };
+    let polygons: Vec<Vec<(f32, f32)>> = outline
+        .iter()
+        .map(|contour| contour.iter().copied().map(transform).collect())
+        .collect();
+    let mut coverage = rasterize(&polygons, side);
+
+    // Heavier or lighter print
+    match rng.gen_range(0..6) {
+        0 => coverage = morph(&coverage, side, f32::max),
+        1 => coverage = morph(&coverage, side, f32::min),
+        _ => {}
+    }
+    if rng.gen_bool(0.5) {
+        coverage = blur(&coverage, side);
+    }
+
+    // Gray pixels with paper and ink levels, a lighting gradient and noise,
+    // then the same normalization photo import applies
+    let paper = rng.gen_range(150.0..255.0f32);
+    let ink_level = rng.gen_range(0.0..(paper - 70.0));
+    let gradient = rng.gen_range(-20.0..20.0f32);
+    let noise = rng.gen_range(0.0..12.0f32);
+    let pixels: Vec<f32> = coverage
+        .iter()
+        .enumerate()
+        .map(|(index, &ink)| {
+            let light = paper + gradient * (index % side) as f32 / side as f32;
+            let value = light - ink * (light - ink_level) + rng.gen_range(-noise..=noise);
+            value.clamp(0.0, 255.0)
+        })
+        .collect();
+    let mut sorted = pixels.clone();
+    sorted.sort_unstable_by(f32::total_cmp);
+    let light = sorted[sorted.len() * 9 / 10];
+    let darkest = sorted[sorted.len() / 50];
+    let ink: Vec<f32> = pixels
+        .iter()
+        .map(|&pixel| ((light - pixel) / (light - darkest).max(1.0)).clamp(0.0, 1.0))
+        .collect();
+
+    digit_recognition::glyph(&ink, side, side)
+}
+
+fn bounds(values: impl Iterator<Item = f32>) -> (f32, f32) {
+    values.fold((f32::MAX, f32::MIN), |(min, max), value| (min.min(value), max.max(value)))
+}
+
+// Coverage of each pixel by the polygons under the nonzero rule, from 4x4
+// samples per pixel
+fn rasterize(polygons: &[Vec<(f32, f32)>], side: usize) -> Vec<f32> {
+    const SAMPLES: usize = 4;
+    let mut coverage = vec![0.0; side * side];
+    let edges: Vec<((f32, f32), (f32, f32))> = polygons
+        .iter()
+        .flat_map(|polygon| (0..polygon.len()).map(move |index| (polygon[index], polygon[(index + 1) % polygon.len()])))
+        .collect();
+
+    for sample_y in 0..side * SAMPLES {
+        let y = (sample_y as f32 + 0.5) / SAMPLES as f32;
+        let mut crossings: Vec<(f32, i32)> = edges
+            .iter()
+            .filter(|((_, y0), (_, y1))| (*y0 <= y) != (*y1 <= y))
+            .map(|((x0, y0), (x1, y1))| (x0 + (y - y0) / (y1 - y0) * (x1 - x0), if y1 > y0 { 1 } else { -1 }))
+            .collect();
+        crossings.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        let mut winding = 0;
+        for pair in crossings.windows(2) {
+            winding += pair[0].1;
+            if winding == 0 {
+                continue;
+            }
+            // Samples between the two crossings are inside
+            let from = (pair[0].0 * SAMPLES as f32 - 0.5).ceil().max(0.0) as usize;
+            let to = ((pair[1].0 * SAMPLES as f32 - 0.5).ceil().max(0.0) as usize).min(side * SAMPLES);
+            for sample_x in from..to {
+                coverage[(sample_y / SAMPLES) * side + sample_x / SAMPLES] += 1.0 / (SAMPLES * SAMPLES) as f32;
+            }
+        }
+    }
+    coverage
+}
+
+// Grows or shrinks strokes by a pixel
+fn morph(coverage: &[f32], side: usize, pick: fn(f32, f32) -> f32) -> Vec<f32> {
+    (0..side * side)
+        .map(|index| {
+            let (x, y) = (index % side, index / side);
+            let mut value = coverage[index];
+            for (nx, ny) in [(x.wrapping_sub(1), y), (x + 1, y), (x, y.wrapping_sub(1)), (x, y + 1)] {
+                if nx < side && ny < side {
+                    value = pick(value, coverage[ny * side + nx]);
+                }
+            }
+            value
+        })
+        .collect()
+}
+
+fn blur(coverage: &[f32], side: usize) -> Vec<f32> {
+    (0..side * side)
+        .map(|index| {
+            let (x, y) = (index % side, index / side);
+            let (mut total, mut count) = (0.0, 0.0);
+            for ny in y.saturating_sub(1)..(y + 2).min(side) {
+                for nx in x.saturating_sub(1)..(x + 2).min(side) {
+                    total += coverage[ny * side + nx];
+                    count += 1.0;
+                }
+            }
+            total / count
+        })
+        .collect()
+}
+
+// Just enough TrueType to read the digit outlines: the character map,
+// glyph locations and simple glyphs
+fn parse_font(bytes: &[u8]) -> Option<Font> {
+    let u16_at = |offset: usize| Some(u16::from_be_bytes([*bytes.get(offset)?, *bytes.get(offset + 1)?]));
+    let u32_at = |offset: usize| Some(((u16_at(offset)? as u32) << 16) | u16_at(offset + 2)? as u32);
+    let table = |tag: &[u8; 4]| {
+        (0..u16_at(4)? as usize)
+            .map(|index| 12 + 16 * index)
+            .find(|&record| bytes.get(record..record + 4) == Some(tag))
+            .and_then(|record| u32_at(record + 8))
+            .map(|offset| offset as usize)
+    };
+
+    let head = table(b"head")?;
+    let long_offsets = u16_at(head + 50)? == 1;
+    let loca = table(b"loca")?;
+    let glyf = table(b"glyf")?;
+    let cmap = table(b"cmap")?;
+
+    // A Unicode BMP subtable in format 4
+    let subtable = (0..u16_at(cmap + 2)? as usize)
+        .map(|index| cmap + 4 + 8 * index)
+        .filter(|&record| matches!((u16_at(record), u16_at(record + 2)), (Some(0), _) | (Some(3), Some(1))))
+        .filter_map(|record| Some(cmap + u32_at(record + 4)? as usize))
+        .find(|&subtable| u16_at(subtable) == Some(4))?;
+    let segments = u16_at(subtable + 6)? as usize / 2;
+    let ends = subtable + 14;
+    let starts = ends + 2 * segments + 2;
+    let deltas = starts + 2 * segments;
+    let range_offsets = deltas + 2 * segments;
+    let glyph_index = |ch: u16| -> Option<u16> {
+        let segment = (0..segments).find(|&segment| u16_at(ends + 2 * segment).is_some_and(|end| end >= ch))?;
+        let start = u16_at(starts + 2 * segment)?;
+        if start > ch {
+            return None;
+        }
+        let delta = u16_at(deltas + 2 * segment)?;
+        let range_offset = u16_at(range_offsets + 2 * segment)? as usize;
+        if range_offset == 0 {
+            return Some(ch.wrapping_add(delta));
+        }
+        let address = range_offsets + 2 * segment + range_offset + 2 * (ch - start) as usize;
+        Some(u16_at(address)?.wrapping_add(delta))
+    };
+    let glyph_offset = |glyph: usize| -> Option<usize> {
+        Some(if long_offsets {
+            u32_at(loca + 4 * glyph)? as usize
+        } else {
+            u16_at(loca + 2 * glyph)? as usize * 2
+        })
+    };
+
+    let digits = (b'1'..=b'9')
+        .map(|ch| {
+            let glyph = glyph_index(ch as u16)? as usize;
+            let offset = glyf + glyph_offset(glyph)?;
+            parse_simple_glyph(bytes, offset)
+        })
+        .collect::<Option<Vec<Outline>>>()?;
+    Some(Font { digits })
+}
+
+fn parse_simple_glyph(bytes: &[u8], offset: usize) -> Option<Outline> {
+    let u16_at = |offset: usize| Some(u16::from_be_bytes([*bytes.get(offset)?, *bytes.get(offset + 1)?]));
+    let contours = i16::from_be_bytes(u16_at(offset)?.to_be_bytes());
+    if contours <= 0 {
+        return None; // Composite or empty
+    }
+    let contours = contours as usize;
+    let ends: Vec<usize> = (0..contours)
+        .map(|index| u16_at(offset + 10 + 2 * index).map(|end| end as usize))
+        .collect::<Option<_>>()?;
+    let point_count = ends.last()? + 1;
+    let instructions = u16_at(offset + 10 + 2 * contours)? as usize;
+    let mut position = offset + 12 + 2 * contours + instructions;
+
+    let mut flags = Vec::with_capacity(point_count);
+    while flags.len() < point_count {
+        let flag = *bytes.get(position)?;
+        position += 1;
+        flags.push(flag);
+        if flag & 8 != 0 {
+            let repeat = *bytes.get(position)?;
+            position += 1;
+            flags.extend(std::iter::repeat_n(flag, repeat as usize));
+        }
+    }
+
+    // X coordinates, then Y, each a delta that is a byte (with a sign flag)
+    // or a word, or repeats the previous value
+    let mut read_coordinates = |short: u8, same: u8| -> Option<Vec<f32>> {
+        let mut value = 0i32;
+        let mut coordinates = Vec::with_capacity(point_count);
+        for &flag in &flags[..point_count] {
+            if flag & short != 0 {
+                let delta = *bytes.get(position)? as i32;
+                position += 1;
+                value += if flag & same != 0 { delta } else { -delta };
+            } else if flag & same == 0 {
+                value += i16::from_be_bytes(u16_at(position)?.to_be_bytes()) as i32;
+                position += 2;
+            }
+            coordinates.push(value as f32);
+        }
+        Some(coordinates)
+    };
+    let xs = read_coordinates(2, 16)?;
+    let ys = read_coordinates(4, 32)?;
+
+    let mut outline = Vec::with_capacity(contours);
+    let mut start = 0;
+    for &end in &ends {
+        let points: Vec<((f32, f32), bool)> = (start..=end).map(|index| ((xs[index], ys[index]), flags[index] & 1 != 0)).collect();
+        outline.push(flatten_contour(&points));
+        start = end + 1;
+    }
+    Some(outline)
+}
+
+// Turns a contour of on-curve points and quadratic control points into a
+// polygon
+fn flatten_contour(points: &[((f32, f32), bool)]) -> Vec<(f32, f32)> {
+    const STEPS: usize = 8;
+    let midpoint = |a: (f32, f32), b: (f32, f32)| ((a.0 + b.0) / 2.0, (a.1 + b.1) / 2.0);
+    let count = points.len();
+    // Start on an on-curve point, or between two control points
+    let first = points.iter().position(|point| point.1);
+    let (start, begin) = match first {
+        Some(index) => (points[index].0, index),
+        None => (midpoint(points[0].0, points[1 % count].0), 0),
+    };
+
+    let mut polygon = vec![start];
+    let mut current = start;
+    let mut control: Option<(f32, f32)> = None;
+    for step in 1..=count {
+        let (point, on_curve) = points[(begin + step) % count];
+        let target = match (control, on_curve) {
+            (None, true) => {
+                polygon.push(point);
+                current = point;
+                continue;
+            }
+            (None, false) => {
+                control = Some(point);
+                continue;
+            }
+            (Some(_), true) => point,
+            // Two control points in a row imply an on-curve point between them
+            (Some(previous), false) => midpoint(previous, point),
+        };
+        let handle = control.take().unwrap_or(current);
+        for index in 1..=STEPS {
+            let t = index as f32 / STEPS as f32;
+            let s = 1.0 - t;
+            polygon.push((
+                s * s * current.0 + 2.0 * s * t * handle.0 + t * t * target.0,
+                s * s * current.1 + 2.0 * s * t * handle.1 + t * t * target.1,
+            ));
+        }
+        current = target;
+        if !on_curve {
+            control = Some(point);
+        }
+    }
+    // Close through a trailing control point
+    if let Some(handle) = control {
+        for index in 1..=STEPS {
+            let t = index as f32 / STEPS as f32;
+            let s = 1.0 - t;
+            polygon.push((
+                s * s * current.0 + 2.0 * s * t * handle.0 + t * t * start.0,
+                s * s * current.1 + 2.0 * s * t * handle.1 + t * t * start.1,
+            ));
+        }
+    }
+    polygon
+}
@@ -7,6 +7,29 @@ very-easy = Very Easy
 easy = Easy
 medium = Medium
 hard = Hard
+daily-puzzle = 📅 Daily Puzzle
+seed-placeholder = Seed (optional)
+seed-title = Enter the seed from a printed sheet or the leaderboard, then pick its difficulty to play that puzzle again
+
+# Generation Options
+board-size = Board:
+board-samurai = Samurai
+variant = Variant:
+variant-classic = Classic
+variant-diagonal = Diagonal (Sudoku X)
+variant-killer = Killer
+variant-jigsaw = Jigsaw
+variant-sandwich = Sandwich
+variant-x-sums = X-Sums
+symmetry = Symmetry:
+symmetry-none = None
+symmetry-rotational = Rotational (180°)
+symmetry-diagonal = Diagonal
+symmetry-mirror = Mirror
+minimal-puzzle = Minimal
+rule-anti-knight = Anti-knight
+rule-anti-king = Anti-king
+rule-non-consecutive = Non-consecutive
 
 # Game Controls
 hint = 💡 Hint
@@ -15,6 +38,79 @@ redo = ↷ Redo (↓/→)
 language-switch = 中文
 note-mode = Note Mode
 fill-mode = Fill Mode
+zoom-in = Zoom in
+zoom-out = Zoom out
+
+# Puzzle Editor
+create-puzzle = ✏️ Create Puzzle
+editor-title = ✏️ Puzzle Editor
+editor-no-solution = ❌ No solution
+editor-unique-solution = ✅ Unique solution
+editor-multiple-solutions = ⚠️ Multiple solutions
+editor-givens = Givens: { $count }
+editor-difficulty = Estimated difficulty: { $difficulty }
+lock-givens = 🔒 Lock Givens and Play
+clear-grid = Clear Grid
+ambiguity-hint = Cells outlined in purple take different values in different solutions.
+ambiguity-suggestion = Add these givens to make the solution unique:
+ambiguity-incomplete = The search stopped early, so these givens may leave more than one solution.
+add-suggested-givens = ➕ Add Suggested Givens
+ambiguity-notice = ⚠️ This puzzle has more than one solution. Cells outlined in purple can take different values.
+
+# Killer Cages
+cage-combinations = 🧮 Cage Combinations
+cage-info = Sum { $sum } in { $cells } cells
+cage-duplicate = A number repeats in this cage
+cage-sum-exceeded = The numbers in this cage exceed its sum
+cage-wrong-sum = The numbers in this cage don't add up to its sum
+
+# Import
+import-title = 📥 Import Puzzle
+import-help = One item per line: grid (81 cells, . for empty), then optional rules: diagonal, antiknight, antiking, nonconsecutive, cage <sum> <cells>, thermo <cells>, arrow <cells>, white <cell> <cell>, black <cell> <cell>. Cells are written like r1c2. You can also paste f-puzzles JSON, its compressed data, or an f-puzzles or SudokuPad link.
+import-button = Import
+import-missing-grid = The puzzle needs a line starting with "grid".
+import-grid-length = The grid has { $length } cells; expected 16, 36, 81 or 256.
+import-digit = "{ $digit }" is not a digit on this board.
+import-cell = "{ $cell }" is not a cell on this board.
+import-unknown-rule = Unknown rule "{ $rule }".
+import-rule-cells = Not enough or invalid cells in "{ $line }".
+fpuzzles-decode = This isn't f-puzzles JSON, compressed data or a link.
+fpuzzles-size = { $size }x{ $size } boards can't be imported.
+fpuzzles-malformed = The "{ $key }" entry isn't valid f-puzzles data.
+fpuzzles-unsupported = Unsupported constraints: { $constraints }
+fpuzzles-samurai = Samurai boards can't be exported to f-puzzles.
+export-button = Export to f-puzzles
+export-open-sudokupad = Open in SudokuPad
+export-open-fpuzzles = Open in f-puzzles
+
+# Photo Import
+photo-title = Import from Photo
+photo-help = Open a photo of a printed puzzle or a screenshot (PNG or JPEG). The grid is read on this device; nothing is uploaded.
+photo-reading = Reading the photo…
+photo-size = Board size:
+photo-check = Check the digits against your photo and fix any misreads before loading.
+photo-check-unsure = Check the digits against your photo and fix any misreads before loading. Highlighted cells were hard to read ({ $count }).
+photo-load = Load Puzzle
+photo-cancel = Cancel
+photo-format = Only PNG and JPEG images can be read
+photo-decode = The image could not be read
+photo-unsupported-jpeg = This JPEG uses a format that can't be read yet (such as progressive). Save it as PNG and try again.
+photo-no-grid = No sudoku grid was found. Try a photo taken straight on with the whole grid in view.
+
+# Printing
+print-title = 🖨️ Print Sheets
+print-source-givens = Current puzzle
+print-source-progress = Current puzzle with my progress
+print-source-batch = New puzzles like the current one
+print-per-page = Per page:
+print-difficulty-labels = Difficulty
+print-seed-labels = Seed
+print-answer-key = Answer key
+print-svg = Export SVG
+print-pdf = Export PDF
+print-saved = Saved to { $path }
+print-download = Download { $file }
+print-failed = The sheet couldn't be saved.
 
 # Move History
 move-history = 📋 Move History
@@ -24,13 +120,167 @@ current-position = ► Current position
 completed-moves = ✓ Completed moves
 future-moves = ○ Future moves (after undo)
 
+# Statistics
+stats-title = 📊 Statistics
+stats-started = Started
+stats-completed = Completed
+stats-win-rate = Win rate
+stats-best-time = Best time
+stats-average-time = Average time
+stats-hints = Hints used
+stats-mistakes = Mistakes
+stats-current-streak = Current streak
+stats-best-streak = Best streak
+stats-help = Generated puzzles count toward their difficulty. Starting a new game before solving the current one ends its streak.
+
+# Profiles
+profile-title = 👤 Player
+profile-playing-as = Playing as
+profile-new-name = New player name
+profile-add = Add
+profile-name-invalid = Enter a name that is not already taken.
+profile-avatar = Avatar
+profile-language = Language
+profile-start-difficulty = First puzzle
+profile-colorblind = Colorblind mode
+profile-highlight = Highlight
+profile-rename = Rename
+profile-save = Save
+profile-cancel = Cancel
+profile-remove = Remove player
+profile-remove-confirm = Remove { $name } and their statistics?
+
+# Leaderboard
+leaderboard-title = 🏆 Leaderboard
+leaderboard-daily = Daily puzzle { $date }
+leaderboard-seed = This puzzle (seed { $seed })
+leaderboard-all-players = All players
+leaderboard-no-hints = No hints
+leaderboard-personal-best = Your best: { $time }
+leaderboard-empty = No results yet. Solve a puzzle to get on the board.
+leaderboard-player = Player
+leaderboard-time = Time
+leaderboard-date = Date
+
+# Achievements
+achievements-title = 🏅 Achievements ({ $unlocked }/{ $total })
+achievement-unlocked = Achievement unlocked
+achievement-first-win-very-easy = First Steps
+achievement-first-win-very-easy-description = Solve a Very Easy puzzle
+achievement-first-win-easy = Warming Up
+achievement-first-win-easy-description = Solve an Easy puzzle
+achievement-first-win-medium = Getting Serious
+achievement-first-win-medium-description = Solve a Medium puzzle
+achievement-first-win-hard = Summit
+achievement-first-win-hard-description = Solve a Hard puzzle
+achievement-no-hints = On My Own
+achievement-no-hints-description = Solve a puzzle without hints
+achievement-no-hints-10 = Independent Thinker
+achievement-no-hints-10-description = Solve 10 puzzles without hints
+achievement-no-notes = Mental Math
+achievement-no-notes-description = Solve a puzzle without taking any notes
+achievement-flawless = Flawless
+achievement-flawless-description = Solve a puzzle with no hints and no mistakes
+achievement-under-10-minutes = Quick Thinker
+achievement-under-10-minutes-description = Solve a 9×9 puzzle in under 10 minutes
+achievement-under-5-minutes = Lightning
+achievement-under-5-minutes-description = Solve a 9×9 puzzle in under 5 minutes
+achievement-streak-3 = On a Roll
+achievement-streak-3-description = Solve 3 puzzles in a row without abandoning one
+achievement-streak-10 = Unstoppable
+achievement-streak-10-description = Solve 10 puzzles in a row without abandoning one
+achievement-naked-singles = Sharp Eye
+achievement-naked-singles-description = Place 50 naked singles: the only number left for a cell
+achievement-hidden-singles = Detective
+achievement-hidden-singles-description = Place 25 hidden singles: the only cell left for a number in a row, column or box
+
+# Themes
+theme = Theme
+theme-system = System
+theme-light = Light
+theme-dark = Dark
+theme-high-contrast = High contrast
+palette-title = 🎨 Custom Palettes
+palette-help = Start from the current theme, pick your own colors and save them as a palette. Palettes are shared by every player on this device.
+palette-customize = Customize current theme
+palette-name = Palette name
+palette-save = Save palette
+palette-discard = Discard
+palette-name-missing = Enter a name for the palette.
+palette-delete = Delete { $name }
+palette-color-page = Page
+palette-color-surface = Panels
+palette-color-surface-alt = Number pad
+palette-color-text = Text
+palette-color-muted = Secondary text
+palette-color-border = Borders
+palette-color-input = Text fields
+palette-color-grid-line = Box lines
+palette-color-cell-line = Cell lines
+palette-color-cell = Empty cells
+palette-color-cell-filled = Filled cells
+palette-color-given = Given numbers
+palette-color-entered = Your numbers
+palette-color-selected = Selected cell
+palette-color-selected-given = Selected given
+palette-color-highlight = Same number
+palette-color-selected-highlight = Selected same number
+palette-color-peer = Same row, column or box
+palette-color-candidate = Where the number can go
+palette-color-conflict = Conflict
+palette-color-conflict-given = Conflicting given
+palette-color-conflict-text = Conflict text
+palette-color-conflict-border = Conflict outline
+palette-color-shaded = Shaded cells
+palette-color-accent = Buttons
+palette-color-accent-editor = Puzzle editor and ambiguous cells
+palette-color-accent-import = Import and export
+palette-color-accent-tools = Cages and printing
+palette-color-accent-photo = Photo import
+palette-color-accent-profile = Players
+palette-color-accent-hint = Hint button
+palette-color-success = Redo and success
+palette-color-success-background = Hints in the move log
+palette-color-success-text = Hint text in the move log
+palette-color-error = Error messages
+palette-color-overlay-line = Thermometers and arrows
+palette-color-note-mode = Notes mode button
+palette-color-note-mode-text = Notes mode text
+palette-color-disabled = Disabled buttons
+palette-color-disabled-text = Disabled text
+palette-color-celebration-start = Win banner start
+palette-color-celebration-end = Win banner end
+
+# Colorblind Mode
+colorblind-legend = Stripes: conflict · Dots: same number or note · Bar: number can go here · Ring: selected · ✦: hint
+
+# Accessibility
+board-label = Sudoku board
+cell-position = Row { $row }, column { $col }
+cell-given = { $num }, given
+cell-hint = { $num }, from a hint
+cell-entered = { $num }
+cell-empty = empty
+cell-notes = notes { $notes }
+cell-conflict = conflicts with another cell
+cell-cage = cage of { $sum }
+announce-hint = Hint: { $num } in row { $row }, column { $col }
+announce-undo = Undone: row { $row }, column { $col } is now { $value }
+announce-redo = Redone: row { $row }, column { $col } is now { $value }
+announce-completed = Puzzle solved in { $time }
+
+# Highlighting
+highlight-peers = Row, column and box
+highlight-notes = Notes with the number
+highlight-candidates = Where the number can go
+
 # Instructions
 instructions-title = How to Play:
 how-to-play = How to Play:
-instruction-1 = Click on an empty cell to select it (highlighted in blue)
+instruction-1 = Click on a cell to select it (highlighted in blue)
 instruction-2 = Use keyboard numbers (1-9) or click on empty cells to open number picker
 instruction-3 = Press Delete, Backspace, or 0 to clear the selected cell
-instruction-4 = Use arrow keys (↑/↓ or ←/→) to undo/redo moves
+instruction-4 = On the board, arrow keys move the selection; elsewhere they undo/redo moves (↑/← undo, ↓/→ redo), and Ctrl+Z / Ctrl+Y work anywhere
 instruction-5 = Each row, column, and 3×3 box must contain all numbers 1-9
 instruction-6 = Dark gray cells are given numbers and cannot be changed
 instruction-7 = Light blue cells show your input numbers
@@ -38,10 +288,10 @@ instruction-8 = Red cells with '!' indicate conflicts that need to be resolved
 instruction-9 = Click '💡 Hint' to get help with one cell
 instruction-10 = Use 'Undo' and 'Redo' buttons or keyboard shortcuts to navigate your move history
 instruction-11 = View your complete move history in the log panel
+instruction-12 = On touch screens, tap a cell to select it, long-press a cell to switch between numbers and notes, or swipe left/right on the number panel
 
 # Win Message
 congratulations = 🎉 CONGRATULATIONS! 🎉
 well-done = Well done,
-well-done-anonymous = Well done!
 sudoku-master = You solved the puzzle like a true Sudoku master!
 amazing-work = Amazing work! Ready for another challenge?
\ No newline at end of file
@@ -7,6 +7,29 @@ very-easy = 非常简单
 easy = 简单
 medium = 中等
 hard = 困难
+daily-puzzle = 📅 每日一题
+seed-placeholder = 种子（可选）
+seed-title = 输入打印题单或排行榜上的种子，再选择对应难度即可重玩该谜题
+
+# Generation Options
+board-size = 棋盘：
+board-samurai = 武士数独
+variant = 变体：
+variant-classic = 经典
+variant-diagonal = 对角线数独 (X)
+variant-killer = 杀手数独
+variant-jigsaw = 锯齿数独
+variant-sandwich = 三明治数独
+variant-x-sums = X 和数独
+symmetry = 对称：
+symmetry-none = 无
+symmetry-rotational = 中心对称 (180°)
+symmetry-diagonal = 对角线对称
+symmetry-mirror = 镜像对称
+minimal-puzzle = 最简谜题
+rule-anti-knight = 无马步
+rule-anti-king = 无王步
+rule-non-consecutive = 无连续
 
 # Game Controls
 hint = 💡 提示
@@ -15,6 +38,79 @@ redo = ↷ 重做 (↓/→)
 language-switch = English
 note-mode = 笔记模式
 fill-mode = 填入模式
+zoom-in = 放大
+zoom-out = 缩小
+
+# Puzzle Editor
+create-puzzle = ✏️ 创建谜题
+editor-title = ✏️ 谜题编辑器
+editor-no-solution = ❌ 无解
+editor-unique-solution = ✅ 唯一解
+editor-multiple-solutions = ⚠️ 多个解
+editor-givens = 给定数字：{ $count }
+editor-difficulty = 预估难度：{ $difficulty }
+lock-givens = 🔒 锁定给定数字并开始
+clear-grid = 清空网格
+ambiguity-hint = 紫色虚线框中的单元格在不同的解中数字不同。
+ambiguity-suggestion = 添加以下给定数字即可使解唯一：
+ambiguity-incomplete = 搜索提前停止，添加这些给定数字后可能仍有多个解。
+add-suggested-givens = ➕ 添加建议的给定数字
+ambiguity-notice = ⚠️ 这个谜题有多个解。紫色虚线框中的单元格可以填入不同的数字。
+
+# Killer Cages
+cage-combinations = 🧮 笼子组合
+cage-info = { $cells } 格之和为 { $sum }
+cage-duplicate = 这个笼子里有重复的数字
+cage-sum-exceeded = 这个笼子里的数字超过了它的和
+cage-wrong-sum = 这个笼子里的数字之和不等于它的和
+
+# Import
+import-title = 📥 导入谜题
+import-help = 每行一项：grid（81 个单元格，空格用 . 表示），然后是可选规则：diagonal、antiknight、antiking、nonconsecutive、cage <和> <单元格>、thermo <单元格>、arrow <单元格>、white <单元格> <单元格>、black <单元格> <单元格>。单元格写作 r1c2。也可以粘贴 f-puzzles JSON、其压缩数据，或 f-puzzles 与 SudokuPad 链接。
+import-button = 导入
+import-missing-grid = 谜题需要一行以 "grid" 开头。
+import-grid-length = 网格有 { $length } 个单元格；应为 16、36、81 或 256 个。
+import-digit = "{ $digit }" 不是这个棋盘上的数字。
+import-cell = "{ $cell }" 不是这个棋盘上的单元格。
+import-unknown-rule = 未知规则 "{ $rule }"。
+import-rule-cells = "{ $line }" 中的单元格不足或无效。
+fpuzzles-decode = 这不是 f-puzzles JSON、压缩数据或链接。
+fpuzzles-size = 无法导入 { $size }x{ $size } 的棋盘。
+fpuzzles-malformed = "{ $key }" 项不是有效的 f-puzzles 数据。
+fpuzzles-unsupported = 不支持的约束：{ $constraints }
+fpuzzles-samurai = 武士数独无法导出到 f-puzzles。
+export-button = 导出到 f-puzzles
+export-open-sudokupad = 在 SudokuPad 中打开
+export-open-fpuzzles = 在 f-puzzles 中打开
+
+# Photo Import
+photo-title = 从照片导入
+photo-help = 打开印刷谜题的照片或截图（PNG 或 JPEG）。识别完全在本机进行，不会上传任何内容。
+photo-reading = 正在识别照片…
+photo-size = 棋盘大小：
+photo-check = 请对照照片检查数字，修正识别错误后再载入。
+photo-check-unsure = 请对照照片检查数字，修正识别错误后再载入。有 { $count } 个高亮单元格难以辨认。
+photo-load = 载入谜题
+photo-cancel = 取消
+photo-format = 只能读取 PNG 和 JPEG 图片
+photo-decode = 无法读取该图片
+photo-unsupported-jpeg = 暂不支持这种 JPEG 格式（如渐进式）。请另存为 PNG 后重试。
+photo-no-grid = 未找到数独网格。请正对网格拍摄，并确保整个网格都在画面内。
+
+# Printing
+print-title = 🖨️ 打印题纸
+print-source-givens = 当前谜题
+print-source-progress = 当前谜题及我的进度
+print-source-batch = 与当前谜题同类的新谜题
+print-per-page = 每页：
+print-difficulty-labels = 难度
+print-seed-labels = 种子
+print-answer-key = 答案页
+print-svg = 导出 SVG
+print-pdf = 导出 PDF
+print-saved = 已保存到 { $path }
+print-download = 下载 { $file }
+print-failed = 无法保存题纸。
 
 # Move History
 move-history = 📋 移动历史
@@ -24,13 +120,167 @@ current-position = ► 当前位置
 completed-moves = ✓ 已完成的移动
 future-moves = ○ 未来的移动（撤销后）
 
+# Statistics
+stats-title = 📊 统计
+stats-started = 开始局数
+stats-completed = 完成局数
+stats-win-rate = 胜率
+stats-best-time = 最佳用时
+stats-average-time = 平均用时
+stats-hints = 使用提示
+stats-mistakes = 错误次数
+stats-current-streak = 当前连胜
+stats-best-streak = 最长连胜
+stats-help = 生成的谜题按难度计入统计。未解完当前谜题就开始新游戏会中断该难度的连胜。
+
+# Profiles
+profile-title = 👤 玩家
+profile-playing-as = 当前玩家
+profile-new-name = 新玩家名字
+profile-add = 添加
+profile-name-invalid = 请输入一个未被使用的名字。
+profile-avatar = 头像
+profile-language = 语言
+profile-start-difficulty = 首局难度
+profile-colorblind = 色盲模式
+profile-highlight = 高亮
+profile-rename = 重命名
+profile-save = 保存
+profile-cancel = 取消
+profile-remove = 删除玩家
+profile-remove-confirm = 删除 { $name } 及其统计数据？
+
+# Leaderboard
+leaderboard-title = 🏆 排行榜
+leaderboard-daily = 每日一题 { $date }
+leaderboard-seed = 本题（种子 { $seed }）
+leaderboard-all-players = 所有玩家
+leaderboard-no-hints = 无提示
+leaderboard-personal-best = 你的最佳：{ $time }
+leaderboard-empty = 暂无成绩。解出一道题即可上榜。
+leaderboard-player = 玩家
+leaderboard-time = 用时
+leaderboard-date = 日期
+
+# Achievements
+achievements-title = 🏅 成就（{ $unlocked }/{ $total }）
+achievement-unlocked = 成就解锁
+achievement-first-win-very-easy = 第一步
+achievement-first-win-very-easy-description = 解出一道非常简单的题
+achievement-first-win-easy = 热身完毕
+achievement-first-win-easy-description = 解出一道简单题
+achievement-first-win-medium = 认真起来
+achievement-first-win-medium-description = 解出一道中等题
+achievement-first-win-hard = 登顶
+achievement-first-win-hard-description = 解出一道困难题
+achievement-no-hints = 独立完成
+achievement-no-hints-description = 不使用提示解出一道题
+achievement-no-hints-10 = 独立思考者
+achievement-no-hints-10-description = 不使用提示解出 10 道题
+achievement-no-notes = 心算高手
+achievement-no-notes-description = 不做任何笔记解出一道题
+achievement-flawless = 完美无瑕
+achievement-flawless-description = 不用提示且没有错误地解出一道题
+achievement-under-10-minutes = 思维敏捷
+achievement-under-10-minutes-description = 在 10 分钟内解出一道 9×9 题
+achievement-under-5-minutes = 闪电速度
+achievement-under-5-minutes-description = 在 5 分钟内解出一道 9×9 题
+achievement-streak-3 = 势如破竹
+achievement-streak-3-description = 连续解出 3 道题，中途不放弃
+achievement-streak-10 = 势不可挡
+achievement-streak-10-description = 连续解出 10 道题，中途不放弃
+achievement-naked-singles = 火眼金睛
+achievement-naked-singles-description = 填出 50 个唯余数：格子里只剩一个可填的数字
+achievement-hidden-singles = 侦探
+achievement-hidden-singles-description = 填出 25 个隐性唯一数：某数字在行、列或宫中只剩一个位置
+
+# Themes
+theme = 主题
+theme-system = 跟随系统
+theme-light = 浅色
+theme-dark = 深色
+theme-high-contrast = 高对比度
+palette-title = 🎨 自定义配色
+palette-help = 以当前主题为基础选择自己的颜色并保存为配色方案。配色方案由本设备上的所有玩家共享。
+palette-customize = 自定义当前主题
+palette-name = 配色名称
+palette-save = 保存配色
+palette-discard = 放弃
+palette-name-missing = 请输入配色名称。
+palette-delete = 删除 { $name }
+palette-color-page = 页面
+palette-color-surface = 面板
+palette-color-surface-alt = 数字面板
+palette-color-text = 文字
+palette-color-muted = 次要文字
+palette-color-border = 边框
+palette-color-input = 输入框
+palette-color-grid-line = 宫格线
+palette-color-cell-line = 单元格线
+palette-color-cell = 空格
+palette-color-cell-filled = 已填格
+palette-color-given = 题目数字
+palette-color-entered = 你的数字
+palette-color-selected = 选中格
+palette-color-selected-given = 选中的题目格
+palette-color-highlight = 相同数字
+palette-color-selected-highlight = 选中的相同数字
+palette-color-peer = 同行、同列或同宫
+palette-color-candidate = 数字可填的位置
+palette-color-conflict = 冲突
+palette-color-conflict-given = 冲突的题目格
+palette-color-conflict-text = 冲突文字
+palette-color-conflict-border = 冲突边框
+palette-color-shaded = 阴影格
+palette-color-accent = 按钮
+palette-color-accent-editor = 编辑器与多解格
+palette-color-accent-import = 导入与导出
+palette-color-accent-tools = 笼子与打印
+palette-color-accent-photo = 照片导入
+palette-color-accent-profile = 玩家
+palette-color-accent-hint = 提示按钮
+palette-color-success = 重做与成功
+palette-color-success-background = 操作记录中的提示
+palette-color-success-text = 操作记录中的提示文字
+palette-color-error = 错误信息
+palette-color-overlay-line = 温度计和箭头
+palette-color-note-mode = 笔记模式按钮
+palette-color-note-mode-text = 笔记模式文字
+palette-color-disabled = 禁用按钮
+palette-color-disabled-text = 禁用文字
+palette-color-celebration-start = 胜利横幅起始色
+palette-color-celebration-end = 胜利横幅结束色
+
+# Colorblind Mode
+colorblind-legend = 斜纹：冲突 · 圆点：相同数字或笔记 · 横条：数字可填 · 圆环：选中 · ✦：提示
+
+# Accessibility
+board-label = 数独棋盘
+cell-position = 第 { $row } 行，第 { $col } 列
+cell-given = { $num }，题目给定
+cell-hint = { $num }，来自提示
+cell-entered = { $num }
+cell-empty = 空
+cell-notes = 笔记 { $notes }
+cell-conflict = 与其他格子冲突
+cell-cage = 笼子和为 { $sum }
+announce-hint = 提示：第 { $row } 行第 { $col } 列填入 { $num }
+announce-undo = 已撤销：第 { $row } 行第 { $col } 列现在为 { $value }
+announce-redo = 已重做：第 { $row } 行第 { $col } 列现在为 { $value }
+announce-completed = 谜题完成，用时 { $time }
+
+# Highlighting
+highlight-peers = 同行、同列与同宫
+highlight-notes = 含该数字的笔记
+highlight-candidates = 该数字可填的位置
+
 # Instructions
 instructions-title = 游戏规则：
 how-to-play = 游戏规则：
-instruction-1 = 点击空白单元格选择它（蓝色高亮显示）
+instruction-1 = 点击单元格选择它（蓝色高亮显示）
 instruction-2 = 使用键盘数字键（1-9）或点击空白单元格打开数字选择器
 instruction-3 = 按Delete、Backspace或0键清除选中的单元格
-instruction-4 = 使用方向键（↑/↓ 或 ←/→）来撤销/重做移动
+instruction-4 = 在棋盘上用方向键移动选中的格子；在其他位置方向键用于撤销/重做（↑/← 撤销，↓/→ 重做），Ctrl+Z / Ctrl+Y 在任何位置都可用
 instruction-5 = 每行、每列和每个3×3方格都必须包含数字1-9
 instruction-6 = 深灰色单元格是给定数字，不能更改
 instruction-7 = 浅蓝色单元格显示你输入的数字
@@ -38,10 +288,10 @@ instruction-8 = 带有'!'的红色单元格表示冲突，需要解决
 instruction-9 = 点击'💡 提示'获得一个单元格的帮助
 instruction-10 = 使用'撤销'和'重做'按钮或键盘快捷键来浏览你的移动历史
 instruction-11 = 在日志面板中查看你的完整移动历史
+instruction-12 = 在触摸屏上，轻点格子选中它，长按格子在填数与笔记之间切换，或在数字面板上左右滑动切换模式
 
 # Win Message
 congratulations = 🎉 恭喜你！🎉
 well-done = 干得好，
-well-done-anonymous = 干得好！
 sudoku-master = 你像真正的数独大师一样解决了这个谜题！
 amazing-work = 太棒了！准备好迎接另一个挑战了吗？
\ No newline at end of file
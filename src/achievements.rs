@@ -0,0 +1,265 @@
+//! Achievements unlocked by play
+//!
+//! Each achievement is a rule that turns game events into progress towards a
+//! goal. The app feeds every event the current game emits to `handle`, and the
+//! progress of each player profile is saved on this device.
+
+use crate::game_logic::{BoardSize, Difficulty, GameEvent, Technique};
+use crate::storage;
+
+// How an event moves an achievement along
+enum Progress {
+    Add(u32),
+    // Progress becomes at least this, for records such as streaks
+    Reach(u32),
+}
+
+// Per-player state the rules can look at
+#[derive(Clone, Default, PartialEq, Debug)]
+struct PlayState {
+    // Solved games in a row, without leaving one unsolved for a new one
+    streak: u32,
+    in_progress: bool,
+}
+
+pub struct Achievement {
+    // Used for the saved progress and the locale keys
+    pub key: &'static str,
+    pub icon: &'static str,
+    pub goal: u32,
+    rule: fn(&PlayState, &GameEvent) -> Option<Progress>,
+}
+
+fn first_win(event: &GameEvent, tier: Difficulty) -> Option<Progress> {
+    match event {
+        GameEvent::Completed {
+            difficulty: Some(difficulty),
+            ..
+        } if *difficulty == tier => Some(Progress::Add(1)),
+        _ => None,
+    }
+}
+
+// A classic-sized solve within `seconds`
+fn fast_win(event: &GameEvent, limit: u64) -> Option<Progress> {
+    match event {
+        GameEvent::Completed { size, seconds, .. } if *size == BoardSize::Nine && *seconds < limit => {
+            Some(Progress::Add(1))
+        }
+        _ => None,
+    }
+}
+
+fn placed_with(event: &GameEvent, wanted: Technique) -> Option<Progress> {
+    match event {
+        GameEvent::Input {
+            conflict: false,
+            technique: Some(technique),
+            ..
+        } if *technique == wanted => Some(Progress::Add(1)),
+        _ => None,
+    }
+}
+
+fn win_streak(state: &PlayState, event: &GameEvent) -> Option<Progress> {
+    matches!(event, GameEvent::Completed { .. }).then_some(Progress::Reach(state.streak))
+}
+
+pub const ACHIEVEMENTS: [Achievement; 14] = [
+    Achievement {
+        key: "first-win-very-easy",
+        icon: "🌱",
+        goal: 1,
+        rule: |_, event| first_win(event, Difficulty::VeryEasy),
+    },
+    Achievement {
+        key: "first-win-easy",
+        icon: "🌿",
+        goal: 1,
+        rule: |_, event| first_win(event, Difficulty::Easy),
+    },
+    Achievement {
+        key: "first-win-medium",
+        icon: "🌳",
+        goal: 1,
+        rule: |_, event| first_win(event, Difficulty::Medium),
+    },
+    Achievement {
+        key: "first-win-hard",
+        icon: "🏔️",
+        goal: 1,
+        rule: |_, event| first_win(event, Difficulty::Hard),
+    },
+    Achievement {
+        key: "no-hints",
+        icon: "🧠",
+        goal: 1,
+        rule: |_, event| matches!(event, GameEvent::Completed { hints: 0, .. }).then_some(Progress::Add(1)),
+    },
+    Achievement {
+        key: "no-hints-10",
+        icon: "🎓",
+        goal: 10,
+        rule: |_, event| matches!(event, GameEvent::Completed { hints: 0, .. }).then_some(Progress::Add(1)),
+    },
+    Achievement {
+        key: "no-notes",
+        icon: "✍️",
+        goal: 1,
+        rule: |_, event| matches!(event, GameEvent::Completed { notes: 0, .. }).then_some(Progress::Add(1)),
+    },
+    Achievement {
+        key: "flawless",
+        icon: "💎",
+        goal: 1,
+        rule: |_, event| {
+            matches!(event, GameEvent::Completed { hints: 0, mistakes: 0, .. }).then_some(Progress::Add(1))
+        },
+    },
+    Achievement {
+        key: "under-10-minutes",
+        icon: "⏱️",
+        goal: 1,
+        rule: |_, event| fast_win(event, 10 * 60),
+    },
+    Achievement {
+        key: "under-5-minutes",
+        icon: "⚡",
+        goal: 1,
+        rule: |_, event| fast_win(event, 5 * 60),
+    },
+    Achievement {
+        key: "streak-3",
+        icon: "🔥",
+        goal: 3,
+        rule: win_streak,
+    },
+    Achievement {
+        key: "streak-10",
+        icon: "🌋",
+        goal: 10,
+        rule: win_streak,
+    },
+    Achievement {
+        key: "naked-singles",
+        icon: "🎯",
+        goal: 50,
+        rule: |_, event| placed_with(event, Technique::NakedSingle),
+    },
+    Achievement {
+        key: "hidden-singles",
+        icon: "🔍",
+        goal: 25,
+        rule: |_, event| placed_with(event, Technique::HiddenSingle),
+    },
+];
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Achievements {
+    // One per achievement, in the order of `ACHIEVEMENTS`, capped at the goal
+    pub progress: Vec<u32>,
+    state: PlayState,
+    profile: u32,
+}
+
+impl Achievements {
+    pub fn load(profile: u32) -> Self {
+        let mut achievements = Self {
+            progress: vec![0; ACHIEVEMENTS.len()],
+            state: PlayState::default(),
+            profile,
+        };
+        let data = storage::load(&achievements_key(profile)).unwrap_or_default();
+        for line in data.lines() {
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            let Ok(value) = value.trim().parse::<u32>() else {
+                continue;
+            };
+            match key.trim() {
+                "streak" => achievements.state.streak = value,
+                "in-progress" => achievements.state.in_progress = value == 1,
+                key => {
+                    if let Some(index) = ACHIEVEMENTS.iter().position(|achievement| achievement.key == key) {
+                        achievements.progress[index] = value.min(ACHIEVEMENTS[index].goal);
+                    }
+                }
+            }
+        }
+        achievements
+    }
+
+    pub fn is_unlocked(&self, index: usize) -> bool {
+        self.progress[index] >= ACHIEVEMENTS[index].goal
+    }
+
+    pub fn unlocked_count(&self) -> usize {
+        (0..ACHIEVEMENTS.len()).filter(|&index| self.is_unlocked(index)).count()
+    }
+
+    /// Applies an event and returns the achievements it unlocked
+    pub fn handle(&mut self, event: &GameEvent) -> Vec<&'static Achievement> {
+        match event {
+            GameEvent::Started => {
+                if self.state.in_progress {
+                    self.state.streak = 0;
+                }
+                self.state.in_progress = true;
+            }
+            GameEvent::Completed { .. } => {
+                self.state.in_progress = false;
+                self.state.streak += 1;
+            }
+            _ => {}
+        }
+
+        let mut unlocked = Vec::new();
+        let mut changed = matches!(event, GameEvent::Started | GameEvent::Completed { .. });
+        for (index, achievement) in ACHIEVEMENTS.iter().enumerate() {
+            if self.is_unlocked(index) {
+                continue;
+            }
+            let progress = match (achievement.rule)(&self.state, event) {
+                Some(Progress::Add(amount)) => self.progress[index] + amount,
+                Some(Progress::Reach(value)) => self.progress[index].max(value),
+                None => continue,
+            };
+            let progress = progress.min(achievement.goal);
+            if progress != self.progress[index] {
+                self.progress[index] = progress;
+                changed = true;
+                if self.is_unlocked(index) {
+                    unlocked.push(achievement);
+                }
+            }
+        }
+        if changed {
+            self.save();
+        }
+        unlocked
+    }
+
+    // One `key=value` line per achievement with progress, after the play state
+    fn save(&self) {
+        let mut lines = vec![
+            format!("streak={}", self.state.streak),
+            format!("in-progress={}", self.state.in_progress as u8),
+        ];
+        for (achievement, progress) in ACHIEVEMENTS.iter().zip(&self.progress) {
+            if *progress > 0 {
+                lines.push(format!("{}={}", achievement.key, progress));
+            }
+        }
+        storage::save(&achievements_key(self.profile), &lines.join("\n"));
+    }
+}
+
+fn achievements_key(profile: u32) -> String {
+    format!("achievements-{profile}")
+}
+
+/// Clears the achievements of a removed profile, whose id may be reused
+pub fn remove(profile: u32) {
+    storage::save(&achievements_key(profile), "");
+}
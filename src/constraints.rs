@@ -0,0 +1,1119 @@
+//! Constraint module describing the rules a Sudoku grid must follow
+//!
+//! Every rule of a puzzle is a `Constraint`. Houses (rows, columns, boxes and
+//! other groups without repeats) are listed explicitly so the solver can reason
+//! about them; any other relationship between cells, such as sums or
+//! inequalities, narrows the candidates of a cell. Generation, solving,
+//! conflict detection and rendering all go through a `Rules` set, so a variant
+//! is added by implementing new constraints.
+
+use crate::game_logic::{BoardSize, Grid};
+use std::fmt::Debug;
+use std::sync::Arc;
+
+pub type Cell = (usize, usize);
+
+/// How a constraint is drawn on the board
+#[derive(Clone, PartialEq, Debug)]
+pub enum Decoration {
+    // Thick borders between cells of different regions; holds a region index
+    // per cell, or `None` for cells out of play
+    Regions(Vec<Vec<Option<usize>>>),
+    // Cells with a tinted background, such as the diagonals of Sudoku X
+    ShadedCells(Vec<Cell>),
+    // A dashed outline around the cage with its sum in the corner
+    Cage(Cage),
+    // A bulb in the first cell and a tube through the rest
+    Thermometer(Vec<Cell>),
+    // A circle in the first cell and an arrow through the rest
+    Arrow(Vec<Cell>),
+    // A dot on the edge between two cells
+    KropkiDot(Cell, Cell, Kropki),
+    // Numbers in the ring around the board, next to their row or column
+    Sandwich(OutsideClue),
+    XSum(OutsideClue),
+    // A rule over the whole board, named above it
+    GlobalRule(GlobalRule),
+}
+
+pub trait Constraint: Debug + Send + Sync {
+    /// Groups of cells in which no number may repeat. A house with as many
+    /// cells as there are numbers holds every number exactly once.
+    fn houses(&self) -> Vec<Vec<Cell>> {
+        Vec::new()
+    }
+
+    /// Narrows the candidates of `(row, col)`, given as a bitmask with bit `n`
+    /// set for number `n`, by the rule's cell relationships. Only the other
+    /// filled cells of `grid` are considered; the value at `(row, col)` is ignored.
+    fn restrict(&self, _grid: &Grid, _row: usize, _col: usize, candidates: u32) -> u32 {
+        candidates
+    }
+
+    /// Whether the filled cell `(row, col)` already breaks the rule with the
+    /// other filled cells of `grid`. Unlike `restrict`, this doesn't look at
+    /// what the empty cells could still hold.
+    fn is_broken(&self, _grid: &Grid, _row: usize, _col: usize) -> bool {
+        false
+    }
+
+    fn decorations(&self) -> Vec<Decoration> {
+        Vec::new()
+    }
+
+    /// Pairs of filled cells that break the rule together, which the board
+    /// connects with a line
+    fn conflicts(&self, _grid: &Grid) -> Vec<(Cell, Cell)> {
+        Vec::new()
+    }
+}
+
+// The rows of every grid on the board
+#[derive(Debug)]
+pub struct Rows(pub BoardSize);
+
+impl Constraint for Rows {
+    fn houses(&self) -> Vec<Vec<Cell>> {
+        let numbers = self.0.numbers();
+        self.0
+            .grids()
+            .into_iter()
+            .flat_map(|(top, left)| {
+                (top..top + numbers).map(move |row| (left..left + numbers).map(|col| (row, col)).collect())
+            })
+            .collect()
+    }
+}
+
+// The columns of every grid on the board
+#[derive(Debug)]
+pub struct Columns(pub BoardSize);
+
+impl Constraint for Columns {
+    fn houses(&self) -> Vec<Vec<Cell>> {
+        let numbers = self.0.numbers();
+        self.0
+            .grids()
+            .into_iter()
+            .flat_map(|(top, left)| {
+                (left..left + numbers).map(move |col| (top..top + numbers).map(|row| (row, col)).collect())
+            })
+            .collect()
+    }
+}
+
+// Regions that each hold every number once: the boxes of a classic board or
+// the irregular shapes of a Jigsaw Sudoku. Holds a region index per cell, or
+// `None` for cells out of play.
+#[derive(Debug)]
+pub struct Regions(pub Vec<Vec<Option<usize>>>);
+
+impl Regions {
+    // The rectangular boxes of a classic board: 2x2, 2x3, 3x3 or 4x4. The
+    // grids of a Samurai board line up with its boxes, so a box two grids
+    // share is one region.
+    pub fn boxes(size: BoardSize) -> Self {
+        let side = size.side();
+        let (box_rows, box_cols) = size.box_dims();
+        Self(
+            (0..side)
+                .map(|row| {
+                    (0..side)
+                        .map(|col| {
+                            size.contains(row, col)
+                                .then(|| (row / box_rows) * (side / box_cols) + col / box_cols)
+                        })
+                        .collect()
+                })
+                .collect(),
+        )
+    }
+}
+
+impl Constraint for Regions {
+    fn houses(&self) -> Vec<Vec<Cell>> {
+        let count = self.0.iter().flatten().flatten().max().map_or(0, |&max| max + 1);
+        let mut houses = vec![Vec::new(); count];
+        for (row, cells) in self.0.iter().enumerate() {
+            for (col, region) in cells.iter().enumerate() {
+                if let Some(region) = region {
+                    houses[*region].push((row, col));
+                }
+            }
+        }
+        // Box indices skip the gaps of a Samurai board
+        houses.retain(|house| !house.is_empty());
+        houses
+    }
+
+    fn decorations(&self) -> Vec<Decoration> {
+        vec![Decoration::Regions(self.0.clone())]
+    }
+}
+
+// Both main diagonals hold every number once (Sudoku X)
+#[derive(Debug)]
+pub struct Diagonals(pub BoardSize);
+
+impl Constraint for Diagonals {
+    fn houses(&self) -> Vec<Vec<Cell>> {
+        let side = self.0.side();
+        vec![
+            (0..side).map(|i| (i, i)).collect(),
+            (0..side).map(|i| (i, side - 1 - i)).collect(),
+        ]
+    }
+
+    fn decorations(&self) -> Vec<Decoration> {
+        vec![Decoration::ShadedCells(self.houses().concat())]
+    }
+}
+
+/// A Killer Sudoku cage: its numbers don't repeat and add up to `sum`
+#[derive(Clone, PartialEq, Debug)]
+pub struct Cage {
+    pub cells: Vec<Cell>,
+    pub sum: u32,
+}
+
+impl Cage {
+    /// Every set of distinct numbers up to `side` that fills the cage, in
+    /// ascending order
+    pub fn combinations(&self, side: usize) -> Vec<Vec<u8>> {
+        fn extend(
+            start: u8,
+            side: u8,
+            left: usize,
+            sum: u32,
+            current: &mut Vec<u8>,
+            combinations: &mut Vec<Vec<u8>>,
+        ) {
+            if left == 0 {
+                if sum == 0 {
+                    combinations.push(current.clone());
+                }
+                return;
+            }
+            for num in start..=side {
+                if num as u32 > sum {
+                    break;
+                }
+                current.push(num);
+                extend(num + 1, side, left - 1, sum - num as u32, current, combinations);
+                current.pop();
+            }
+        }
+
+        let mut combinations = Vec::new();
+        extend(1, side as u8, self.cells.len(), self.sum, &mut Vec::new(), &mut combinations);
+        combinations
+    }
+}
+
+// The cages of a Killer Sudoku, with each cell's cage looked up by position
+#[derive(Debug)]
+pub struct Cages {
+    cages: Vec<Cage>,
+    cage_of: Vec<Vec<Option<usize>>>,
+    // The combinations of each cage as bitmasks with bit `n` set for number `n`
+    combinations: Vec<Vec<u32>>,
+    // For each cell, the cages outside it that lie within one of its houses.
+    // Numbers such a cage must contain can't go in the cell.
+    locking_cages: Vec<Vec<Vec<usize>>>,
+}
+
+impl Cages {
+    /// `houses` are those of the other rules, such as the rows, columns and
+    /// regions, which may be irregular
+    pub fn new(size: BoardSize, cages: Vec<Cage>, houses: &[Vec<Cell>]) -> Self {
+        let side = size.side();
+        let mut cage_of = vec![vec![None; side]; side];
+        for (index, cage) in cages.iter().enumerate() {
+            for &(row, col) in &cage.cells {
+                cage_of[row][col] = Some(index);
+            }
+        }
+        let combinations = cages
+            .iter()
+            .map(|cage| {
+                cage.combinations(size.numbers())
+                    .into_iter()
+                    .map(|numbers| numbers.iter().fold(0, |mask, num| mask | 1 << num))
+                    .collect()
+            })
+            .collect();
+        let mut locking_cages: Vec<Vec<Vec<usize>>> = vec![vec![Vec::new(); side]; side];
+        for (index, cage) in cages.iter().enumerate() {
+            for house in houses.iter().filter(|house| cage.cells.iter().all(|cell| house.contains(cell))) {
+                for &(row, col) in house {
+                    let locking = &mut locking_cages[row][col];
+                    if cage_of[row][col] != Some(index) && !locking.contains(&index) {
+                        locking.push(index);
+                    }
+                }
+            }
+        }
+
+        Self {
+            cages,
+            cage_of,
+            combinations,
+            locking_cages,
+        }
+    }
+
+    // Union of the combinations of a cage that contain every number already
+    // placed in it, ignoring the cell at `skip`
+    fn possible_numbers(&self, grid: &Grid, index: usize, skip: Cell) -> (u32, u32) {
+        let used = self.cages[index]
+            .cells
+            .iter()
+            .filter(|&&cell| cell != skip)
+            .filter_map(|&(r, c)| grid[r][c])
+            .fold(0u32, |mask, num| mask | 1 << num);
+        let (possible, required) = self.combinations[index]
+            .iter()
+            .filter(|&&combination| combination & used == used)
+            .fold((0, u32::MAX), |(possible, required), &combination| {
+                (possible | combination, required & combination)
+            });
+        (possible, required)
+    }
+}
+
+impl Constraint for Cages {
+    fn houses(&self) -> Vec<Vec<Cell>> {
+        self.cages.iter().map(|cage| cage.cells.clone()).collect()
+    }
+
+    // Keeps the numbers of the cell's cage combinations that contain every
+    // number already placed in the cage, minus the numbers that cages within
+    // the cell's row, column or box must hold
+    fn restrict(&self, grid: &Grid, row: usize, col: usize, mut candidates: u32) -> u32 {
+        if let Some(index) = self.cage_of[row][col] {
+            let (possible, _) = self.possible_numbers(grid, index, (row, col));
+            candidates &= possible;
+        }
+
+        for &index in &self.locking_cages[row][col] {
+            let (possible, required) = self.possible_numbers(grid, index, (row, col));
+            if possible != 0 {
+                candidates &= !required;
+            }
+        }
+
+        candidates
+    }
+
+    fn decorations(&self) -> Vec<Decoration> {
+        self.cages.iter().cloned().map(Decoration::Cage).collect()
+    }
+}
+
+// Bitmask of the numbers from `low` to `high`, empty when `low > high`
+fn range_mask(low: i32, high: i32) -> u32 {
+    (low.max(1)..=high.min(31)).fold(0, |mask, num| mask | 1 << num)
+}
+
+// Numbers strictly increase from the bulb, the first cell, along the tube
+#[derive(Debug)]
+pub struct Thermometer(pub Vec<Cell>);
+
+impl Constraint for Thermometer {
+    fn restrict(&self, grid: &Grid, row: usize, col: usize, candidates: u32) -> u32 {
+        let Some(index) = self.0.iter().position(|&cell| cell == (row, col)) else {
+            return candidates;
+        };
+        let side = grid.len() as i32;
+        let index = index as i32;
+
+        // Each step along the tube needs at least one more than the step before
+        let mut low = index + 1;
+        let mut high = side - (self.0.len() as i32 - 1 - index);
+        for (other, &(r, c)) in self.0.iter().enumerate() {
+            if let Some(num) = grid[r][c] {
+                let distance = other as i32 - index;
+                if distance < 0 {
+                    low = low.max(num as i32 - distance);
+                } else if distance > 0 {
+                    high = high.min(num as i32 - distance);
+                }
+            }
+        }
+
+        candidates & range_mask(low, high)
+    }
+
+    // A filled cell out of order with another filled cell of the tube
+    fn is_broken(&self, grid: &Grid, row: usize, col: usize) -> bool {
+        let (Some(index), Some(num)) = (self.0.iter().position(|&cell| cell == (row, col)), grid[row][col]) else {
+            return false;
+        };
+        self.0.iter().enumerate().any(|(other, &(r, c))| {
+            grid[r][c].is_some_and(|value| (other < index && value >= num) || (other > index && value <= num))
+        })
+    }
+
+    fn decorations(&self) -> Vec<Decoration> {
+        vec![Decoration::Thermometer(self.0.clone())]
+    }
+}
+
+// The number in the circle, the first cell, is the sum of the numbers along
+// the arrow, which may repeat
+#[derive(Debug)]
+pub struct Arrow(pub Vec<Cell>);
+
+impl Constraint for Arrow {
+    fn restrict(&self, grid: &Grid, row: usize, col: usize, candidates: u32) -> u32 {
+        let Some(index) = self.0.iter().position(|&cell| cell == (row, col)) else {
+            return candidates;
+        };
+        let side = grid.len() as i32;
+
+        // Sum and number of empty cells along the shaft, not counting this cell
+        let (mut shaft_sum, mut shaft_empty) = (0, 0);
+        for &(r, c) in self.0[1..].iter().filter(|&&cell| cell != (row, col)) {
+            match grid[r][c] {
+                Some(num) => shaft_sum += num as i32,
+                None => shaft_empty += 1,
+            }
+        }
+
+        if index == 0 {
+            return candidates & range_mask(shaft_sum + shaft_empty, shaft_sum + shaft_empty * side);
+        }
+        let (circle_row, circle_col) = self.0[0];
+        match grid[circle_row][circle_col] {
+            // This cell takes what the circle leaves after the rest of the shaft
+            Some(circle) => {
+                let rest = circle as i32 - shaft_sum;
+                candidates & range_mask(rest - shaft_empty * side, rest - shaft_empty)
+            }
+            None => candidates & range_mask(1, side - shaft_sum - shaft_empty),
+        }
+    }
+
+    // The shaft already adds up to more than the circle, or is full and
+    // misses it
+    fn is_broken(&self, grid: &Grid, row: usize, col: usize) -> bool {
+        if grid[row][col].is_none() || !self.0.contains(&(row, col)) {
+            return false;
+        }
+        let (circle_row, circle_col) = self.0[0];
+        let Some(circle) = grid[circle_row][circle_col] else {
+            return false;
+        };
+        let shaft: Vec<u8> = self.0[1..].iter().filter_map(|&(r, c)| grid[r][c]).collect();
+        let sum: u32 = shaft.iter().map(|&num| num as u32).sum();
+        sum > circle as u32 || (shaft.len() == self.0.len() - 1 && sum != circle as u32)
+    }
+
+    fn decorations(&self) -> Vec<Decoration> {
+        vec![Decoration::Arrow(self.0.clone())]
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Kropki {
+    // The two numbers are consecutive
+    White,
+    // One number is double the other
+    Black,
+}
+
+#[derive(Debug)]
+pub struct KropkiDot(pub Cell, pub Cell, pub Kropki);
+
+impl KropkiDot {
+    // A dot sits on the edge between two orthogonally adjacent cells
+    pub fn is_adjacent((row, col): Cell, (other_row, other_col): Cell) -> bool {
+        row.abs_diff(other_row) + col.abs_diff(other_col) == 1
+    }
+}
+
+impl Constraint for KropkiDot {
+    fn restrict(&self, grid: &Grid, row: usize, col: usize, candidates: u32) -> u32 {
+        let partner = if self.0 == (row, col) {
+            self.1
+        } else if self.1 == (row, col) {
+            self.0
+        } else {
+            return candidates;
+        };
+        let side = grid.len() as u32;
+
+        let allowed = (1..=side).filter(|&num| match (grid[partner.0][partner.1], self.2) {
+            (Some(other), Kropki::White) => num.abs_diff(other as u32) == 1,
+            (Some(other), Kropki::Black) => num == 2 * other as u32 || 2 * num == other as u32,
+            (None, Kropki::White) => true,
+            (None, Kropki::Black) => 2 * num <= side || num % 2 == 0,
+        });
+        candidates & allowed.fold(0, |mask, num| mask | 1 << num)
+    }
+
+    fn is_broken(&self, grid: &Grid, row: usize, col: usize) -> bool {
+        if self.0 != (row, col) && self.1 != (row, col) {
+            return false;
+        }
+        let (Some(first), Some(second)) = (grid[self.0.0][self.0.1], grid[self.1.0][self.1.1]) else {
+            return false;
+        };
+        match self.2 {
+            Kropki::White => first.abs_diff(second) != 1,
+            Kropki::Black => first != 2 * second && second != 2 * first,
+        }
+    }
+
+    fn decorations(&self) -> Vec<Decoration> {
+        vec![Decoration::KropkiDot(self.0, self.1, self.2)]
+    }
+}
+
+/// The side of the board an outside clue sits on
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Edge {
+    Top,
+    Bottom,
+    Left,
+    Right,
+}
+
+/// A sum written outside the board for the row or column `index`, read from
+/// `edge` inwards
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct OutsideClue {
+    pub edge: Edge,
+    pub index: usize,
+    pub sum: u32,
+}
+
+impl OutsideClue {
+    // The cells of the clue's row or column, starting next to the clue
+    pub fn line(&self, side: usize) -> Vec<Cell> {
+        let index = self.index;
+        match self.edge {
+            Edge::Top => (0..side).map(|row| (row, index)).collect(),
+            Edge::Bottom => (0..side).rev().map(|row| (row, index)).collect(),
+            Edge::Left => (0..side).map(|col| (index, col)).collect(),
+            Edge::Right => (0..side).rev().map(|col| (index, col)).collect(),
+        }
+    }
+}
+
+// What the solver knows about the line of an outside clue while narrowing the
+// candidates of one of its cells: the other cells' values, with prefix sums
+// of the known values and counts of the other empty cells
+struct LineState {
+    position: usize,
+    values: Vec<Option<u8>>,
+    sums: Vec<i32>,
+    empty: Vec<i32>,
+}
+
+impl LineState {
+    fn new(grid: &Grid, line: &[Cell], row: usize, col: usize) -> Option<Self> {
+        let position = line.iter().position(|&cell| cell == (row, col))?;
+        let values: Vec<Option<u8>> = line
+            .iter()
+            .enumerate()
+            .map(|(index, &(r, c))| if index == position { None } else { grid[r][c] })
+            .collect();
+        let mut sums = vec![0; values.len() + 1];
+        let mut empty = vec![0; values.len() + 1];
+        for (index, value) in values.iter().enumerate() {
+            sums[index + 1] = sums[index] + value.map_or(0, |num| num as i32);
+            empty[index + 1] = empty[index] + (value.is_none() && index != position) as i32;
+        }
+        Some(Self {
+            position,
+            values,
+            sums,
+            empty,
+        })
+    }
+
+    // Sum of the known values and number of other empty cells in `start..end`
+    fn span(&self, start: usize, end: usize) -> (i32, i32) {
+        (self.sums[end] - self.sums[start], self.empty[end] - self.empty[start])
+    }
+}
+
+// Bounds on the sum of `count` distinct numbers from `low` to `high`
+fn sum_bounds(count: i32, low: i32, high: i32) -> (i32, i32) {
+    let min = (0..count).map(|step| low + step).sum();
+    let max = (0..count).map(|step| high - step).sum();
+    (min, max)
+}
+
+// The numbers between the 1 and the largest number of a row or column add up
+// to the clue. Only the top and left clues are given, as a sandwich reads the
+// same from both ends.
+#[derive(Debug)]
+pub struct Sandwich(pub OutsideClue);
+
+impl Sandwich {
+    pub fn from_solution(solution: &Grid, edge: Edge, index: usize) -> Self {
+        let side = solution.len();
+        let mut clue = OutsideClue { edge, index, sum: 0 };
+        let line: Vec<u8> = clue
+            .line(side)
+            .into_iter()
+            .filter_map(|(row, col)| solution[row][col])
+            .collect();
+        let crusts: Vec<usize> = (0..line.len())
+            .filter(|&index| line[index] == 1 || line[index] as usize == side)
+            .collect();
+        if let [start, end] = crusts[..] {
+            clue.sum = line[start + 1..end].iter().map(|&num| num as u32).sum();
+        }
+        Self(clue)
+    }
+}
+
+impl Constraint for Sandwich {
+    // Tries every placement of the two crusts the filled cells leave open and
+    // keeps the numbers this cell can take in any of them
+    fn restrict(&self, grid: &Grid, row: usize, col: usize, candidates: u32) -> u32 {
+        let side = grid.len();
+        let Some(state) = LineState::new(grid, &self.0.line(side), row, col) else {
+            return candidates;
+        };
+        let high = side as i32;
+        let spots = |crust: u8| match state.values.iter().position(|&value| value == Some(crust)) {
+            Some(index) => vec![index],
+            None => (0..side).filter(|&index| state.values[index].is_none()).collect(),
+        };
+
+        let mut allowed = 0;
+        for first in spots(1) {
+            for last in spots(side as u8) {
+                if first == last {
+                    continue;
+                }
+                let (start, end) = (first.min(last) + 1, first.max(last));
+                let (known, empty) = state.span(start, end);
+                let left = self.0.sum as i32 - known;
+                let (min, max) = sum_bounds(empty, 2, high - 1);
+                let position = state.position;
+
+                if (start..end).contains(&position) {
+                    allowed |= range_mask((left - max).max(2), (left - min).min(high - 1));
+                } else if (min..=max).contains(&left) {
+                    allowed |= if position == first {
+                        1 << 1
+                    } else if position == last {
+                        1 << side
+                    } else {
+                        range_mask(2, high - 1)
+                    };
+                }
+            }
+        }
+        candidates & allowed
+    }
+
+    // With both crusts placed, the filling already adds up to more than the
+    // clue, or is full and misses it
+    fn is_broken(&self, grid: &Grid, row: usize, col: usize) -> bool {
+        let side = grid.len();
+        let values: Vec<Option<u8>> = self.0.line(side).into_iter().map(|(r, c)| grid[r][c]).collect();
+        let crust = |num: usize| values.iter().position(|&value| value.map(usize::from) == Some(num));
+        let (Some(one), Some(top)) = (crust(1), crust(side)) else {
+            return false;
+        };
+        let (start, end) = (one.min(top), one.max(top));
+        let Some(position) = self.0.line(side).iter().position(|&cell| cell == (row, col)) else {
+            return false;
+        };
+        if grid[row][col].is_none() || !(start..=end).contains(&position) {
+            return false;
+        }
+        let filling = &values[start + 1..end];
+        let sum: u32 = filling.iter().flatten().map(|&num| num as u32).sum();
+        sum > self.0.sum || (filling.iter().all(Option::is_some) && sum != self.0.sum)
+    }
+
+    fn decorations(&self) -> Vec<Decoration> {
+        vec![Decoration::Sandwich(self.0)]
+    }
+}
+
+// The first number of a row or column, read from the clue, says how many
+// numbers from the clue's end add up to the clue
+#[derive(Debug)]
+pub struct XSum(pub OutsideClue);
+
+impl XSum {
+    pub fn from_solution(solution: &Grid, edge: Edge, index: usize) -> Self {
+        let mut clue = OutsideClue { edge, index, sum: 0 };
+        let line: Vec<u8> = clue
+            .line(solution.len())
+            .into_iter()
+            .filter_map(|(row, col)| solution[row][col])
+            .collect();
+        if let Some(&count) = line.first() {
+            clue.sum = line.iter().take(count as usize).map(|&num| num as u32).sum();
+        }
+        Self(clue)
+    }
+}
+
+impl Constraint for XSum {
+    // Tries every count the first cell may hold and keeps the numbers this
+    // cell can take with any of them
+    fn restrict(&self, grid: &Grid, row: usize, col: usize, candidates: u32) -> u32 {
+        let side = grid.len();
+        let Some(state) = LineState::new(grid, &self.0.line(side), row, col) else {
+            return candidates;
+        };
+        let high = side as i32;
+        let counts = match state.values[0] {
+            Some(count) => count..=count,
+            None => 1..=side as u8,
+        };
+
+        let mut allowed = 0;
+        for count in counts {
+            let count_cells = count as usize;
+            let (known, empty) = state.span(1, count_cells);
+            let left = self.0.sum as i32 - count as i32 - known;
+            let (min, max) = sum_bounds(empty, 1, high);
+            let position = state.position;
+
+            if position == 0 {
+                if (min..=max).contains(&left) {
+                    allowed |= 1 << count;
+                }
+            } else if position < count_cells {
+                allowed |= range_mask(left - max, left - min) & !(1 << count);
+            } else if (min..=max).contains(&left) {
+                allowed |= range_mask(1, high);
+            }
+        }
+        candidates & allowed
+    }
+
+    // With the count placed, the counted numbers already add up to more than
+    // the clue, or are all placed and miss it
+    fn is_broken(&self, grid: &Grid, row: usize, col: usize) -> bool {
+        let line = self.0.line(grid.len());
+        let Some(count) = grid[line[0].0][line[0].1] else {
+            return false;
+        };
+        let counted = &line[..count as usize];
+        if grid[row][col].is_none() || !counted.contains(&(row, col)) {
+            return false;
+        }
+        let values: Vec<u8> = counted.iter().filter_map(|&(r, c)| grid[r][c]).collect();
+        let sum: u32 = values.iter().map(|&num| num as u32).sum();
+        sum > self.0.sum || (values.len() == counted.len() && sum != self.0.sum)
+    }
+
+    fn decorations(&self) -> Vec<Decoration> {
+        vec![Decoration::XSum(self.0)]
+    }
+}
+
+/// Rules that apply across the whole board on top of any variant
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum GlobalRule {
+    // Cells a chess knight's move apart hold different numbers
+    AntiKnight,
+    // Cells a chess king's move apart, diagonals included, hold different numbers
+    AntiKing,
+    // Orthogonally adjacent cells don't hold consecutive numbers
+    NonConsecutive,
+}
+
+impl GlobalRule {
+    pub fn all() -> [GlobalRule; 3] {
+        [GlobalRule::AntiKnight, GlobalRule::AntiKing, GlobalRule::NonConsecutive]
+    }
+
+    fn offsets(&self) -> &'static [(isize, isize)] {
+        match self {
+            GlobalRule::AntiKnight => &[
+                (-2, -1),
+                (-2, 1),
+                (-1, -2),
+                (-1, 2),
+                (1, -2),
+                (1, 2),
+                (2, -1),
+                (2, 1),
+            ],
+            GlobalRule::AntiKing => &[
+                (-1, -1),
+                (-1, 0),
+                (-1, 1),
+                (0, -1),
+                (0, 1),
+                (1, -1),
+                (1, 0),
+                (1, 1),
+            ],
+            GlobalRule::NonConsecutive => &[(-1, 0), (0, -1), (0, 1), (1, 0)],
+        }
+    }
+
+    // The cells on the board the rule relates to (row, col)
+    fn neighbors(&self, side: usize, row: usize, col: usize) -> impl Iterator<Item = Cell> {
+        self.offsets().iter().filter_map(move |&(dr, dc)| {
+            let (r, c) = (row.checked_add_signed(dr)?, col.checked_add_signed(dc)?);
+            (r < side && c < side).then_some((r, c))
+        })
+    }
+
+    // Numbers a cell can't hold next to a cell holding `num`
+    fn excluded(&self, num: u8) -> u32 {
+        match self {
+            GlobalRule::AntiKnight | GlobalRule::AntiKing => 1 << num,
+            GlobalRule::NonConsecutive => (1 << (num - 1) | 1 << (num + 1)) & !1,
+        }
+    }
+}
+
+impl Constraint for GlobalRule {
+    fn decorations(&self) -> Vec<Decoration> {
+        vec![Decoration::GlobalRule(*self)]
+    }
+
+    fn restrict(&self, grid: &Grid, row: usize, col: usize, candidates: u32) -> u32 {
+        self.neighbors(grid.len(), row, col)
+            .filter_map(|(r, c)| grid[r][c])
+            .fold(candidates, |candidates, num| candidates & !self.excluded(num))
+    }
+
+    fn is_broken(&self, grid: &Grid, row: usize, col: usize) -> bool {
+        grid[row][col].is_some_and(|num| self.restrict(grid, row, col, 1 << num) == 0)
+    }
+
+    fn conflicts(&self, grid: &Grid) -> Vec<(Cell, Cell)> {
+        let side = grid.len();
+        let mut conflicts = Vec::new();
+        for row in 0..side {
+            for col in 0..side {
+                let Some(num) = grid[row][col] else {
+                    continue;
+                };
+                // Each pair is reported once, from its first cell
+                for (r, c) in self.neighbors(side, row, col) {
+                    if (r, c) > (row, col)
+                        && grid[r][c].is_some_and(|other| self.excluded(num) & 1 << other != 0)
+                    {
+                        conflicts.push(((row, col), (r, c)));
+                    }
+                }
+            }
+        }
+        conflicts
+    }
+}
+
+/// The full set of constraints of a puzzle, with its houses indexed by cell.
+/// Cloning is cheap, and two sets are equal when they are the same set.
+#[derive(Clone, Debug)]
+pub struct Rules(Arc<RulesInner>);
+
+#[derive(Debug)]
+struct RulesInner {
+    size: BoardSize,
+    constraints: Vec<Box<dyn Constraint>>,
+    houses: Vec<Vec<Cell>>,
+    // Indices into `houses` for each cell
+    cell_houses: Vec<Vec<Vec<usize>>>,
+}
+
+impl PartialEq for Rules {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Rules {
+    pub fn new(size: BoardSize, constraints: Vec<Box<dyn Constraint>>) -> Self {
+        let side = size.side();
+        let houses: Vec<Vec<Cell>> = constraints
+            .iter()
+            .flat_map(|constraint| constraint.houses())
+            .collect();
+
+        let mut cell_houses = vec![vec![Vec::new(); side]; side];
+        for (house_idx, house) in houses.iter().enumerate() {
+            for &(row, col) in house {
+                cell_houses[row][col].push(house_idx);
+            }
+        }
+
+        Self(Arc::new(RulesInner {
+            size,
+            constraints,
+            houses,
+            cell_houses,
+        }))
+    }
+
+    // Rows, columns and boxes
+    pub fn classic(size: BoardSize) -> Self {
+        Self::new(size, Self::classic_constraints(size))
+    }
+
+    pub fn classic_constraints(size: BoardSize) -> Vec<Box<dyn Constraint>> {
+        vec![Box::new(Rows(size)), Box::new(Columns(size)), Box::new(Regions::boxes(size))]
+    }
+
+    pub fn size(&self) -> BoardSize {
+        self.0.size
+    }
+
+    pub fn houses(&self) -> &[Vec<Cell>] {
+        &self.0.houses
+    }
+
+    // Indices into `houses()` of the houses containing (row, col)
+    pub fn houses_of(&self, row: usize, col: usize) -> &[usize] {
+        &self.0.cell_houses[row][col]
+    }
+
+    // Candidates of (row, col) left by every constraint's cell relationships
+    pub fn restrict(&self, grid: &Grid, row: usize, col: usize, candidates: u32) -> u32 {
+        self.0
+            .constraints
+            .iter()
+            .fold(candidates, |candidates, constraint| {
+                constraint.restrict(grid, row, col, candidates)
+            })
+    }
+
+    // Whether `num` can go at (row, col) given the other filled cells
+    pub fn allows(&self, grid: &Grid, row: usize, col: usize, num: u8) -> bool {
+        !self.repeats(grid, row, col, num) && self.restrict(grid, row, col, 1 << num) != 0
+    }
+
+    // Whether the number at (row, col) repeats in a house or breaks a rule
+    // with the other filled cells. A number the rules merely rule out, such
+    // as one a cage elsewhere must hold, doesn't count.
+    pub fn is_broken(&self, grid: &Grid, row: usize, col: usize) -> bool {
+        grid[row][col].is_some_and(|num| {
+            self.repeats(grid, row, col, num)
+                || self.0.constraints.iter().any(|constraint| constraint.is_broken(grid, row, col))
+        })
+    }
+
+    fn repeats(&self, grid: &Grid, row: usize, col: usize, num: u8) -> bool {
+        self.houses_of(row, col).iter().any(|&house| {
+            self.0.houses[house]
+                .iter()
+                .any(|&(r, c)| (r, c) != (row, col) && grid[r][c] == Some(num))
+        })
+    }
+
+    // Whether every filled cell is allowed by the rules
+    pub fn is_consistent(&self, grid: &Grid) -> bool {
+        grid.iter().enumerate().all(|(row, cells)| {
+            cells
+                .iter()
+                .enumerate()
+                .all(|(col, cell)| cell.is_none_or(|num| self.allows(grid, row, col, num)))
+        })
+    }
+
+    pub fn decorations(&self) -> Vec<Decoration> {
+        self.0
+            .constraints
+            .iter()
+            .flat_map(|constraint| constraint.decorations())
+            .collect()
+    }
+
+    // Pairs of filled cells breaking a rule together
+    pub fn conflicts(&self, grid: &Grid) -> Vec<(Cell, Cell)> {
+        self.0
+            .constraints
+            .iter()
+            .flat_map(|constraint| constraint.conflicts(grid))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NINE: BoardSize = BoardSize::Nine;
+
+    fn grid_with(size: BoardSize, numbers: &[(Cell, u8)]) -> Grid {
+        let mut grid = size.empty_grid();
+        for &((row, col), num) in numbers {
+            grid[row][col] = Some(num);
+        }
+        grid
+    }
+
+    // The candidates of (row, col) as a sorted list
+    fn candidates(constraint: &dyn Constraint, grid: &Grid, (row, col): Cell) -> Vec<u8> {
+        let mask = constraint.restrict(grid, row, col, ((1 << 10) - 1) & !1);
+        (1..=9).filter(|num| mask & 1 << num != 0).collect()
+    }
+
+    #[test]
+    fn classic_houses_fit_every_size() {
+        for size in BoardSize::all() {
+            let rules = Rules::classic(size);
+            let grids = size.grids().len();
+            // Samurai grids share a box with the center grid
+            let boxes = grids * size.numbers() - (grids - 1);
+            assert_eq!(rules.houses().len(), grids * 2 * size.numbers() + boxes, "{size:?}");
+            assert!(rules.houses().iter().all(|house| house.len() == size.numbers()), "{size:?}");
+            for (row, col) in size.cells() {
+                let houses = rules.houses_of(row, col).len();
+                assert!(houses == 3 || size == BoardSize::Samurai && houses == 5, "{size:?} ({row}, {col})");
+            }
+        }
+    }
+
+    #[test]
+    fn six_by_six_boxes_are_two_rows_of_three() {
+        let Regions(regions) = Regions::boxes(BoardSize::Six);
+        assert_eq!(regions[0][..], [Some(0), Some(0), Some(0), Some(1), Some(1), Some(1)]);
+        assert_eq!(regions[1][0], Some(0));
+        assert_eq!(regions[2][0], Some(2));
+        assert_eq!(regions[5][5], Some(5));
+    }
+
+    #[test]
+    fn repeats_in_a_house_break_the_rules() {
+        let rules = Rules::classic(NINE);
+        let grid = grid_with(NINE, &[((0, 0), 5), ((0, 8), 5), ((4, 4), 5), ((8, 8), 1)]);
+        assert!(rules.is_broken(&grid, 0, 0));
+        assert!(rules.is_broken(&grid, 0, 8));
+        assert!(!rules.is_broken(&grid, 4, 4));
+        assert!(!rules.is_broken(&grid, 8, 8));
+        assert!(!rules.is_broken(&grid, 1, 1));
+        assert!(!rules.is_consistent(&grid));
+        assert!(!rules.allows(&grid, 4, 0, 5));
+        assert!(rules.allows(&grid, 4, 0, 6));
+    }
+
+    #[test]
+    fn global_rules_restrict_their_neighbors() {
+        let grid = grid_with(NINE, &[((4, 4), 5)]);
+        let rules = [GlobalRule::AntiKnight, GlobalRule::AntiKing, GlobalRule::NonConsecutive];
+        let (knight, king, consecutive) = (&rules[0], &rules[1], &rules[2]);
+
+        assert!(!candidates(knight, &grid, (2, 3)).contains(&5));
+        assert!(candidates(knight, &grid, (3, 3)).contains(&5));
+        assert!(!candidates(king, &grid, (3, 3)).contains(&5));
+        assert_eq!(candidates(consecutive, &grid, (4, 5)), [1, 2, 3, 5, 7, 8, 9]);
+        assert_eq!(candidates(consecutive, &grid, (5, 5)).len(), 9);
+
+        let broken = grid_with(NINE, &[((4, 4), 5), ((4, 5), 6)]);
+        assert!(consecutive.is_broken(&broken, 4, 4));
+        assert!(!king.is_broken(&broken, 4, 4));
+        assert_eq!(consecutive.conflicts(&broken), [((4, 4), (4, 5))]);
+    }
+
+    fn cages(houses: &[Box<dyn Constraint>], cages: Vec<Cage>) -> Cages {
+        let houses: Vec<Vec<Cell>> = houses.iter().flat_map(|constraint| constraint.houses()).collect();
+        Cages::new(NINE, cages, &houses)
+    }
+
+    #[test]
+    fn cages_lock_their_numbers_out_of_shared_houses() {
+        // A sum of 3 over two cells needs a 1 and a 2
+        let cages = cages(
+            &Rules::classic_constraints(NINE),
+            vec![Cage { cells: vec![(0, 0), (0, 1)], sum: 3 }],
+        );
+        let empty = NINE.empty_grid();
+
+        assert_eq!(candidates(&cages, &empty, (0, 0)), [1, 2]);
+        assert_eq!(candidates(&cages, &empty, (0, 5)), [3, 4, 5, 6, 7, 8, 9]);
+        assert_eq!(candidates(&cages, &empty, (2, 2)), [3, 4, 5, 6, 7, 8, 9]);
+        // Column 0 holds only one of the cage's cells
+        assert_eq!(candidates(&cages, &empty, (5, 0)).len(), 9);
+
+        // Repeats are left to the row; the cage only keeps the sum reachable
+        let started = grid_with(NINE, &[((0, 0), 1)]);
+        assert_eq!(candidates(&cages, &started, (0, 1)), [1, 2]);
+    }
+
+    #[test]
+    fn cages_lock_their_numbers_out_of_irregular_regions() {
+        // Region 0 takes (1, 3) from region 1 and gives it (2, 2), so the cage
+        // shares only the irregular region, not a row, column or box
+        let Regions(mut regions) = Regions::boxes(NINE);
+        regions[1][3] = Some(0);
+        regions[2][2] = Some(1);
+        let cage = Cage { cells: vec![(0, 0), (1, 3)], sum: 3 };
+        let jigsaw: Vec<Box<dyn Constraint>> = vec![Box::new(Rows(NINE)), Box::new(Columns(NINE)), Box::new(Regions(regions))];
+        let jigsaw = cages(&jigsaw, vec![cage.clone()]);
+        let classic = cages(&Rules::classic_constraints(NINE), vec![cage]);
+        let empty = NINE.empty_grid();
+
+        assert_eq!(candidates(&jigsaw, &empty, (2, 1)), [3, 4, 5, 6, 7, 8, 9]);
+        assert_eq!(candidates(&classic, &empty, (2, 1)).len(), 9);
+        // (2, 2) left region 0, so the cage no longer locks it
+        assert_eq!(candidates(&jigsaw, &empty, (2, 2)).len(), 9);
+        assert_eq!(candidates(&classic, &empty, (2, 2)).len(), 9);
+    }
+
+    #[test]
+    fn kropki_dots_pair_their_cells() {
+        let white = KropkiDot((0, 0), (0, 1), Kropki::White);
+        let black = KropkiDot((0, 0), (0, 1), Kropki::Black);
+        let empty = NINE.empty_grid();
+
+        assert_eq!(candidates(&white, &empty, (0, 0)).len(), 9);
+        // Without a partner, only numbers that have a half or a double on the board
+        assert_eq!(candidates(&black, &empty, (0, 0)), [1, 2, 3, 4, 6, 8]);
+        assert_eq!(candidates(&white, &grid_with(NINE, &[((0, 1), 5)]), (0, 0)), [4, 6]);
+        assert_eq!(candidates(&black, &grid_with(NINE, &[((0, 1), 4)]), (0, 0)), [2, 8]);
+        assert!(candidates(&black, &grid_with(NINE, &[((0, 1), 7)]), (0, 0)).is_empty());
+        assert_eq!(candidates(&white, &empty, (1, 1)).len(), 9);
+
+        let pair = |first, second| grid_with(NINE, &[((0, 0), first), ((0, 1), second)]);
+        assert!(!white.is_broken(&pair(3, 4), 0, 0));
+        assert!(white.is_broken(&pair(3, 5), 0, 1));
+        assert!(!black.is_broken(&pair(6, 3), 0, 0));
+        assert!(black.is_broken(&pair(3, 5), 0, 1));
+        assert!(!black.is_broken(&pair(3, 5), 1, 1));
+    }
+
+    #[test]
+    fn thermometers_rise_from_the_bulb() {
+        let thermo = Thermometer(vec![(0, 0), (0, 1), (0, 2)]);
+        let empty = NINE.empty_grid();
+
+        assert_eq!(candidates(&thermo, &empty, (0, 0)), [1, 2, 3, 4, 5, 6, 7]);
+        assert_eq!(candidates(&thermo, &empty, (0, 1)), [2, 3, 4, 5, 6, 7, 8]);
+        assert_eq!(candidates(&thermo, &empty, (0, 2)), [3, 4, 5, 6, 7, 8, 9]);
+        let tip = grid_with(NINE, &[((0, 2), 5)]);
+        assert_eq!(candidates(&thermo, &tip, (0, 0)), [1, 2, 3]);
+        assert_eq!(candidates(&thermo, &tip, (0, 1)), [2, 3, 4]);
+
+        let out_of_order = grid_with(NINE, &[((0, 0), 4), ((0, 2), 3)]);
+        assert!(thermo.is_broken(&out_of_order, 0, 0));
+        assert!(thermo.is_broken(&out_of_order, 0, 2));
+        assert!(!thermo.is_broken(&out_of_order, 0, 1));
+        assert!(!thermo.is_broken(&grid_with(NINE, &[((0, 0), 2), ((0, 2), 3)]), 0, 2));
+    }
+
+    #[test]
+    fn arrows_sum_to_their_circle() {
+        let arrow = Arrow(vec![(0, 0), (0, 1), (0, 2)]);
+        let empty = NINE.empty_grid();
+
+        assert_eq!(candidates(&arrow, &empty, (0, 0)), [2, 3, 4, 5, 6, 7, 8, 9]);
+        assert_eq!(candidates(&arrow, &empty, (0, 1)), [1, 2, 3, 4, 5, 6, 7, 8]);
+        assert_eq!(candidates(&arrow, &grid_with(NINE, &[((0, 0), 5)]), (0, 1)), [1, 2, 3, 4]);
+        assert_eq!(candidates(&arrow, &grid_with(NINE, &[((0, 0), 5), ((0, 2), 3)]), (0, 1)), [2]);
+        assert_eq!(candidates(&arrow, &grid_with(NINE, &[((0, 1), 3), ((0, 2), 4)]), (0, 0)), [7]);
+
+        assert!(arrow.is_broken(&grid_with(NINE, &[((0, 0), 5), ((0, 1), 6)]), 0, 1));
+        assert!(arrow.is_broken(&grid_with(NINE, &[((0, 0), 5), ((0, 1), 2), ((0, 2), 2)]), 0, 0));
+        assert!(!arrow.is_broken(&grid_with(NINE, &[((0, 0), 5), ((0, 1), 2), ((0, 2), 3)]), 0, 0));
+        assert!(!arrow.is_broken(&grid_with(NINE, &[((0, 1), 6), ((0, 2), 7)]), 0, 1));
+    }
+}
@@ -0,0 +1,209 @@
+//! Printed digit recognition for photo import
+//!
+//! A cell's ink is cropped to the digit, scaled into a 16x16 glyph and fed to
+//! a small two-layer network embedded in the app. The network is trained on
+//! rendered font digits by `cargo run --release --example
+//! train_digit_classifier` and stored with 8-bit weights, one scale per layer.
+
+use std::sync::OnceLock;
+
+const CLASSIFIER: &[u8] = include_bytes!("../assets/digit_classifier.bin");
+const MAGIC: &[u8; 4] = b"PDC1";
+
+/// Side of the square glyph the network reads
+pub const GLYPH_SIDE: usize = 16;
+// Side of the box the digit is fitted into, leaving a margin around it
+const FIT_SIDE: f32 = 12.0;
+// Digits 1 to 9
+pub const CLASSES: usize = 9;
+
+pub struct Layer {
+    pub inputs: usize,
+    pub outputs: usize,
+    // Row-major, one row of `inputs` weights per output
+    pub weights: Vec<f32>,
+    pub biases: Vec<f32>,
+}
+
+impl Layer {
+    pub fn forward(&self, input: &[f32]) -> Vec<f32> {
+        self.weights
+            .chunks(self.inputs)
+            .zip(&self.biases)
+            .map(|(row, bias)| bias + row.iter().zip(input).map(|(weight, x)| weight * x).sum::<f32>())
+            .collect()
+    }
+}
+
+pub struct Network {
+    pub hidden: Layer,
+    pub output: Layer,
+}
+
+impl Network {
+    /// Probabilities of the digits 1 to 9
+    pub fn probabilities(&self, glyph: &[f32]) -> Vec<f32> {
+        let hidden: Vec<f32> = self.hidden.forward(glyph).into_iter().map(|x| x.max(0.0)).collect();
+        softmax(&self.output.forward(&hidden))
+    }
+}
+
+pub fn softmax(scores: &[f32]) -> Vec<f32> {
+    let max = scores.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+    let exps: Vec<f32> = scores.iter().map(|score| (score - max).exp()).collect();
+    let total: f32 = exps.iter().sum();
+    exps.iter().map(|value| value / total).collect()
+}
+
+/// Probabilities of the digits 1 to 9 for a cell, or `None` if it holds no
+/// digit. `ink` holds one value per pixel, from 0 for paper to 1 for ink.
+pub fn classify(ink: &[f32], width: usize, height: usize) -> Option<Vec<f32>> {
+    let glyph = glyph(ink, width, height)?;
+    Some(network()?.probabilities(&glyph))
+}
+
+/// Crops a cell's ink to the digit and scales it into the network's input,
+/// or returns `None` for an empty cell. Specks and stray grid lines along the
+/// cell's edge are left out.
+pub fn glyph(ink: &[f32], width: usize, height: usize) -> Option<Vec<f32>> {
+    let mut label = vec![usize::MAX; width * height];
+    let (mut left, mut top, mut right, mut bottom) = (width, height, 0, 0);
+    let mut ink_pixels = 0;
+
+    for start in 0..width * height {
+        if ink[start] < 0.5 || label[start] != usize::MAX {
+            continue;
+        }
+        // Flood fill one stroke
+        let mut stack = vec![start];
+        let mut pixels = Vec::new();
+        label[start] = start;
+        while let Some(index) = stack.pop() {
+            pixels.push(index);
+            let (x, y) = (index % width, index / width);
+            let neighbours = [
+                (x > 0).then(|| index - 1),
+                (x + 1 < width).then_some(index + 1),
+                (y > 0).then(|| index - width),
+                (y + 1 < height).then_some(index + width),
+            ];
+            for next in neighbours.into_iter().flatten() {
+                if ink[next] >= 0.5 && label[next] == usize::MAX {
+                    label[next] = start;
+                    stack.push(next);
+                }
+            }
+        }
+
+        let xs = pixels.iter().map(|index| index % width);
+        let ys = pixels.iter().map(|index| index / width);
+        let (min_x, max_x) = (xs.clone().min()?, xs.max()?);
+        let (min_y, max_y) = (ys.clone().min()?, ys.max()?);
+        let center_x = (min_x + max_x) as f32 / 2.0 / width as f32;
+        let center_y = (min_y + max_y) as f32 / 2.0 / height as f32;
+        let central = (0.2..=0.8).contains(&center_x) && (0.2..=0.8).contains(&center_y);
+        let speck = pixels.len() * 400 < width * height;
+        if central && !speck {
+            left = left.min(min_x);
+            right = right.max(max_x);
+            top = top.min(min_y);
+            bottom = bottom.max(max_y);
+            ink_pixels += pixels.len();
+        }
+    }
+
+    // Too little ink, or too short, to be a digit
+    if ink_pixels * 60 < width * height || (bottom + 1).saturating_sub(top) * 4 < height {
+        return None;
+    }
+
+    // Keep the aspect ratio so a 1 stays narrow
+    let (box_width, box_height) = ((right - left + 1) as f32, (bottom - top + 1) as f32);
+    let scale = FIT_SIDE / box_width.max(box_height);
+    let offset_x = (GLYPH_SIDE as f32 - box_width * scale) / 2.0;
+    let offset_y = (GLYPH_SIDE as f32 - box_height * scale) / 2.0;
+
+    // Each glyph pixel averages the cell pixels it covers, or takes the
+    // nearest one when the digit is scaled up
+    let source_range = |glyph: usize, offset: f32, start: usize, end: usize| {
+        let from = start as f32 + (glyph as f32 - offset) / scale;
+        let to = start as f32 + (glyph as f32 + 1.0 - offset) / scale;
+        if to <= start as f32 || from >= (end + 1) as f32 {
+            return None;
+        }
+        let from = (from.max(start as f32) as usize).min(end);
+        let to = (to.ceil() as usize).clamp(from + 1, end + 1);
+        Some(from..to)
+    };
+    let mut glyph = vec![0.0; GLYPH_SIDE * GLYPH_SIDE];
+    for glyph_y in 0..GLYPH_SIDE {
+        let Some(rows) = source_range(glyph_y, offset_y, top, bottom) else {
+            continue;
+        };
+        for glyph_x in 0..GLYPH_SIDE {
+            let Some(columns) = source_range(glyph_x, offset_x, left, right) else {
+                continue;
+            };
+            let count = rows.len() * columns.len();
+            let total: f32 = rows
+                .clone()
+                .flat_map(|y| columns.clone().map(move |x| ink[y * width + x]))
+                .sum();
+            glyph[glyph_y * GLYPH_SIDE + glyph_x] = total / count as f32;
+        }
+    }
+    Some(glyph)
+}
+
+#[allow(dead_code)] // Used by the classifier training example
+pub fn encode_network(network: &Network) -> Vec<u8> {
+    let mut bytes = MAGIC.to_vec();
+    for layer in [&network.hidden, &network.output] {
+        bytes.extend_from_slice(&(layer.inputs as u16).to_le_bytes());
+        bytes.extend_from_slice(&(layer.outputs as u16).to_le_bytes());
+        let scale = layer.weights.iter().fold(0.0f32, |max, weight| max.max(weight.abs())) / 127.0;
+        bytes.extend_from_slice(&scale.to_le_bytes());
+        bytes.extend(layer.weights.iter().map(|weight| (weight / scale).round() as i8 as u8));
+        for bias in &layer.biases {
+            bytes.extend_from_slice(&bias.to_le_bytes());
+        }
+    }
+    bytes
+}
+
+pub fn decode_network(bytes: &[u8]) -> Option<Network> {
+    let mut rest = bytes.strip_prefix(MAGIC)?;
+    let mut read_layer = || -> Option<Layer> {
+        let header = rest.get(..8)?;
+        let inputs = u16::from_le_bytes([header[0], header[1]]) as usize;
+        let outputs = u16::from_le_bytes([header[2], header[3]]) as usize;
+        let scale = f32::from_le_bytes([header[4], header[5], header[6], header[7]]);
+        let weights = rest
+            .get(8..8 + inputs * outputs)?
+            .iter()
+            .map(|&byte| byte as i8 as f32 * scale)
+            .collect();
+        let bias_start = 8 + inputs * outputs;
+        let biases = rest
+            .get(bias_start..bias_start + 4 * outputs)?
+            .chunks(4)
+            .map(|bytes| f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+            .collect();
+        rest = &rest[bias_start + 4 * outputs..];
+        Some(Layer {
+            inputs,
+            outputs,
+            weights,
+            biases,
+        })
+    };
+    let hidden = read_layer()?;
+    let output = read_layer()?;
+    (hidden.inputs == GLYPH_SIDE * GLYPH_SIDE && output.inputs == hidden.outputs && output.outputs == CLASSES)
+        .then_some(Network { hidden, output })
+}
+
+fn network() -> Option<&'static Network> {
+    static NETWORK: OnceLock<Option<Network>> = OnceLock::new();
+    NETWORK.get_or_init(|| decode_network(CLASSIFIER)).as_ref()
+}
@@ -0,0 +1,588 @@
+//! Import and export of f-puzzles JSON, the format SudokuPad also reads
+//!
+//! Puzzles are usually shared as lz-string compressed base64, on its own or at
+//! the end of an f-puzzles (`?load=`) or SudokuPad (`/fpuzzles`) link; raw JSON
+//! is accepted too. Cells are named `R<row>C<col>` counting from 1, and outside
+//! clues sit in row or column 0 or `size + 1`. Constraints pigsudoku can't
+//! check are reported by name rather than dropped.
+
+use crate::constraints::{
+    Arrow, Cage, Cages, Cell, Columns, Constraint, Decoration, Diagonals, Edge, GlobalRule,
+    Kropki, KropkiDot, OutsideClue, Regions, Rows, Rules, Sandwich, Thermometer, XSum,
+};
+use crate::game_logic::{BoardSize, Grid};
+use crate::lz_string;
+use serde_json::{Map, Value, json};
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum FpuzzlesError {
+    // Neither JSON nor a compressed puzzle
+    Decode,
+    // Only 4x4, 6x6, 9x9 and 16x16 boards are supported
+    Size(usize),
+    // The named entry doesn't have the expected shape
+    Malformed(String),
+    Unsupported(Vec<String>),
+}
+
+// Entries that don't affect the rules
+const METADATA: [&str; 8] = [
+    "size",
+    "grid",
+    "title",
+    "author",
+    "ruleset",
+    "solution",
+    "disabledlogic",
+    "truecandidatesoptions",
+];
+
+// Whether `text` looks like f-puzzles input rather than the text format,
+// whose grid line always contains whitespace
+pub fn is_fpuzzles(text: &str) -> bool {
+    let text = text.trim();
+    text.starts_with('{') || !text.is_empty() && !text.contains(char::is_whitespace)
+}
+
+pub fn parse_fpuzzles(text: &str) -> Result<(Rules, Grid), FpuzzlesError> {
+    let json = decode(text).ok_or(FpuzzlesError::Decode)?;
+    let puzzle: Map<String, Value> = serde_json::from_str(&json).map_err(|_| FpuzzlesError::Decode)?;
+
+    let side = puzzle.get("size").and_then(Value::as_u64).unwrap_or(9) as usize;
+    let size = BoardSize::all()
+        .into_iter()
+        .filter(|size| size.grids().len() == 1)
+        .find(|size| size.side() == side)
+        .ok_or(FpuzzlesError::Size(side))?;
+
+    let mut unsupported = Vec::new();
+    let (grid, regions) = parse_grid(size, &puzzle, &mut unsupported)?;
+    let mut constraints: Vec<Box<dyn Constraint>> = vec![
+        Box::new(Rows(size)),
+        Box::new(Columns(size)),
+        Box::new(regions),
+    ];
+    let mut cages = Vec::new();
+
+    for (key, value) in &puzzle {
+        if METADATA.contains(&key.as_str()) || is_empty(value) {
+            continue;
+        }
+        match key.as_str() {
+            // Only the two diagonals together make Sudoku X
+            "diagonal+" | "diagonal-" => {
+                let other = if key == "diagonal+" { "diagonal-" } else { "diagonal+" };
+                if puzzle.get(other).is_some_and(|other| !is_empty(other)) {
+                    if key == "diagonal+" {
+                        constraints.push(Box::new(Diagonals(size)));
+                    }
+                } else {
+                    unsupported.push(key.clone());
+                }
+            }
+            "antiknight" => constraints.push(Box::new(GlobalRule::AntiKnight)),
+            "antiking" => constraints.push(Box::new(GlobalRule::AntiKing)),
+            "nonconsecutive" => constraints.push(Box::new(GlobalRule::NonConsecutive)),
+            "killercage" => {
+                for cage in entries(key, value)? {
+                    let cells = cells(size, key, cage.get("cells"))?;
+                    // A cell belongs to one cage at most, once
+                    if cells.iter().enumerate().any(|(index, cell)| {
+                        cells[..index].contains(cell) || cages.iter().any(|cage: &Cage| cage.cells.contains(cell))
+                    }) {
+                        return Err(FpuzzlesError::Malformed(key.clone()));
+                    }
+                    match cage.get("value").and_then(number) {
+                        Some(sum) if !cells.is_empty() => cages.push(Cage { cells, sum }),
+                        _ => unsupported.push("killercage without a sum".to_string()),
+                    }
+                }
+            }
+            "thermometer" => {
+                for thermo in entries(key, value)? {
+                    for line in lines(size, key, thermo)? {
+                        constraints.push(Box::new(Thermometer(line)));
+                    }
+                }
+            }
+            "arrow" => {
+                for arrow in entries(key, value)? {
+                    let circle = cells(size, key, arrow.get("cells"))?;
+                    let [circle] = circle[..] else {
+                        unsupported.push("arrow with a multi-cell circle".to_string());
+                        continue;
+                    };
+                    for mut line in lines(size, key, arrow)? {
+                        if line[0] != circle {
+                            line.insert(0, circle);
+                        }
+                        constraints.push(Box::new(Arrow(line)));
+                    }
+                }
+            }
+            "difference" | "ratio" => {
+                let (kind, default) = if key == "difference" {
+                    (Kropki::White, 1)
+                } else {
+                    (Kropki::Black, 2)
+                };
+                for dot in entries(key, value)? {
+                    let pair = cells(size, key, dot.get("cells"))?;
+                    let value = dot.get("value").and_then(number).unwrap_or(default);
+                    match pair[..] {
+                        [first, second] if !KropkiDot::is_adjacent(first, second) => {
+                            return Err(FpuzzlesError::Malformed(key.clone()));
+                        }
+                        [first, second] if value == default => {
+                            constraints.push(Box::new(KropkiDot(first, second, kind)));
+                        }
+                        [_, _] => unsupported.push(format!("{key} {value}")),
+                        _ => return Err(FpuzzlesError::Malformed(key.clone())),
+                    }
+                }
+            }
+            "sandwichsum" | "xsum" => {
+                for clue in entries(key, value)? {
+                    let name = clue.get("cell").and_then(Value::as_str).unwrap_or_default();
+                    let Some(clue) = outside_clue(size, name, clue.get("value")) else {
+                        return Err(FpuzzlesError::Malformed(key.clone()));
+                    };
+                    if key == "sandwichsum" {
+                        constraints.push(Box::new(Sandwich(clue)));
+                    } else {
+                        constraints.push(Box::new(XSum(clue)));
+                    }
+                }
+            }
+            // Negative constraints ("no other dots" and the like), cosmetic
+            // shapes that may carry rules, and every other variant
+            _ => unsupported.push(key.clone()),
+        }
+    }
+
+    if !unsupported.is_empty() {
+        unsupported.sort();
+        unsupported.dedup();
+        return Err(FpuzzlesError::Unsupported(unsupported));
+    }
+    if !cages.is_empty() {
+        // Cages lock numbers out of the irregular regions too
+        let houses: Vec<Vec<Cell>> = constraints.iter().flat_map(|constraint| constraint.houses()).collect();
+        constraints.push(Box::new(Cages::new(size, cages, &houses)));
+    }
+    Ok((Rules::new(size, constraints), grid))
+}
+
+// The JSON of raw, compressed or linked input
+fn decode(text: &str) -> Option<String> {
+    let text = text.trim();
+    if text.starts_with('{') {
+        return Some(text.to_string());
+    }
+    let data = ["?load=", "&load=", "/fpuzzles"]
+        .iter()
+        .find_map(|marker| text.rsplit_once(marker).map(|(_, data)| data))
+        .unwrap_or(text);
+    // Links may escape the base64 symbols, or turn `+` into a space
+    let data = data
+        .replace("%2B", "+")
+        .replace("%2F", "/")
+        .replace("%3D", "=")
+        .replace(' ', "+");
+    lz_string::decompress_from_base64(&data).filter(|json| json.starts_with('{'))
+}
+
+// Givens and regions, both optional per cell. Regions default to the boxes.
+fn parse_grid(
+    size: BoardSize,
+    puzzle: &Map<String, Value>,
+    unsupported: &mut Vec<String>,
+) -> Result<(Grid, Regions), FpuzzlesError> {
+    let mut grid = size.empty_grid();
+    let Regions(mut regions) = Regions::boxes(size);
+    let Some(rows) = puzzle.get("grid") else {
+        return Ok((grid, Regions(regions)));
+    };
+    let malformed = || FpuzzlesError::Malformed("grid".to_string());
+
+    let rows = rows.as_array().filter(|rows| rows.len() == size.side()).ok_or_else(malformed)?;
+    for (row, cells) in rows.iter().enumerate() {
+        let cells = cells.as_array().filter(|cells| cells.len() == size.side()).ok_or_else(malformed)?;
+        for (col, cell) in cells.iter().enumerate() {
+            // Values the solver entered aren't part of the puzzle
+            let given = cell.get("given").and_then(Value::as_bool).unwrap_or(false);
+            if let Some(value) = cell.get("value").and_then(Value::as_u64).filter(|_| given) {
+                if value == 0 || value as usize > size.numbers() {
+                    return Err(malformed());
+                }
+                grid[row][col] = Some(value as u8);
+            }
+            match cell.get("region") {
+                None => {}
+                Some(Value::Null) => unsupported.push("cells outside any region".to_string()),
+                Some(region) => {
+                    let region = region.as_u64().filter(|&region| (region as usize) < size.numbers());
+                    regions[row][col] = Some(region.ok_or_else(malformed)? as usize);
+                }
+            }
+        }
+    }
+
+    let mut counts = vec![0; size.numbers()];
+    for region in regions.iter().flatten().flatten() {
+        counts[*region] += 1;
+    }
+    if counts.iter().any(|&count| count != size.numbers()) {
+        unsupported.push("regions of unequal size".to_string());
+    }
+    Ok((grid, Regions(regions)))
+}
+
+// False, null and empty lists are how f-puzzles writes an unused constraint
+fn is_empty(value: &Value) -> bool {
+    match value {
+        Value::Null | Value::Bool(false) => true,
+        Value::Array(items) => items.is_empty(),
+        _ => false,
+    }
+}
+
+fn entries<'a>(key: &str, value: &'a Value) -> Result<&'a [Value], FpuzzlesError> {
+    value
+        .as_array()
+        .map(Vec::as_slice)
+        .ok_or_else(|| FpuzzlesError::Malformed(key.to_string()))
+}
+
+// Sums and dot values are written either as numbers or as strings
+fn number(value: &Value) -> Option<u32> {
+    match value {
+        Value::Number(number) => number.as_u64().map(|number| number as u32),
+        Value::String(text) => text.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+fn cells(size: BoardSize, key: &str, value: Option<&Value>) -> Result<Vec<Cell>, FpuzzlesError> {
+    let malformed = || FpuzzlesError::Malformed(key.to_string());
+    value
+        .and_then(Value::as_array)
+        .ok_or_else(malformed)?
+        .iter()
+        .map(|name| {
+            name.as_str()
+                .and_then(parse_cell)
+                .filter(|&(row, col)| (1..=size.side()).contains(&row) && (1..=size.side()).contains(&col))
+                .map(|(row, col)| (row - 1, col - 1))
+                .ok_or_else(malformed)
+        })
+        .collect()
+}
+
+// The lines of a thermometer or arrow, each at least two cells long. A shorter
+// line, or none at all, would drop the constraint from the puzzle.
+fn lines(size: BoardSize, key: &str, entry: &Value) -> Result<Vec<Vec<Cell>>, FpuzzlesError> {
+    let malformed = || FpuzzlesError::Malformed(key.to_string());
+    let lines = entry
+        .get("lines")
+        .and_then(Value::as_array)
+        .filter(|lines| !lines.is_empty())
+        .ok_or_else(malformed)?;
+    lines
+        .iter()
+        .map(|line| {
+            cells(size, key, Some(line)).and_then(|line| if line.len() > 1 { Ok(line) } else { Err(malformed()) })
+        })
+        .collect()
+}
+
+// `R<row>C<col>`, without checking the board bounds
+fn parse_cell(name: &str) -> Option<Cell> {
+    let name = name.to_uppercase();
+    let (row, col) = name.strip_prefix('R')?.split_once('C')?;
+    Some((row.parse().ok()?, col.parse().ok()?))
+}
+
+fn outside_clue(size: BoardSize, name: &str, value: Option<&Value>) -> Option<OutsideClue> {
+    let (row, col) = parse_cell(name)?;
+    let sum = value.and_then(number)?;
+    let side = size.side();
+    let inside = 1..=side;
+    let (edge, index) = match (row, col) {
+        (0, col) if inside.contains(&col) => (Edge::Top, col),
+        (row, col) if row == side + 1 && inside.contains(&col) => (Edge::Bottom, col),
+        (row, 0) if inside.contains(&row) => (Edge::Left, row),
+        (row, col) if col == side + 1 && inside.contains(&row) => (Edge::Right, row),
+        _ => return None,
+    };
+    Some(OutsideClue {
+        edge,
+        index: index - 1,
+        sum,
+    })
+}
+
+fn cell_name((row, col): Cell) -> String {
+    format!("R{}C{}", row + 1, col + 1)
+}
+
+fn cell_names(cells: &[Cell]) -> Vec<String> {
+    cells.iter().copied().map(cell_name).collect()
+}
+
+fn clue_cell_name(side: usize, clue: &OutsideClue) -> String {
+    let index = clue.index + 1;
+    let (row, col) = match clue.edge {
+        Edge::Top => (0, index),
+        Edge::Bottom => (side + 1, index),
+        Edge::Left => (index, 0),
+        Edge::Right => (index, side + 1),
+    };
+    format!("R{row}C{col}")
+}
+
+/// The puzzle as f-puzzles JSON, with `grid` as its givens. Samurai boards
+/// have no f-puzzles equivalent.
+pub fn export_fpuzzles(rules: &Rules, grid: &Grid) -> Result<String, FpuzzlesError> {
+    let size = rules.size();
+    if size.grids().len() > 1 {
+        return Err(FpuzzlesError::Unsupported(vec!["samurai".to_string()]));
+    }
+    let side = size.side();
+    let Regions(boxes) = Regions::boxes(size);
+    let mut regions = boxes.clone();
+    let mut puzzle = Map::new();
+    let mut flags = Vec::new();
+    let mut push = |key: &str, entry: Value| {
+        if let Value::Array(entries) = puzzle.entry(key).or_insert_with(|| json!([])) {
+            entries.push(entry);
+        }
+    };
+
+    for decoration in rules.decorations() {
+        match decoration {
+            Decoration::Regions(map) => regions = map,
+            // Only the diagonals are shaded
+            Decoration::ShadedCells(_) => flags.extend(["diagonal+", "diagonal-"]),
+            Decoration::Cage(cage) => push(
+                "killercage",
+                json!({"cells": cell_names(&cage.cells), "value": cage.sum.to_string()}),
+            ),
+            Decoration::Thermometer(cells) => push("thermometer", json!({"lines": [cell_names(&cells)]})),
+            Decoration::Arrow(cells) => push(
+                "arrow",
+                json!({"cells": [cell_name(cells[0])], "lines": [cell_names(&cells)]}),
+            ),
+            Decoration::KropkiDot(first, second, kind) => push(
+                if kind == Kropki::White { "difference" } else { "ratio" },
+                json!({"cells": cell_names(&[first, second])}),
+            ),
+            Decoration::Sandwich(clue) => push(
+                "sandwichsum",
+                json!({"cell": clue_cell_name(side, &clue), "value": clue.sum.to_string()}),
+            ),
+            Decoration::XSum(clue) => push(
+                "xsum",
+                json!({"cell": clue_cell_name(side, &clue), "value": clue.sum.to_string()}),
+            ),
+            Decoration::GlobalRule(rule) => flags.push(match rule {
+                GlobalRule::AntiKnight => "antiknight",
+                GlobalRule::AntiKing => "antiking",
+                GlobalRule::NonConsecutive => "nonconsecutive",
+            }),
+        }
+    }
+    for flag in flags {
+        puzzle.insert(flag.to_string(), json!(true));
+    }
+
+    // Cells only name their region where it differs from the boxes
+    let cells: Vec<Value> = (0..side)
+        .map(|row| {
+            let cells: Vec<Value> = (0..side)
+                .map(|col| {
+                    let mut cell = Map::new();
+                    if let Some(num) = grid[row][col] {
+                        cell.insert("value".to_string(), json!(num));
+                        cell.insert("given".to_string(), json!(true));
+                    }
+                    if regions[row][col] != boxes[row][col] {
+                        cell.insert("region".to_string(), json!(regions[row][col]));
+                    }
+                    Value::Object(cell)
+                })
+                .collect();
+            Value::Array(cells)
+        })
+        .collect();
+    puzzle.insert("size".to_string(), json!(side));
+    puzzle.insert("grid".to_string(), Value::Array(cells));
+
+    Ok(Value::Object(puzzle).to_string())
+}
+
+// Links that open an exported puzzle in SudokuPad and f-puzzles
+pub fn links(json: &str) -> (String, String) {
+    let data = lz_string::compress_to_base64(json);
+    (
+        format!("https://sudokupad.app/fpuzzles{data}"),
+        format!("https://www.f-puzzles.com/?load={data}"),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIZE: BoardSize = BoardSize::Nine;
+
+    fn clue(edge: Edge, index: usize, sum: u32) -> OutsideClue {
+        OutsideClue { edge, index, sum }
+    }
+
+    // Exports the classic rules plus `extra` and parses the result back
+    fn assert_round_trip(extra: Vec<Box<dyn Constraint>>) {
+        let mut constraints = Rules::classic_constraints(SIZE);
+        constraints.extend(extra);
+        let rules = Rules::new(SIZE, constraints);
+        let mut grid = SIZE.empty_grid();
+        grid[0][0] = Some(5);
+        grid[8][4] = Some(9);
+
+        let json = export_fpuzzles(&rules, &grid).unwrap();
+        let (parsed, parsed_grid) = parse_fpuzzles(&json).unwrap();
+        assert_eq!(parsed_grid, grid);
+        let (expected, actual) = (rules.decorations(), parsed.decorations());
+        assert_eq!(actual.len(), expected.len(), "{json}");
+        for decoration in &expected {
+            assert!(actual.contains(decoration), "{decoration:?} lost in {json}");
+        }
+    }
+
+    #[test]
+    fn classic() {
+        assert_round_trip(Vec::new());
+    }
+
+    #[test]
+    fn irregular_regions() {
+        let Regions(mut regions) = Regions::boxes(SIZE);
+        regions[0][2] = Some(1);
+        regions[0][3] = Some(0);
+        let mut constraints: Vec<Box<dyn Constraint>> = vec![Box::new(Rows(SIZE)), Box::new(Columns(SIZE))];
+        constraints.push(Box::new(Regions(regions.clone())));
+        let rules = Rules::new(SIZE, constraints);
+
+        let json = export_fpuzzles(&rules, &SIZE.empty_grid()).unwrap();
+        let (parsed, _) = parse_fpuzzles(&json).unwrap();
+        assert!(parsed.decorations().contains(&Decoration::Regions(regions)));
+    }
+
+    #[test]
+    fn diagonals() {
+        assert_round_trip(vec![Box::new(Diagonals(SIZE))]);
+    }
+
+    #[test]
+    fn global_rules() {
+        for rule in [GlobalRule::AntiKnight, GlobalRule::AntiKing, GlobalRule::NonConsecutive] {
+            assert_round_trip(vec![Box::new(rule)]);
+        }
+    }
+
+    #[test]
+    fn killer_cages() {
+        let cages = vec![
+            Cage { cells: vec![(0, 1), (0, 2)], sum: 3 },
+            Cage { cells: vec![(4, 4), (4, 5), (5, 4)], sum: 20 },
+        ];
+        let houses: Vec<Vec<Cell>> = Rules::classic_constraints(SIZE)
+            .iter()
+            .flat_map(|constraint| constraint.houses())
+            .collect();
+        assert_round_trip(vec![Box::new(Cages::new(SIZE, cages, &houses))]);
+    }
+
+    #[test]
+    fn thermometers_and_arrows() {
+        assert_round_trip(vec![
+            Box::new(Thermometer(vec![(1, 1), (1, 2), (2, 3)])),
+            Box::new(Arrow(vec![(6, 6), (6, 7), (7, 8)])),
+        ]);
+    }
+
+    #[test]
+    fn kropki_dots() {
+        assert_round_trip(vec![
+            Box::new(KropkiDot((3, 3), (3, 4), Kropki::White)),
+            Box::new(KropkiDot((5, 0), (6, 0), Kropki::Black)),
+        ]);
+    }
+
+    #[test]
+    fn rejects_dots_between_cells_that_arent_adjacent() {
+        for cells in [r#"["R1C1", "R1C3"]"#, r#"["R1C1", "R2C2"]"#, r#"["R4C4", "R4C4"]"#] {
+            let json = format!(r#"{{"size": 9, "ratio": [{{"cells": {cells}}}]}}"#);
+            assert_eq!(parse_fpuzzles(&json), Err(FpuzzlesError::Malformed("ratio".to_string())));
+        }
+    }
+
+    #[test]
+    fn rejects_lines_too_short_to_keep() {
+        for (key, entry) in [
+            ("thermometer", r#"{"lines": [["R1C1"]]}"#),
+            ("thermometer", r#"{"lines": [["R1C1", "R1C2"], []]}"#),
+            ("thermometer", r#"{"lines": []}"#),
+            ("arrow", r#"{"cells": ["R5C5"], "lines": [["R5C5"]]}"#),
+        ] {
+            let json = format!(r#"{{"size": 9, "{key}": [{entry}]}}"#);
+            assert_eq!(parse_fpuzzles(&json), Err(FpuzzlesError::Malformed(key.to_string())), "{json}");
+        }
+    }
+
+    #[test]
+    fn rejects_cages_sharing_cells() {
+        for cages in [
+            r#"[{"cells": ["R1C1", "R1C2", "R1C1"], "value": 10}]"#,
+            r#"[{"cells": ["R1C1", "R1C2"], "value": 3}, {"cells": ["R1C2", "R1C3"], "value": 5}]"#,
+        ] {
+            let json = format!(r#"{{"size": 9, "killercage": {cages}}}"#);
+            assert_eq!(parse_fpuzzles(&json), Err(FpuzzlesError::Malformed("killercage".to_string())), "{json}");
+        }
+    }
+
+    #[test]
+    fn outside_clues() {
+        assert_round_trip(vec![
+            Box::new(Sandwich(clue(Edge::Top, 2, 15))),
+            Box::new(Sandwich(clue(Edge::Left, 7, 0))),
+            Box::new(XSum(clue(Edge::Bottom, 0, 12))),
+            Box::new(XSum(clue(Edge::Right, 8, 30))),
+        ]);
+    }
+
+    #[test]
+    fn reports_each_unsupported_constraint_once() {
+        let json = r#"{
+            "size": 9,
+            "palindrome": [{}],
+            "difference": [
+                {"cells": ["R1C1", "R1C2"], "value": 3},
+                {"cells": ["R2C1", "R2C2"], "value": 4},
+                {"cells": ["R3C1", "R3C2"], "value": 3}
+            ],
+            "killercage": [{"cells": ["R4C4"]}, {"cells": ["R5C5"]}],
+            "littlekillersum": [{}]
+        }"#;
+        assert_eq!(
+            parse_fpuzzles(json),
+            Err(FpuzzlesError::Unsupported(vec![
+                "difference 3".to_string(),
+                "difference 4".to_string(),
+                "killercage without a sum".to_string(),
+                "littlekillersum".to_string(),
+                "palindrome".to_string(),
+            ]))
+        );
+    }
+}
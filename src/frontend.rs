@@ -1,147 +1,778 @@
 //! Frontend module containing UI components and styling
 
-use crate::game_logic::{Difficulty, SudokuGame};
+use crate::achievements::{self, ACHIEVEMENTS, Achievement, Achievements};
+use crate::constraints::{Cell, Decoration, Edge, GlobalRule, Kropki, OutsideClue, Rules};
+use crate::fpuzzles::{self, FpuzzlesError};
+use crate::leaderboard::{Filter, Leaderboard, Scope};
+use crate::photo_import::{self, CellReading, PHOTO_SIZES, PhotoError, PhotoScan};
+use crate::print::{self, PrintFormat, PrintOptions, PrintedPuzzle, SavedSheet};
+use crate::puzzle_format::{self, ImportError};
+use crate::profiles::{AVATARS, Highlighting, LANGUAGES, Profiles};
+use crate::stats::{self, Stats, TierStats};
+use crate::theme::{Color, CustomPalettes, Palette, Theme};
+use crate::game_logic::{
+    self, AmbiguityReport, BoardSize, CageProblem, Difficulty, GameEvent, GenerationOptions, SudokuGame,
+    Symmetry, Variant,
+};
 use dioxus::prelude::*;
 use dioxus_i18n::t;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+// Zoom range of boards with several grids
+const MIN_ZOOM: f64 = 0.5;
+const MAX_ZOOM: f64 = 1.5;
+const ZOOM_STEP: f64 = 0.25;
+// Single-grid boards shrink to fit narrow screens down to this cell size
+const MIN_CELL_PX: f64 = 24.0;
+// A touch held this long without moving is a long press
+const LONG_PRESS_MS: u128 = 500;
+// Movement, in pixels, that a tap or long press still allows
+const TOUCH_SLOP_PX: f64 = 10.0;
+// Horizontal movement, in pixels, that makes a swipe
+const SWIPE_PX: f64 = 50.0;
+
+// Where and when a touch began, to tell taps, long presses and swipes apart
+#[derive(Clone, Copy)]
+struct TouchStart {
+    x: f64,
+    y: f64,
+    millis: u128,
+}
+
+impl TouchStart {
+    fn new(event: &TouchData) -> Option<Self> {
+        let point = event.touches_changed().into_iter().next()?.client_coordinates();
+        Some(Self {
+            x: point.x,
+            y: point.y,
+            millis: game_logic::unix_millis(),
+        })
+    }
+
+    // How far the touch moved across and down, and how long it lasted, when
+    // `event` ended it
+    fn end(&self, event: &TouchData) -> Option<(f64, f64, u128)> {
+        let point = event.touches_changed().into_iter().next()?.client_coordinates();
+        Some((point.x - self.x, point.y - self.y, game_logic::unix_millis().saturating_sub(self.millis)))
+    }
+}
 
 #[component]
-pub fn SudokuGrid(game: Signal<SudokuGame>) -> Element {
+pub fn SudokuGrid(
+    game: Signal<SudokuGame>,
+    ambiguity: Memo<Option<AmbiguityReport>>,
+    colorblind: bool,
+    highlighting: Highlighting,
+    mut is_note_mode: Signal<bool>,
+) -> Element {
+    let mut zoom = use_signal(|| 1.0);
+    // Width the board has to fit in, once measured
+    let mut available_px = use_signal(|| None::<f64>);
+    let mut touch_start = use_signal(|| None::<TouchStart>);
+    // Mounted cells, so focus can follow the selection when it moves with
+    // the arrow keys
+    let cell_elements = use_hook(|| Rc::new(RefCell::new(HashMap::<(usize, usize), Rc<MountedData>>::new())));
 
     let game_state = game.read();
+    let size = game_state.size;
+    let side = size.side();
+    // The one cell reached with Tab; the arrow keys move on from there
+    let focus_cell = game_state.selected_cell.unwrap_or_else(|| size.cells()[0]);
+    // Collect what the rules draw on the board
+    let mut regions = None;
+    let mut shaded_cells = Vec::new();
+    let mut cages = Vec::new();
+    let mut overlays = Vec::new();
+    let mut clues = Vec::new();
+    let mut global_rules = Vec::new();
+    for decoration in game_state.rules.decorations() {
+        match decoration {
+            Decoration::Regions(map) => regions = Some(map),
+            Decoration::ShadedCells(cells) => shaded_cells.extend(cells),
+            Decoration::Cage(cage) => cages.push(cage),
+            Decoration::Sandwich(clue) | Decoration::XSum(clue) => clues.push(clue),
+            Decoration::GlobalRule(rule) => global_rules.push(rule),
+            // Lines and dots are drawn over the whole board
+            overlay => overlays.push(overlay),
+        }
+    }
+    let mut cage_of = vec![vec![None; side]; side];
+    for (index, cage) in cages.iter().enumerate() {
+        for &(row, col) in &cage.cells {
+            cage_of[row][col] = Some(index);
+        }
+    }
+    let cage_problems: Vec<Option<CageProblem>> =
+        cages.iter().map(|cage| game_state.cage_problem(cage)).collect();
+    // Thick borders separate the regions
+    let region_of = |row: usize, col: usize| regions.as_ref().and_then(|regions| regions[row][col]);
+    // Samurai boards scroll inside a viewport and can be zoomed
+    let is_multi_grid = size.grids().len() > 1;
+    // 16x16 and Samurai boards use smaller cells so they still fit on screen
+    let (base_cell_px, base_font_px): (f64, f64) = if side > 9 { (36.0, 15.0) } else { (50.0, 18.0) };
+    // Other boards shrink to the width available, with room for the outside
+    // clues and the border
+    let fit_px = match available_px() {
+        Some(width) if !is_multi_grid => {
+            let columns = side + if clues.is_empty() { 0 } else { 2 };
+            ((width - 6.0) / columns as f64).floor().max(MIN_CELL_PX)
+        }
+        _ => f64::MAX,
+    };
+    let cell_px = (base_cell_px * zoom()).min(fit_px);
+    let font_px = (base_font_px * cell_px / base_cell_px) as usize;
+    let cell_px = cell_px as usize;
+    // Outside clues sit in a ring one cell wide around the board
+    let ring_px = if clues.is_empty() { 0 } else { cell_px };
+    let ambiguous_cells = ambiguity
+        .read()
+        .as_ref()
+        .map(|report| report.differing_cells.clone())
+        .unwrap_or_default();
+    // Global rules like anti-knight relate cells outside a common house, so
+    // each broken pair is joined by a line
+    let conflict_lines = game_state.rules.conflicts(&game_state.grid);
+
     rsx! {
         div {
-            style: "display: inline-block; border: 3px solid #333; background-color: white; margin-bottom: 20px;",
+            style: "display: flex; flex-direction: column; align-items: center; gap: 10px; margin-bottom: 20px; width: 100%;",
+            onresize: move |event: Event<ResizeData>| {
+                if let Ok(size) = event.get_content_box_size() {
+                    available_px.set(Some(size.width));
+                }
+            },
 
-            for row in 0..9 {
+            // Global rules leave nothing to draw, so they are named above the board
+            if !global_rules.is_empty() {
                 div {
-                    style: "display: flex;",
+                    style: "display: flex; gap: 6px;",
+                    for rule in global_rules {
+                        span {
+                            style: "padding: 2px 10px; font-size: 13px; color: var(--text); background-color: var(--shaded); border-radius: 10px;",
+                            {global_rule_label(rule)}
+                        }
+                    }
+                }
+            }
 
-                    for col in 0..9 {
-                        {
-                            let cell_value = game_state.grid[row][col];
-                            let is_selected = game_state.selected_cell == Some((row, col));
-                            let is_initial = game_state.is_initial_cell(row, col);
-                            let is_highlighted = game_state.is_cell_highlighted(row, col);
-                            let has_conflict = game_state.has_conflicts(row, col);
-
-                            let mut cell_style = String::from(
-                                "width: 50px; height: 50px; border: 1px solid #ccc; \
-                                 display: flex; align-items: center; justify-content: center; \
-                                 font-size: 18px; font-weight: bold; cursor: pointer; transition: all 0.2s; position: relative;"
-                            );
-
-                            // Add thick borders for 3x3 boxes
-                            if row % 3 == 0 {
-                                cell_style.push_str(" border-top: 2px solid #333;");
-                            }
-                            if col % 3 == 0 {
-                                cell_style.push_str(" border-left: 2px solid #333;");
-                            }
-                            if row == 8 {
-                                cell_style.push_str(" border-bottom: 2px solid #333;");
-                            }
-                            if col == 8 {
-                                cell_style.push_str(" border-right: 2px solid #333;");
-                            }
+            if is_multi_grid {
+                div {
+                    style: "display: flex; align-items: center; gap: 8px;",
+                    button {
+                        style: "padding: 4px 12px; font-size: 16px; border: 1px solid var(--border); border-radius: 5px; background-color: var(--surface); color: var(--text); cursor: pointer;",
+                        title: t!("zoom-out"),
+                        disabled: zoom() <= MIN_ZOOM,
+                        onclick: move |_| zoom.set((zoom() - ZOOM_STEP).max(MIN_ZOOM)),
+                        "−"
+                    }
+                    span {
+                        style: "min-width: 50px; color: var(--text);",
+                        {format!("{:.0}%", zoom() * 100.0)}
+                    }
+                    button {
+                        style: "padding: 4px 12px; font-size: 16px; border: 1px solid var(--border); border-radius: 5px; background-color: var(--surface); color: var(--text); cursor: pointer;",
+                        title: t!("zoom-in"),
+                        disabled: zoom() >= MAX_ZOOM,
+                        onclick: move |_| zoom.set((zoom() + ZOOM_STEP).min(MAX_ZOOM)),
+                        "+"
+                    }
+                }
+            }
 
-                            // Cell coloring - distinguish between initial and user input
-                            if has_conflict {
-                                // Conflict cells: red background/border to indicate error
-                                if is_initial {
-                                    cell_style.push_str(" background-color: #ffcdd2; color: #d32f2f; font-weight: 900; border: 2px solid #f44336;");
-                                } else {
-                                    cell_style.push_str(" background-color: #ffebee; color: #d32f2f; font-weight: 600; border: 2px solid #f44336;");
-                                }
-                            } else if is_selected {
-                                if is_highlighted {
-                                    // Selected and highlighted: darker yellow
-                                    if is_initial {
-                                        cell_style.push_str(" background-color: #ffc107; color: #333; font-weight: 900;");
+            div {
+                style: format!(
+                    "position: relative; padding: {ring_px}px; {}",
+                    if is_multi_grid { "max-width: min(90vw, 800px); max-height: 80vh; overflow: auto;" } else { "" }
+                ),
+
+                {clue_ring(&clues, side, cell_px, font_px)}
+
+                // Long presses pick notes, so they should not select text or open
+                // a menu
+                div {
+                    style: format!(
+                        "display: inline-block; position: relative; user-select: none; -webkit-user-select: none; -webkit-touch-callout: none; {}",
+                        if is_multi_grid { "" } else { "border: 3px solid var(--grid-line); background-color: var(--cell);" }
+                    ),
+                    role: "grid",
+                    aria_label: t!("board-label"),
+                    aria_rowcount: "{side}",
+                    aria_colcount: "{side}",
+
+                    for row in 0..side {
+                        div {
+                            style: "display: flex;",
+                            role: "row",
+                            aria_rowindex: "{row + 1}",
+
+                            for col in 0..side {
+                                {
+                                    let cell_value = game_state.grid[row][col];
+                                    let is_selected = game_state.selected_cell == Some((row, col));
+                                    let is_initial = game_state.is_initial_cell(row, col);
+                                    let is_highlighted = game_state.is_cell_highlighted(row, col);
+                                    let has_conflict = game_state.has_conflicts(row, col);
+                                    let is_ambiguous = ambiguous_cells.contains(&(row, col));
+                                    let is_shaded = shaded_cells.contains(&(row, col));
+                                    let is_hint = colorblind && cell_value.is_some() && game_state.is_hint_cell(row, col);
+                                    let is_peer = highlighting.peers && game_state.is_peer_of_selected(row, col);
+                                    let has_noted = highlighting.notes && game_state.has_highlighted_note(row, col);
+                                    let is_candidate = highlighting.candidates && game_state.can_hold_highlighted(row, col);
+                                    let label = cell_label(&game_state, row, col);
+                                    let tabindex = if (row, col) == focus_cell { "0" } else { "-1" };
+                                    let mounted_cells = cell_elements.clone();
+                                    let focusable_cells = cell_elements.clone();
+
+                                    let mut cell_style = format!(
+                                        "width: {cell_px}px; height: {cell_px}px; box-sizing: border-box; border: 1px solid var(--cell-line); \
+                                         display: flex; align-items: center; justify-content: center; \
+                                         font-size: {font_px}px; font-weight: bold; cursor: pointer; transition: all 0.2s; position: relative; \
+                                         touch-action: manipulation;"
+                                    );
+
+                                    // Add thick borders between regions
+                                    if row == 0 || region_of(row - 1, col) != region_of(row, col) {
+                                        cell_style.push_str(" border-top: 2px solid var(--grid-line);");
+                                    }
+                                    if col == 0 || region_of(row, col - 1) != region_of(row, col) {
+                                        cell_style.push_str(" border-left: 2px solid var(--grid-line);");
+                                    }
+                                    if row == side - 1 || !size.contains(row + 1, col) {
+                                        cell_style.push_str(" border-bottom: 2px solid var(--grid-line);");
+                                    }
+                                    if col == side - 1 || !size.contains(row, col + 1) {
+                                        cell_style.push_str(" border-right: 2px solid var(--grid-line);");
+                                    }
+
+                                    // Cell coloring - distinguish between initial and user input
+                                    if has_conflict {
+                                        // Conflict cells: red background/border to indicate error
+                                        if is_initial {
+                                            cell_style.push_str(" background-color: var(--conflict-given); color: var(--conflict-text); font-weight: 900; border: 2px solid var(--conflict-border);");
+                                        } else {
+                                            cell_style.push_str(" background-color: var(--conflict); color: var(--conflict-text); font-weight: 600; border: 2px solid var(--conflict-border);");
+                                        }
+                                    } else if is_selected {
+                                        if is_highlighted {
+                                            // Selected and highlighted: darker yellow
+                                            if is_initial {
+                                                cell_style.push_str(" background-color: var(--selected-highlight); color: var(--given); font-weight: 900;");
+                                            } else {
+                                                cell_style.push_str(" background-color: var(--selected-highlight); color: var(--entered); font-weight: 600;");
+                                            }
+                                        } else if is_initial {
+                                            cell_style.push_str(" background-color: var(--selected-given); color: var(--given); font-weight: 900;");
+                                        } else {
+                                            cell_style.push_str(" background-color: var(--selected); color: var(--entered);");
+                                        }
+                                    } else if is_highlighted {
+                                        // Highlighted cells: light yellow background
+                                        if is_initial {
+                                            cell_style.push_str(" background-color: var(--highlight); color: var(--given); font-weight: 900;");
+                                        } else {
+                                            cell_style.push_str(" background-color: var(--highlight); color: var(--entered); font-weight: 600;");
+                                        }
+                                    } else if is_initial {
+                                        // Given numbers: same background as filled cells, bold given color
+                                        cell_style.push_str(" background-color: var(--cell-filled); color: var(--given); font-weight: 900;");
+                                    } else if cell_value.is_some() {
+                                        // User input numbers: same background as preset cells, entered color
+                                        cell_style.push_str(" background-color: var(--cell-filled); color: var(--entered); font-weight: 600;");
                                     } else {
-                                        cell_style.push_str(" background-color: #ffc107; color: #1976D2; font-weight: 600;");
+                                        // Empty cells: plain background
+                                        cell_style.push_str(" background-color: var(--cell); color: var(--muted);");
                                     }
-                                } else if is_initial {
-                                    cell_style.push_str(" background-color: #ffecb3; color: #333; font-weight: 900;");
-                                } else {
-                                    cell_style.push_str(" background-color: #e3f2fd; color: #1976D2;");
-                                }
-                            } else if is_highlighted {
-                                // Highlighted cells: light yellow background
-                                if is_initial {
-                                    cell_style.push_str(" background-color: #fff9c4; color: #000; font-weight: 900;");
-                                } else {
-                                    cell_style.push_str(" background-color: #fff9c4; color: #1976D2; font-weight: 600;");
-                                }
-                            } else if is_initial {
-                                // Given numbers: same background as filled cells, bold black text
-                                cell_style.push_str(" background-color: #f8f9fa; color: #000; font-weight: 900;");
-                            } else if cell_value.is_some() {
-                                // User input numbers: same background as preset cells, blue text
-                                cell_style.push_str(" background-color: #f8f9fa; color: #1976D2; font-weight: 600;");
-                            } else {
-                                // Empty cells: white background
-                                cell_style.push_str(" background-color: white; color: #666;");
-                            }
 
-                            rsx! {
-                                div {
-                                    style: "{cell_style}",
-                                    onclick: {
-                                        let mut game = game.clone();
-                                        move |_| {
-                                            game.write().select_cell(row, col);
+                                    // Shaded cells of the rules, like the Sudoku X diagonals, unless a state color applies
+                                    if is_shaded && !has_conflict && !is_selected && !is_highlighted {
+                                        cell_style.push_str(" background-color: var(--shaded);");
+                                    }
+
+                                    // Where the highlighted number is noted or could still go,
+                                    // then the houses of the selection
+                                    if !has_conflict && !is_selected && !is_highlighted {
+                                        if has_noted {
+                                            cell_style.push_str(" background-color: var(--highlight);");
+                                        } else if is_candidate {
+                                            cell_style.push_str(" background-color: var(--candidate);");
+                                        } else if is_peer {
+                                            cell_style.push_str(" background-color: var(--peer);");
                                         }
-                                    },
+                                    }
 
-                                    {cell_value.map(|num| {
+                                    // Colorblind mode: stripes for conflicts, dots for the same
+                                    // number and a ring around the selection
+                                    if colorblind {
+                                        let mut patterns = Vec::new();
                                         if has_conflict {
-                                            rsx! {
-                                                span { style: "position: relative;",
-                                                    "{num}"
-                                                    span {
-                                                        style: "position: absolute; top: -8px; right: -8px; \
-                                                               background-color: #f44336; color: white; \
-                                                               border-radius: 50%; width: 16px; height: 16px; \
-                                                               font-size: 10px; display: flex; \
-                                                               align-items: center; justify-content: center; \
-                                                               font-weight: bold;",
-                                                        "!"
-                                                    }
-                                                }
+                                            patterns.push("repeating-linear-gradient(45deg, transparent 0 5px, var(--cvd-conflict) 5px 8px)");
+                                            cell_style.push_str(" border-style: dashed;");
+                                        }
+                                        if is_highlighted || has_noted {
+                                            patterns.push("radial-gradient(circle, var(--cvd-highlight) 1.5px, transparent 2px) 0 0 / 8px 8px");
+                                        } else if is_candidate {
+                                            patterns.push("linear-gradient(var(--cvd-highlight), var(--cvd-highlight)) bottom / 100% 3px no-repeat");
+                                        }
+                                        if !patterns.is_empty() {
+                                            cell_style.push_str(&format!(" background-image: {};", patterns.join(", ")));
+                                        }
+                                        if is_selected {
+                                            cell_style.push_str(" box-shadow: inset 0 0 0 3px var(--cvd-selected);");
+                                        }
+                                    }
+
+                                    // The gaps between the grids of a Samurai board keep their
+                                    // space but show nothing
+                                    if !size.contains(row, col) {
+                                        cell_style.push_str(" visibility: hidden;");
+                                    }
+
+                                    // Cells that differ between two solutions of a non-unique puzzle
+                                    if is_ambiguous {
+                                        cell_style.push_str(" outline: 2px dashed var(--accent-editor); outline-offset: -4px;");
+                                    }
+
+                                    // Killer cages: a dashed outline inside the cells, red while the
+                                    // cage repeats a number or misses its sum, and the sum in the
+                                    // top-left cell
+                                    let cage_index = cage_of[row][col];
+                                    let cage_color = match cage_index.and_then(|index| cage_problems[index]) {
+                                        Some(_) => "var(--conflict-border)",
+                                        None => "var(--muted)",
+                                    };
+                                    let cage_outline = cage_index.map(|index| {
+                                        let same_cage = |r: usize, c: usize| cage_of[r][c] == Some(index);
+                                        let mut style = String::from(
+                                            "position: absolute; inset: 3px; pointer-events: none;",
+                                        );
+                                        for (side_name, open) in [
+                                            ("top", row > 0 && same_cage(row - 1, col)),
+                                            ("bottom", row + 1 < side && same_cage(row + 1, col)),
+                                            ("left", col > 0 && same_cage(row, col - 1)),
+                                            ("right", col + 1 < side && same_cage(row, col + 1)),
+                                        ] {
+                                            if !open {
+                                                style.push_str(&format!(" border-{side_name}: 1px dashed {cage_color};"));
                                             }
-                                        } else {
-                                            rsx! { "{num}" }
                                         }
-                                    }).unwrap_or_else(|| {
-                                        // Show notes if cell is empty
-                                         let notes = game_state.get_notes(row, col);
-                                         if !notes.is_empty() {
-                                             let mut notes_vec: Vec<u8> = notes.iter().cloned().collect();
-                                             notes_vec.sort();
-                                            let notes_display = notes_vec.iter().map(|n| n.to_string()).collect::<Vec<_>>().join(" ");
-                                            rsx! {
-                                                div {
-                                                    style: "font-size: 10px; color: #666; line-height: 1; \
-                                                           display: flex; flex-wrap: wrap; justify-content: center; \
-                                                           align-items: center; width: 100%; height: 100%; \
-                                                           padding: 2px;",
-                                                    "{notes_display}"
+                                        style
+                                    });
+                                    let cage_sum = cage_index
+                                        .filter(|&index| cages[index].cells[0] == (row, col))
+                                        .map(|index| cages[index].sum);
+
+                                    rsx! {
+                                        div {
+                                            style: "{cell_style}",
+                                            role: "gridcell",
+                                            aria_colindex: "{col + 1}",
+                                            aria_label: "{label}",
+                                            aria_selected: "{is_selected}",
+                                            aria_readonly: "{is_initial}",
+                                            aria_invalid: "{has_conflict}",
+                                            tabindex: tabindex,
+                                            onmounted: move |event: Event<MountedData>| {
+                                                mounted_cells.borrow_mut().insert((row, col), event.data());
+                                            },
+                                            onclick: {
+                                                let mut game = game.clone();
+                                                move |_| {
+                                                    game.write().select_cell(row, col);
+                                                }
+                                            },
+                                            onkeydown: move |event: Event<KeyboardData>| {
+                                                move_selection(game, &focusable_cells, (row, col), event);
+                                            },
+                                            ontouchstart: move |event: Event<TouchData>| {
+                                                touch_start.set(TouchStart::new(&event));
+                                            },
+                                            // A long press selects the cell and switches between
+                                            // filling in numbers and taking notes
+                                            ontouchend: {
+                                                let mut game = game;
+                                                move |event: Event<TouchData>| {
+                                                    let Some((dx, dy, millis)) = touch_start.take().and_then(|start| start.end(&event)) else {
+                                                        return;
+                                                    };
+                                                    if millis >= LONG_PRESS_MS && dx.hypot(dy) <= TOUCH_SLOP_PX {
+                                                        event.prevent_default();
+                                                        game.write().select_cell(row, col);
+                                                        is_note_mode.toggle();
+                                                    }
+                                                }
+                                            },
+
+                                            {cage_outline.map(|style| rsx! { div { style: "{style}" } })}
+                                            if is_hint {
+                                                span {
+                                                    style: "position: absolute; bottom: 1px; right: 3px; font-size: 10px; \
+                                                           line-height: 1; color: var(--muted);",
+                                                    "✦"
                                                 }
                                             }
-                                        } else {
-                                            rsx! { "" }
+                                            {cage_sum.map(|sum| rsx! {
+                                                span {
+                                                    style: "position: absolute; top: 1px; left: 2px; font-size: 9px; \
+                                                           font-weight: bold; line-height: 1; color: {cage_color}; \
+                                                           background-color: var(--cell); padding: 0 1px;",
+                                                    "{sum}"
+                                                }
+                                            })}
+
+                                            {cell_value.map(|num| {
+                                                let num = size.digit_label(num);
+                                                if has_conflict {
+                                                    rsx! {
+                                                        span { style: "position: relative;",
+                                                            "{num}"
+                                                            span {
+                                                                style: "position: absolute; top: -8px; right: -8px; \
+                                                                       background-color: var(--conflict-border); color: white; \
+                                                                       border-radius: 50%; width: 16px; height: 16px; \
+                                                                       font-size: 10px; display: flex; \
+                                                                       align-items: center; justify-content: center; \
+                                                                       font-weight: bold;",
+                                                                "!"
+                                                            }
+                                                        }
+                                                    }
+                                                } else {
+                                                    rsx! { "{num}" }
+                                                }
+                                            }).unwrap_or_else(|| {
+                                                // Show notes if cell is empty
+                                                 let notes = game_state.get_notes(row, col);
+                                                 if !notes.is_empty() {
+                                                     let mut notes_vec: Vec<u8> = notes.iter().cloned().collect();
+                                                     notes_vec.sort();
+                                                    // The highlighted number stands out among the notes
+                                                    let noted = if has_noted { game_state.highlighted_number } else { None };
+                                                    rsx! {
+                                                        div {
+                                                            style: "font-size: 10px; color: var(--muted); line-height: 1; \
+                                                                   display: flex; flex-wrap: wrap; justify-content: center; \
+                                                                   align-items: center; gap: 0 3px; width: 100%; height: 100%; \
+                                                                   padding: 2px;",
+                                                            for num in notes_vec {
+                                                                span {
+                                                                    style: if noted == Some(num) { "color: var(--entered); font-weight: 900;" } else { "" },
+                                                                    {size.digit_label(num)}
+                                                                }
+                                                            }
+                                                        }
+                                                    }
+                                                } else {
+                                                    rsx! { "" }
+                                                }
+                                            })}
                                         }
-                                    })}
+                                    }
                                 }
                             }
                         }
                     }
+
+                    {rule_overlay(&overlays, &conflict_lines, side, cell_px)}
+                }
+            }
+
+            if colorblind {
+                div {
+                    style: "font-size: 12px; color: var(--muted);",
+                    {t!("colorblind-legend")}
+                }
+            }
+        }
+    }
+}
+
+// What a screen reader says for a cell: its position, number or notes, and
+// whether it is a given, from a hint or in conflict
+fn cell_label(game: &SudokuGame, row: usize, col: usize) -> String {
+    let size = game.size;
+    let mut parts = vec![t!("cell-position", row: row + 1, col: col + 1)];
+    match game.grid[row][col] {
+        Some(num) => {
+            let num = size.digit_label(num);
+            parts.push(if game.is_initial_cell(row, col) {
+                t!("cell-given", num: num)
+            } else if game.is_hint_cell(row, col) {
+                t!("cell-hint", num: num)
+            } else {
+                t!("cell-entered", num: num)
+            });
+        }
+        None => {
+            parts.push(t!("cell-empty"));
+            let mut notes: Vec<u8> = game.get_notes(row, col).iter().copied().collect();
+            if !notes.is_empty() {
+                notes.sort();
+                let notes = notes.iter().map(|num| size.digit_label(*num)).collect::<Vec<_>>().join(" ");
+                parts.push(t!("cell-notes", notes: notes));
+            }
+        }
+    }
+    if game.has_conflicts(row, col) {
+        parts.push(t!("cell-conflict"));
+    }
+    if let Some(cage) = game.cage_at(row, col) {
+        parts.push(t!("cell-cage", sum: cage.sum));
+    }
+    parts.join(", ")
+}
+
+// Arrow keys on the board move the selection, and the focus with it, instead
+// of undoing and redoing
+fn move_selection(
+    mut game: Signal<SudokuGame>,
+    cell_elements: &RefCell<HashMap<(usize, usize), Rc<MountedData>>>,
+    (row, col): (usize, usize),
+    event: Event<KeyboardData>,
+) {
+    let (down, right) = match event.key() {
+        Key::ArrowUp => (-1, 0),
+        Key::ArrowDown => (1, 0),
+        Key::ArrowLeft => (0, -1),
+        Key::ArrowRight => (0, 1),
+        _ => return,
+    };
+    event.prevent_default();
+    event.stop_propagation();
+
+    // Step over the gaps of a Samurai board and stop at the edge
+    let size = game.peek().size;
+    let side = size.side() as isize;
+    let (mut r, mut c) = (row as isize, col as isize);
+    let target = loop {
+        r += down;
+        c += right;
+        if !(0..side).contains(&r) || !(0..side).contains(&c) {
+            return;
+        }
+        if size.contains(r as usize, c as usize) {
+            break (r as usize, c as usize);
+        }
+    };
+    game.write().select_cell(target.0, target.1);
+    if let Some(element) = cell_elements.borrow().get(&target).cloned() {
+        spawn(async move {
+            let _ = element.set_focus(true).await;
+        });
+    }
+}
+
+// Places each outside clue next to its row or column, in a ring `cell_px`
+// wide around the board and its 3px border
+fn clue_ring(clues: &[OutsideClue], side: usize, cell_px: usize, font_px: usize) -> Element {
+    const BORDER_PX: usize = 3;
+    let far_px = cell_px + BORDER_PX + side * cell_px + BORDER_PX;
+
+    rsx! {
+        for clue in clues {
+            {
+                let along = cell_px + BORDER_PX + clue.index * cell_px;
+                let (left, top) = match clue.edge {
+                    Edge::Top => (along, 0),
+                    Edge::Bottom => (along, far_px),
+                    Edge::Left => (0, along),
+                    Edge::Right => (far_px, along),
+                };
+                rsx! {
+                    div {
+                        style: "position: absolute; left: {left}px; top: {top}px; width: {cell_px}px; height: {cell_px}px; \
+                               display: flex; align-items: center; justify-content: center; \
+                               font-size: {font_px * 4 / 5}px; font-weight: bold; color: var(--text);",
+                        "{clue.sum}"
+                    }
+                }
+            }
+        }
+    }
+}
+
+// Draws thermometers, arrows, kropki dots and conflict lines as an SVG layer
+// over the board
+fn rule_overlay(
+    overlays: &[Decoration],
+    conflict_lines: &[(Cell, Cell)],
+    side: usize,
+    cell_px: usize,
+) -> Element {
+    let cell = cell_px as f64;
+    let center = |(row, col): (usize, usize)| (col as f64 * cell + cell / 2.0, row as f64 * cell + cell / 2.0);
+    let points = |cells: &[(usize, usize)]| {
+        cells
+            .iter()
+            .map(|&cell| {
+                let (x, y) = center(cell);
+                format!("{x},{y}")
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+    let board_px = side * cell_px;
+
+    rsx! {
+        svg {
+            style: "position: absolute; top: 0; left: 0; pointer-events: none;",
+            width: "{board_px}",
+            height: "{board_px}",
+
+            for overlay in overlays {
+                match overlay {
+                    Decoration::Thermometer(cells) => {
+                        let (bulb_x, bulb_y) = center(cells[0]);
+                        rsx! {
+                            polyline {
+                                points: points(cells),
+                                fill: "none",
+                                style: "stroke: var(--overlay-line);",
+                                stroke_opacity: "0.5",
+                                stroke_width: "{cell * 0.3}",
+                                stroke_linecap: "round",
+                                stroke_linejoin: "round",
+                            }
+                            circle { cx: "{bulb_x}", cy: "{bulb_y}", r: "{cell * 0.35}", style: "fill: var(--overlay-line);", fill_opacity: "0.5" }
+                        }
+                    }
+                    Decoration::Arrow(cells) => {
+                        let (circle_x, circle_y) = center(cells[0]);
+                        let radius = cell * 0.4;
+                        // The shaft starts at the edge of the circle
+                        let (next_x, next_y) = center(cells[1]);
+                        let length = ((next_x - circle_x).powi(2) + (next_y - circle_y).powi(2)).sqrt();
+                        let start = format!(
+                            "{},{}",
+                            circle_x + (next_x - circle_x) / length * radius,
+                            circle_y + (next_y - circle_y) / length * radius
+                        );
+                        // Two short strokes angled back from the tip
+                        let (tip_x, tip_y) = center(cells[cells.len() - 1]);
+                        let (from_x, from_y) = center(cells[cells.len() - 2]);
+                        let angle = (tip_y - from_y).atan2(tip_x - from_x);
+                        let head = [angle + 2.6, angle - 2.6].map(|side_angle| {
+                            (tip_x + side_angle.cos() * cell * 0.25, tip_y + side_angle.sin() * cell * 0.25)
+                        });
+                        rsx! {
+                            circle { cx: "{circle_x}", cy: "{circle_y}", r: "{radius}", fill: "none", style: "stroke: var(--overlay-line);", stroke_width: "2" }
+                            polyline {
+                                points: format!("{start} {}", points(&cells[1..])),
+                                fill: "none",
+                                style: "stroke: var(--overlay-line);",
+                                stroke_width: "2",
+                                stroke_linejoin: "round",
+                            }
+                            for (head_x, head_y) in head {
+                                line { x1: "{tip_x}", y1: "{tip_y}", x2: "{head_x}", y2: "{head_y}", style: "stroke: var(--overlay-line);", stroke_width: "2" }
+                            }
+                        }
+                    }
+                    Decoration::KropkiDot(first, second, kind) => {
+                        let ((x1, y1), (x2, y2)) = (center(*first), center(*second));
+                        let fill = match kind {
+                            Kropki::White => "var(--cell)",
+                            Kropki::Black => "var(--given)",
+                        };
+                        rsx! {
+                            circle { cx: "{(x1 + x2) / 2.0}", cy: "{(y1 + y2) / 2.0}", r: "{cell * 0.12}", style: "fill: {fill}; stroke: var(--grid-line);", stroke_width: "1.5" }
+                        }
+                    }
+                    _ => rsx! {},
+                }
+            }
+
+            for &(first, second) in conflict_lines {
+                {
+                    let ((x1, y1), (x2, y2)) = (center(first), center(second));
+                    rsx! {
+                        line {
+                            x1: "{x1}",
+                            y1: "{y1}",
+                            x2: "{x2}",
+                            y2: "{y2}",
+                            style: "stroke: var(--conflict-border);",
+                            stroke_opacity: "0.7",
+                            stroke_width: "3",
+                            stroke_linecap: "round",
+                        }
+                    }
                 }
             }
         }
     }
 }
 
+/// A live region reading hints, undo, redo and completion out to screen
+/// readers, as they happen
+#[component]
+pub fn GameAnnouncer(game: Signal<SudokuGame>) -> Element {
+    // The game id and the number of its events already looked at
+    let mut events_seen = use_signal(|| None::<(u64, usize)>);
+    let mut announced = use_signal(Vec::<GameEvent>::new);
+    use_effect(move || {
+        let game = game.read();
+        let (seen, same_game) = match *events_seen.peek() {
+            Some((id, seen)) if id == game.id() => (seen, true),
+            _ => (0, false),
+        };
+        let events: Vec<GameEvent> = game.events()[seen..]
+            .iter()
+            .filter(|event| {
+                matches!(
+                    event,
+                    GameEvent::Hint { .. } | GameEvent::Undo { .. } | GameEvent::Redo { .. } | GameEvent::Completed { .. }
+                )
+            })
+            .cloned()
+            .collect();
+        // A new puzzle drops what was said about the last one
+        if !events.is_empty() || !same_game {
+            announced.set(events);
+        }
+        events_seen.set(Some((game.id(), game.events().len())));
+    });
+
+    let size = game.read().size;
+    let text = announced
+        .read()
+        .iter()
+        .filter_map(|event| announcement(event, size))
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    rsx! {
+        div {
+            style: "position: absolute; width: 1px; height: 1px; overflow: hidden; clip: rect(0 0 0 0); white-space: nowrap;",
+            role: "status",
+            aria_live: "polite",
+            aria_atomic: "true",
+            "{text}"
+        }
+    }
+}
+
+fn announcement(event: &GameEvent, size: BoardSize) -> Option<String> {
+    let cell_value = |value: &Option<u8>| match value {
+        Some(num) => size.digit_label(*num),
+        None => t!("cell-empty"),
+    };
+    Some(match event {
+        GameEvent::Hint { row, col, num } => {
+            t!("announce-hint", num: size.digit_label(*num), row: row + 1, col: col + 1)
+        }
+        GameEvent::Undo { row, col, value } => {
+            t!("announce-undo", row: row + 1, col: col + 1, value: cell_value(value))
+        }
+        GameEvent::Redo { row, col, value } => {
+            t!("announce-redo", row: row + 1, col: col + 1, value: cell_value(value))
+        }
+        GameEvent::Completed { seconds, .. } => t!("announce-completed", time: format_duration(*seconds)),
+        _ => return None,
+    })
+}
+
 #[component]
 pub fn UndoRedoControls(game: Signal<SudokuGame>) -> Element {
     let game_state = game.read();
@@ -157,9 +788,9 @@ pub fn UndoRedoControls(game: Signal<SudokuGame>) -> Element {
                         "padding: 10px 20px; font-size: 16px; border: none; border-radius: 5px; cursor: {}; transition: all 0.3s; {}",
                         if can_undo { "pointer" } else { "not-allowed" },
                         if can_undo {
-                            "background-color: #2196F3; color: white;"
+                            "background-color: var(--accent); color: white;"
                         } else {
-                            "background-color: #ccc; color: #666;"
+                            "background-color: var(--disabled); color: var(--disabled-text);"
                         }
                     ),
                     disabled: !can_undo,
@@ -177,9 +808,9 @@ pub fn UndoRedoControls(game: Signal<SudokuGame>) -> Element {
                         "padding: 10px 20px; font-size: 16px; border: none; border-radius: 5px; cursor: {}; transition: all 0.3s; {}",
                         if can_redo { "pointer" } else { "not-allowed" },
                         if can_redo {
-                            "background-color: #4CAF50; color: white;"
+                            "background-color: var(--success); color: white;"
                         } else {
-                            "background-color: #ccc; color: #666;"
+                            "background-color: var(--disabled); color: var(--disabled-text);"
                         }
                     ),
                     disabled: !can_redo,
@@ -196,12 +827,27 @@ pub fn UndoRedoControls(game: Signal<SudokuGame>) -> Element {
 }
 
 #[component]
-pub fn NumberPanel(game: Signal<SudokuGame>) -> Element {
-    let mut is_note_mode = use_signal(|| false);
+pub fn NumberPanel(game: Signal<SudokuGame>, mut is_note_mode: Signal<bool>) -> Element {
+    let mut touch_start = use_signal(|| None::<TouchStart>);
+    let size = game.read().size;
+    // Lay the numbers out like one box of the board
+    let (_, box_cols) = size.box_dims();
     
     rsx! {
         div {
-            style: "background-color: #f8f9fa; border: 2px solid #dee2e6; border-radius: 8px; padding: 15px; margin-top: 20px;",
+            style: "background-color: var(--surface-alt); border: 2px solid var(--border); border-radius: 8px; padding: 15px; margin-top: 20px; touch-action: pan-y;",
+            // Swiping left switches to notes, swiping right back to numbers
+            ontouchstart: move |event: Event<TouchData>| {
+                touch_start.set(TouchStart::new(&event));
+            },
+            ontouchend: move |event: Event<TouchData>| {
+                if let Some((dx, dy, _)) = touch_start.take().and_then(|start| start.end(&event))
+                    && dx.abs() >= SWIPE_PX
+                    && dx.abs() > 2.0 * dy.abs()
+                {
+                    is_note_mode.set(dx < 0.0);
+                }
+            },
             
             // Toggle button for note/fill mode
             div {
@@ -210,9 +856,9 @@ pub fn NumberPanel(game: Signal<SudokuGame>) -> Element {
                     style: format!(
                         "padding: 8px 16px; border: none; border-radius: 6px; font-weight: bold; cursor: pointer; transition: all 0.2s; {}",
                         if is_note_mode() {
-                            "background-color: #ffc107; color: #000;"
+                            "background-color: var(--note-mode); color: var(--note-mode-text);"
                         } else {
-                            "background-color: #007bff; color: white;"
+                            "background-color: var(--accent); color: white;"
                         }
                     ),
                     onclick: move |_| {
@@ -226,14 +872,14 @@ pub fn NumberPanel(game: Signal<SudokuGame>) -> Element {
                 }
             }
             
-            // Number grid (3x3)
+            // Number grid, shaped like a box of the board
             div {
-                style: "display: grid; grid-template-columns: repeat(3, 1fr); gap: 8px;",
+                style: "display: grid; grid-template-columns: repeat({box_cols}, 1fr); gap: 8px;",
                 
-                for num in 1..=9 {
+                for num in 1..=size.numbers() as u8 {
                     button {
-                        style: "width: 50px; height: 50px; border: 2px solid #6c757d; border-radius: 6px; \
-                               background-color: white; font-size: 18px; font-weight: bold; cursor: pointer; \
+                        style: "width: 50px; height: 50px; border: 2px solid var(--grid-line); border-radius: 6px; \
+                               background-color: var(--cell); color: var(--text); font-size: 18px; font-weight: bold; cursor: pointer; \
                                transition: all 0.2s; display: flex; align-items: center; justify-content: center;",
                         onmouseenter: move |_| {},
                         onmouseleave: move |_| {},
@@ -245,9 +891,12 @@ pub fn NumberPanel(game: Signal<SudokuGame>) -> Element {
                                 } else {
                                     game_state.input_number(num);
                                 }
+                            } else {
+                                // Without a selection, a number shows where it is and can go
+                                game_state.highlighted_number = Some(num);
                             }
                         },
-                        "{num}"
+                        {size.digit_label(num)}
                     }
                 }
             }
@@ -256,111 +905,1443 @@ pub fn NumberPanel(game: Signal<SudokuGame>) -> Element {
 }
 
 #[component]
-pub fn MoveLog(game: Signal<SudokuGame>) -> Element {
+pub fn CagePanel(game: Signal<SudokuGame>) -> Element {
     let game_state = game.read();
-    let move_log = game_state.get_move_log();
+    let Some((row, col)) = game_state.selected_cell else {
+        return rsx! {};
+    };
+    let Some(cage) = game_state.cage_at(row, col) else {
+        return rsx! {};
+    };
 
-    rsx! {
-            div {
-                style: "background-color: white; padding: 20px; border-radius: 10px; box-shadow: 0 2px 4px rgba(0,0,0,0.1); max-height: 500px; overflow-y: auto;",
+    // Combinations must contain every number already placed in the cage
+    let placed: Vec<u8> = cage
+        .cells
+        .iter()
+        .filter_map(|&(r, c)| game_state.grid[r][c])
+        .collect();
+    let size = game_state.size;
+    let combinations: Vec<(String, bool)> = cage
+        .combinations(size.numbers())
+        .into_iter()
+        .map(|numbers| {
+            let fits = placed.iter().all(|num| numbers.contains(num));
+            let label = numbers
+                .iter()
+                .map(|&num| size.digit_label(num))
+                .collect::<Vec<_>>()
+                .join(" ");
+            (label, fits)
+        })
+        .collect();
+    let problem = game_state.cage_problem(&cage).map(|problem| match problem {
+        CageProblem::Duplicate => t!("cage-duplicate"),
+        CageProblem::SumExceeded => t!("cage-sum-exceeded"),
+        CageProblem::WrongSum => t!("cage-wrong-sum"),
+    });
 
-                h3 {
-                    style: "margin-top: 0; margin-bottom: 15px; color: #333; font-size: 18px; border-bottom: 2px solid #2196F3; padding-bottom: 8px;",
-    {t!("move-history")}
-                }
+    rsx! {
+        div {
+            style: "background-color: var(--surface); padding: 20px; border-radius: 10px; box-shadow: 0 2px 4px rgba(0,0,0,0.1); margin-bottom: 20px;",
 
-                if move_log.is_empty() {
-                    div {
-                        style: "text-align: center; color: #666; font-style: italic; padding: 20px;",
-    {t!("no-moves")}
-                    }
-                } else {
-                    div {
-                        style: "font-family: 'Courier New', monospace; font-size: 14px; line-height: 1.6;",
+            h3 {
+                style: "margin-top: 0; margin-bottom: 10px; color: var(--text); font-size: 18px; border-bottom: 2px solid var(--accent-tools); padding-bottom: 8px;",
+                {t!("cage-combinations")}
+            }
 
-                        for (index, log_entry) in move_log.iter().enumerate() {
-                            {
-                                let entry_style = if log_entry.starts_with("► ") {
-                                    "background-color: #e3f2fd; padding: 8px; margin: 2px 0; border-radius: 4px; border-left: 4px solid #2196F3; font-weight: bold;"
-                                } else if log_entry.starts_with("✓ ") {
-                                    "background-color: #f1f8e9; padding: 8px; margin: 2px 0; border-radius: 4px; border-left: 4px solid #4CAF50; color: #2e7d32;"
-                                } else {
-                                    "background-color: #f5f5f5; padding: 8px; margin: 2px 0; border-radius: 4px; border-left: 4px solid #ccc; color: #666;"
-                                };
+            div {
+                style: "color: var(--text); font-size: 14px; margin-bottom: 8px;",
+                {t!("cage-info", sum: cage.sum, cells: cage.cells.len())}
+            }
 
-                                rsx! {
-                                    div {
-                                        key: "{index}",
-                                        style: "{entry_style}",
-                                        "{log_entry}"
-                                    }
-                                }
-                            }
-                        }
-                    }
+            if let Some(problem) = problem {
+                div {
+                    style: "color: var(--error); font-size: 14px; font-weight: bold; margin-bottom: 8px;",
+                    {problem}
+                }
+            }
 
+            div {
+                style: "font-family: 'Courier New', monospace; font-size: 14px; line-height: 1.6;",
+                for (label, fits) in combinations {
                     div {
-                        style: "margin-top: 15px; padding-top: 15px; border-top: 1px solid #eee; font-size: 12px; color: #666;",
-
-                        div { {t!("legend")} }
-                        div { {t!("current-position")} }
-                        div { {t!("completed-moves")} }
-                        div { {t!("future-moves")} }
+                        style: if fits { "color: var(--text);" } else { "color: var(--disabled-text); text-decoration: line-through;" },
+                        "{label}"
                     }
                 }
             }
         }
+    }
 }
 
 #[component]
-pub fn DifficultySelector(game: Signal<SudokuGame>) -> Element {
+pub fn ImportPanel(game: Signal<SudokuGame>) -> Element {
+    let mut text = use_signal(String::new);
+    let mut error = use_signal(|| None::<String>);
+    // SudokuPad and f-puzzles links to the last export
+    let mut export_links = use_signal(|| None::<(String, String)>);
+
     rsx! {
+        div {
+            style: "background-color: var(--surface); padding: 20px; border-radius: 10px; box-shadow: 0 2px 4px rgba(0,0,0,0.1); margin-top: 20px; text-align: left;",
+
+            h3 {
+                style: "margin-top: 0; margin-bottom: 10px; color: var(--text); font-size: 18px; border-bottom: 2px solid var(--accent-import); padding-bottom: 8px;",
+                {t!("import-title")}
+            }
+
             div {
-                style: "display: flex; justify-content: center; align-items: center; gap: 10px; margin-bottom: 20px; \
-                       background-color: white; padding: 15px; border-radius: 10px; \
-                       box-shadow: 0 2px 4px rgba(0,0,0,0.1);",
+                style: "color: var(--muted); font-size: 12px; margin-bottom: 8px;",
+                {t!("import-help")}
+            }
 
-                span {
-                    style: "font-weight: bold; color: #333; margin-right: 10px;",
-                    {t!("difficulty")}
+            textarea {
+                style: "width: 100%; box-sizing: border-box; height: 110px; font-family: 'Courier New', monospace; font-size: 12px; \
+                       border: 1px solid var(--border); border-radius: 5px; padding: 6px;",
+                placeholder: "grid 53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79\nthermo r1c3 r1c4 r1c5\narrow r5c5 r6c5 r7c5\nwhite r2c2 r2c3",
+                value: "{text}",
+                oninput: move |event: Event<FormData>| text.set(event.value()),
+            }
+
+            if let Some(message) = error() {
+                div {
+                    style: "color: var(--error); font-size: 14px; margin-top: 8px;",
+                    "{message}"
                 }
+            }
 
-                button {
-                    style: "padding: 8px 16px; font-size: 14px; background-color: #8BC34A; \
-                           color: white; border: none; border-radius: 5px; cursor: pointer; \
-                           transition: background-color 0.3s;",
-                    onclick: {
-                        let mut game = game.clone();
-                        move |_| {
-                            game.write().reset_with_difficulty(Difficulty::VeryEasy);
+            button {
+                style: "margin-top: 10px; padding: 8px 16px; font-size: 14px; background-color: var(--accent-import); \
+                       color: white; border: none; border-radius: 5px; cursor: pointer; \
+                       transition: background-color 0.3s;",
+                onclick: move |_| {
+                    let imported = if fpuzzles::is_fpuzzles(&text()) {
+                        fpuzzles::parse_fpuzzles(&text()).map_err(|error| fpuzzles_error_message(&error))
+                    } else {
+                        puzzle_format::parse_puzzle(&text()).map_err(|error| import_error_message(&error))
+                    };
+                    match imported {
+                        Ok((rules, puzzle)) => {
+                            game.write().load_puzzle(rules, puzzle);
+                            error.set(None);
                         }
-                    },
-    {t!("very-easy")}
-                }
+                        Err(message) => error.set(Some(message)),
+                    }
+                },
+                {t!("import-button")}
+            }
 
-                button {
-                    style: "padding: 8px 16px; font-size: 14px; background-color: #4CAF50; \
-                           color: white; border: none; border-radius: 5px; cursor: pointer; \
-                           transition: background-color 0.3s;",
-                    onclick: {
-                        let mut game = game.clone();
-                        move |_| {
-                            game.write().reset_with_difficulty(Difficulty::Easy);
+            button {
+                style: "margin-top: 10px; margin-left: 8px; padding: 8px 16px; font-size: 14px; background-color: var(--surface); \
+                       color: var(--accent-import); border: 2px solid var(--accent-import); border-radius: 5px; cursor: pointer;",
+                onclick: move |_| {
+                    let exported = {
+                        let game_state = game.read();
+                        fpuzzles::export_fpuzzles(&game_state.rules, &game_state.puzzle_grid())
+                    };
+                    match exported {
+                        Ok(json) => {
+                            export_links.set(Some(fpuzzles::links(&json)));
+                            error.set(None);
                         }
-                    },
-    {t!("easy")}
+                        Err(export_error) => {
+                            export_links.set(None);
+                            error.set(Some(fpuzzles_error_message(&export_error)));
+                        }
+                    }
+                },
+                {t!("export-button")}
+            }
+
+            if let Some((sudokupad, fpuzzles_link)) = export_links() {
+                textarea {
+                    style: "width: 100%; box-sizing: border-box; height: 60px; margin-top: 10px; font-family: 'Courier New', monospace; \
+                           font-size: 11px; border: 1px solid var(--border); border-radius: 5px; padding: 6px; word-break: break-all;",
+                    readonly: true,
+                    value: "{sudokupad}",
+                }
+                div {
+                    style: "display: flex; gap: 16px; margin-top: 6px; font-size: 14px;",
+                    a { href: "{sudokupad}", target: "_blank", style: "color: var(--accent-import);", {t!("export-open-sudokupad")} }
+                    a { href: "{fpuzzles_link}", target: "_blank", style: "color: var(--accent-import);", {t!("export-open-fpuzzles")} }
                 }
+            }
+        }
+    }
+}
 
-                button {
+fn import_error_message(error: &ImportError) -> String {
+    match error {
+        ImportError::MissingGrid => t!("import-missing-grid"),
+        ImportError::GridLength(length) => t!("import-grid-length", length: length),
+        ImportError::Digit(ch) => t!("import-digit", digit: ch.to_string()),
+        ImportError::Cell(cell) => t!("import-cell", cell: cell),
+        ImportError::UnknownRule(rule) => t!("import-unknown-rule", rule: rule),
+        ImportError::RuleCells(line) => t!("import-rule-cells", line: line),
+    }
+}
+
+fn fpuzzles_error_message(error: &FpuzzlesError) -> String {
+    match error {
+        FpuzzlesError::Decode => t!("fpuzzles-decode"),
+        FpuzzlesError::Size(size) => t!("fpuzzles-size", size: size),
+        FpuzzlesError::Malformed(key) => t!("fpuzzles-malformed", key: key),
+        FpuzzlesError::Unsupported(names) if names == &["samurai"] => t!("fpuzzles-samurai"),
+        FpuzzlesError::Unsupported(names) => t!("fpuzzles-unsupported", constraints: names.join(", ")),
+    }
+}
+
+// What a printed sheet holds
+#[derive(Clone, Copy, PartialEq)]
+enum PrintSource {
+    Givens,
+    Progress,
+    Batch,
+}
+
+#[component]
+pub fn PrintPanel(game: Signal<SudokuGame>) -> Element {
+    let mut options = use_signal(PrintOptions::default);
+    let mut source = use_signal(|| PrintSource::Givens);
+    let mut batch_size = use_signal(|| 8usize);
+    let mut batch_difficulty = use_signal(|| Difficulty::Medium);
+    // `Some(None)` when the last sheet couldn't be saved
+    let mut saved = use_signal(|| None::<Option<SavedSheet>>);
+
+    let mut export = move |format: PrintFormat| {
+        let puzzles = if source() == PrintSource::Batch {
+            // New puzzles use the current puzzle's board and variant
+            let generation_options = game
+                .read()
+                .generation
+                .as_ref()
+                .map(|generation| generation.options.clone())
+                .unwrap_or_default();
+            (0..batch_size())
+                .map(|_| {
+                    let seed = rand::random::<u32>() as u64;
+                    let puzzle = SudokuGame::generate_seeded_puzzle(seed, batch_difficulty(), generation_options.clone());
+                    PrintedPuzzle::from_game(&puzzle, false)
+                })
+                .collect()
+        } else {
+            vec![PrintedPuzzle::from_game(&game.read(), source() == PrintSource::Progress)]
+        };
+        let bytes = print::render(&puzzles, &options(), format);
+        saved.set(Some(print::save_sheet(&bytes, format)));
+    };
+
+    let sources = [
+        (PrintSource::Givens, t!("print-source-givens")),
+        (PrintSource::Progress, t!("print-source-progress")),
+        (PrintSource::Batch, t!("print-source-batch")),
+    ];
+    let toggles = [
+        (t!("print-difficulty-labels"), options().difficulty_labels),
+        (t!("print-seed-labels"), options().seed_labels),
+        (t!("print-answer-key"), options().answer_key),
+    ];
+
+    rsx! {
+        div {
+            style: "background-color: var(--surface); padding: 20px; border-radius: 10px; box-shadow: 0 2px 4px rgba(0,0,0,0.1); margin-top: 20px; text-align: left;",
+
+            h3 {
+                style: "margin-top: 0; margin-bottom: 10px; color: var(--text); font-size: 18px; border-bottom: 2px solid var(--accent-tools); padding-bottom: 8px;",
+                {t!("print-title")}
+            }
+
+            div {
+                style: "display: flex; flex-wrap: wrap; align-items: center; gap: 8px; margin-bottom: 8px;",
+                select {
+                    style: "padding: 6px 10px; font-size: 14px; border: 1px solid var(--border); border-radius: 5px; cursor: pointer;",
+                    onchange: move |event: Event<FormData>| {
+                        if let Some(&(choice, _)) = event.value().parse::<usize>().ok().and_then(|index| sources.get(index)) {
+                            source.set(choice);
+                        }
+                    },
+                    for (index, (choice, label)) in sources.iter().enumerate() {
+                        option {
+                            value: "{index}",
+                            selected: source() == *choice,
+                            "{label}"
+                        }
+                    }
+                }
+
+                if source() == PrintSource::Batch {
+                    input {
+                        r#type: "number",
+                        min: "1",
+                        max: "24",
+                        style: "width: 60px; padding: 5px; font-size: 14px; border: 1px solid var(--border); border-radius: 5px;",
+                        value: "{batch_size}",
+                        oninput: move |event: Event<FormData>| {
+                            if let Ok(count) = event.value().parse::<usize>() {
+                                batch_size.set(count.clamp(1, 24));
+                            }
+                        },
+                    }
+                    select {
+                        style: "padding: 6px 10px; font-size: 14px; border: 1px solid var(--border); border-radius: 5px; cursor: pointer;",
+                        onchange: move |event: Event<FormData>| {
+                            if let Some(difficulty) = event
+                                .value()
+                                .parse::<usize>()
+                                .ok()
+                                .and_then(|index| Difficulty::all().get(index).cloned())
+                            {
+                                batch_difficulty.set(difficulty);
+                            }
+                        },
+                        for (index, difficulty) in Difficulty::all().into_iter().enumerate() {
+                            option {
+                                value: "{index}",
+                                selected: batch_difficulty() == difficulty,
+                                {difficulty_label(&difficulty)}
+                            }
+                        }
+                    }
+                }
+            }
+
+            div {
+                style: "display: flex; flex-wrap: wrap; align-items: center; gap: 10px; margin-bottom: 8px; font-size: 14px; color: var(--text);",
+                span { {t!("print-per-page")} }
+                select {
+                    style: "padding: 6px 10px; font-size: 14px; border: 1px solid var(--border); border-radius: 5px; cursor: pointer;",
+                    onchange: move |event: Event<FormData>| {
+                        if let Ok(per_page) = event.value().parse::<usize>() {
+                            options.write().per_page = per_page;
+                        }
+                    },
+                    for per_page in PrintOptions::PER_PAGE {
+                        option {
+                            value: "{per_page}",
+                            selected: options().per_page == per_page,
+                            "{per_page}"
+                        }
+                    }
+                }
+
+                for (index, (label, checked)) in toggles.into_iter().enumerate() {
+                    label {
+                        style: "display: flex; align-items: center; gap: 5px; cursor: pointer;",
+                        input {
+                            r#type: "checkbox",
+                            checked: checked,
+                            onchange: move |event: Event<FormData>| {
+                                let mut options = options.write();
+                                match index {
+                                    0 => options.difficulty_labels = event.checked(),
+                                    1 => options.seed_labels = event.checked(),
+                                    _ => options.answer_key = event.checked(),
+                                }
+                            },
+                        }
+                        "{label}"
+                    }
+                }
+            }
+
+            div {
+                style: "display: flex; gap: 8px;",
+                for format in [PrintFormat::Svg, PrintFormat::Pdf] {
+                    button {
+                        style: "padding: 8px 16px; font-size: 14px; background-color: var(--accent-tools); \
+                               color: white; border: none; border-radius: 5px; cursor: pointer; \
+                               transition: background-color 0.3s;",
+                        onclick: move |_| export(format),
+                        if format == PrintFormat::Svg { {t!("print-svg")} } else { {t!("print-pdf")} }
+                    }
+                }
+            }
+
+            match saved() {
+                Some(Some(SavedSheet::File(path))) => rsx! {
+                    div {
+                        style: "color: var(--success); font-size: 13px; margin-top: 8px; word-break: break-all;",
+                        {t!("print-saved", path: path)}
+                    }
+                },
+                Some(Some(SavedSheet::Download { href, file_name })) => rsx! {
+                    a {
+                        style: "display: inline-block; margin-top: 8px; font-size: 14px; color: var(--accent-tools);",
+                        href: "{href}",
+                        download: "{file_name}",
+                        {t!("print-download", file: file_name.clone())}
+                    }
+                },
+                Some(None) => rsx! {
+                    div {
+                        style: "color: var(--error); font-size: 14px; margin-top: 8px;",
+                        {t!("print-failed")}
+                    }
+                },
+                None => rsx! {},
+            }
+        }
+    }
+}
+
+#[component]
+pub fn PhotoImportPanel(game: Signal<SudokuGame>) -> Element {
+    // The grid read from the last photo, waiting to be checked
+    let mut scan = use_signal(|| None::<PhotoScan>);
+    let mut error = use_signal(|| None::<String>);
+    let mut reading = use_signal(|| false);
+
+    let open_photo = move |event: Event<FormData>| async move {
+        let Some(files) = event.files() else {
+            return;
+        };
+        let Some(name) = files.files().into_iter().next() else {
+            return;
+        };
+        reading.set(true);
+        let result = match files.read_file(&name).await {
+            Some(bytes) => photo_import::scan_photo(&bytes).map_err(|error| photo_error_message(&error)),
+            None => Err(photo_error_message(&PhotoError::Decode)),
+        };
+        reading.set(false);
+        match result {
+            Ok(found) => {
+                scan.set(Some(found));
+                error.set(None);
+            }
+            Err(message) => {
+                scan.set(None);
+                error.set(Some(message));
+            }
+        }
+    };
+
+    rsx! {
+        div {
+            style: "background-color: var(--surface); padding: 20px; border-radius: 10px; box-shadow: 0 2px 4px rgba(0,0,0,0.1); margin-top: 20px; text-align: left;",
+
+            h3 {
+                style: "margin-top: 0; margin-bottom: 10px; color: var(--text); font-size: 18px; border-bottom: 2px solid var(--accent-photo); padding-bottom: 8px;",
+                {t!("photo-title")}
+            }
+
+            div {
+                style: "color: var(--muted); font-size: 12px; margin-bottom: 8px;",
+                {t!("photo-help")}
+            }
+
+            input {
+                r#type: "file",
+                accept: "image/png,image/jpeg",
+                style: "font-size: 14px;",
+                onchange: open_photo,
+            }
+
+            if reading() {
+                div {
+                    style: "color: var(--muted); font-size: 14px; margin-top: 8px;",
+                    {t!("photo-reading")}
+                }
+            }
+
+            if let Some(message) = error() {
+                div {
+                    style: "color: var(--error); font-size: 14px; margin-top: 8px;",
+                    "{message}"
+                }
+            }
+
+            if let Some(current) = scan() {
+                {
+                    let size = current.size;
+                    let side = size.side();
+                    let (box_rows, box_cols) = size.box_dims();
+                    let unsure = current.cells.iter().flatten().filter(|cell| cell.unsure).count();
+                    rsx! {
+                        div {
+                            style: "display: flex; align-items: center; gap: 8px; margin-top: 12px; font-size: 14px; color: var(--text);",
+                            span { {t!("photo-size")} }
+                            select {
+                                style: "padding: 6px 10px; font-size: 14px; border: 1px solid var(--border); border-radius: 5px; cursor: pointer;",
+                                onchange: move |event: Event<FormData>| {
+                                    if let Some(&size) = event.value().parse::<usize>().ok().and_then(|index| PHOTO_SIZES.get(index))
+                                        && let Some(scan) = scan.write().as_mut()
+                                    {
+                                        scan.resize(size);
+                                    }
+                                },
+                                for (index, choice) in PHOTO_SIZES.into_iter().enumerate() {
+                                    option {
+                                        value: "{index}",
+                                        selected: size == choice,
+                                        {board_size_label(choice)}
+                                    }
+                                }
+                            }
+                        }
+
+                        div {
+                            style: "color: var(--muted); font-size: 12px; margin-top: 8px;",
+                            if unsure > 0 {
+                                {t!("photo-check-unsure", count: unsure)}
+                            } else {
+                                {t!("photo-check")}
+                            }
+                        }
+
+                        div {
+                            style: "display: inline-grid; grid-template-columns: repeat({side}, 30px); margin-top: 8px; \
+                                   border-right: 2px solid var(--grid-line); border-bottom: 2px solid var(--grid-line);",
+                            for (row, cells) in current.cells.iter().enumerate() {
+                                for (col, cell) in cells.iter().enumerate() {
+                                    input {
+                                        key: "{row}-{col}",
+                                        r#type: "text",
+                                        inputmode: "numeric",
+                                        maxlength: "1",
+                                        style: format!(
+                                            "width: 30px; height: 30px; box-sizing: border-box; text-align: center; font-size: 16px; padding: 0; \
+                                             border: none; border-left: {}; border-top: {}; background-color: {};",
+                                            if col % box_cols == 0 { "2px solid var(--grid-line)" } else { "1px solid var(--cell-line)" },
+                                            if row % box_rows == 0 { "2px solid var(--grid-line)" } else { "1px solid var(--cell-line)" },
+                                            if cell.unsure { "var(--highlight)" } else { "var(--cell)" },
+                                        ),
+                                        value: cell.digit.map(|num| size.digit_label(num)).unwrap_or_default(),
+                                        oninput: move |event: Event<FormData>| {
+                                            if let Some(scan) = scan.write().as_mut() {
+                                                // The last character typed replaces the reading
+                                                let digit = event.value().chars().last().and_then(|ch| size.parse_digit(ch));
+                                                scan.cells[row][col] = CellReading { digit, unsure: false };
+                                            }
+                                        },
+                                    }
+                                }
+                            }
+                        }
+
+                        div {
+                            style: "display: flex; gap: 8px; margin-top: 10px;",
+                            button {
+                                style: "padding: 8px 16px; font-size: 14px; background-color: var(--accent-photo); \
+                                       color: white; border: none; border-radius: 5px; cursor: pointer; \
+                                       transition: background-color 0.3s;",
+                                onclick: move |_| {
+                                    if let Some(confirmed) = scan.take() {
+                                        game.write().load_puzzle(Rules::classic(confirmed.size), confirmed.grid());
+                                    }
+                                },
+                                {t!("photo-load")}
+                            }
+                            button {
+                                style: "padding: 8px 16px; font-size: 14px; background-color: var(--surface); \
+                                       color: var(--accent-photo); border: 2px solid var(--accent-photo); border-radius: 5px; cursor: pointer;",
+                                onclick: move |_| scan.set(None),
+                                {t!("photo-cancel")}
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn photo_error_message(error: &PhotoError) -> String {
+    match error {
+        PhotoError::Format => t!("photo-format"),
+        PhotoError::Decode => t!("photo-decode"),
+        PhotoError::UnsupportedJpeg => t!("photo-unsupported-jpeg"),
+        PhotoError::NoGrid => t!("photo-no-grid"),
+    }
+}
+
+#[component]
+pub fn MoveLog(game: Signal<SudokuGame>) -> Element {
+    let game_state = game.read();
+    let move_log = game_state.get_move_log();
+
+    rsx! {
+            div {
+                style: "background-color: var(--surface); padding: 20px; border-radius: 10px; box-shadow: 0 2px 4px rgba(0,0,0,0.1); max-height: 500px; overflow-y: auto;",
+
+                h3 {
+                    style: "margin-top: 0; margin-bottom: 15px; color: var(--text); font-size: 18px; border-bottom: 2px solid #2196F3; padding-bottom: 8px;",
+    {t!("move-history")}
+                }
+
+                if move_log.is_empty() {
+                    div {
+                        style: "text-align: center; color: var(--muted); font-style: italic; padding: 20px;",
+    {t!("no-moves")}
+                    }
+                } else {
+                    div {
+                        style: "font-family: 'Courier New', monospace; font-size: 14px; line-height: 1.6;",
+
+                        for (index, log_entry) in move_log.iter().enumerate() {
+                            {
+                                let entry_style = if log_entry.starts_with("► ") {
+                                    "background-color: var(--selected); padding: 8px; margin: 2px 0; border-radius: 4px; border-left: 4px solid var(--accent); font-weight: bold;"
+                                } else if log_entry.starts_with("✓ ") {
+                                    "background-color: var(--success-background); padding: 8px; margin: 2px 0; border-radius: 4px; border-left: 4px solid var(--success); color: var(--success-text);"
+                                } else {
+                                    "background-color: var(--surface-alt); padding: 8px; margin: 2px 0; border-radius: 4px; border-left: 4px solid var(--cell-line); color: var(--muted);"
+                                };
+
+                                rsx! {
+                                    div {
+                                        key: "{index}",
+                                        style: "{entry_style}",
+                                        "{log_entry}"
+                                    }
+                                }
+                            }
+                        }
+                    }
+
+                    div {
+                        style: "margin-top: 15px; padding-top: 15px; border-top: 1px solid var(--border); font-size: 12px; color: var(--muted);",
+
+                        div { {t!("legend")} }
+                        div { {t!("current-position")} }
+                        div { {t!("completed-moves")} }
+                        div { {t!("future-moves")} }
+                    }
+                }
+            }
+        }
+}
+
+#[component]
+pub fn StatsPanel(stats: Signal<Stats>) -> Element {
+    let stats = stats.read();
+    let tiers: Vec<&TierStats> = Difficulty::all().iter().map(|difficulty| stats.tier(difficulty)).collect();
+    let time = |seconds: Option<u64>| seconds.map_or("–".to_string(), format_duration);
+    let rows: Vec<(String, Vec<String>)> = vec![
+        (t!("stats-started"), tiers.iter().map(|tier| tier.started.to_string()).collect()),
+        (t!("stats-completed"), tiers.iter().map(|tier| tier.completed.to_string()).collect()),
+        (
+            t!("stats-win-rate"),
+            tiers
+                .iter()
+                .map(|tier| tier.win_rate().map_or("–".to_string(), |rate| format!("{:.0}%", rate * 100.0)))
+                .collect(),
+        ),
+        (t!("stats-best-time"), tiers.iter().map(|tier| time(tier.best_seconds)).collect()),
+        (t!("stats-average-time"), tiers.iter().map(|tier| time(tier.average_seconds())).collect()),
+        (t!("stats-hints"), tiers.iter().map(|tier| tier.hints.to_string()).collect()),
+        (t!("stats-mistakes"), tiers.iter().map(|tier| tier.mistakes.to_string()).collect()),
+        (t!("stats-current-streak"), tiers.iter().map(|tier| tier.current_streak.to_string()).collect()),
+        (t!("stats-best-streak"), tiers.iter().map(|tier| tier.best_streak.to_string()).collect()),
+    ];
+
+    rsx! {
+        div {
+            style: "background-color: var(--surface); padding: 20px; border-radius: 10px; box-shadow: 0 2px 4px rgba(0,0,0,0.1); margin-top: 20px; text-align: left;",
+
+            h3 {
+                style: "margin-top: 0; margin-bottom: 10px; color: var(--text); font-size: 18px; border-bottom: 2px solid #FF9800; padding-bottom: 8px;",
+                {t!("stats-title")}
+            }
+
+            table {
+                style: "width: 100%; border-collapse: collapse; font-size: 13px; color: var(--text);",
+                tr {
+                    th {}
+                    for difficulty in Difficulty::all() {
+                        th {
+                            style: "padding: 4px; text-align: right; font-weight: bold; color: var(--muted);",
+                            {difficulty_label(&difficulty)}
+                        }
+                    }
+                }
+                for (label, values) in rows {
+                    tr {
+                        style: "border-top: 1px solid var(--border);",
+                        td {
+                            style: "padding: 4px;",
+                            "{label}"
+                        }
+                        for value in values {
+                            td {
+                                style: "padding: 4px; text-align: right; font-family: 'Courier New', monospace;",
+                                "{value}"
+                            }
+                        }
+                    }
+                }
+            }
+
+            div {
+                style: "color: var(--muted); font-size: 12px; margin-top: 8px;",
+                {t!("stats-help")}
+            }
+        }
+    }
+}
+
+// Minutes and seconds, with hours once a game takes that long
+fn format_duration(seconds: u64) -> String {
+    let (hours, minutes, seconds) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+    if hours > 0 {
+        format!("{hours}:{minutes:02}:{seconds:02}")
+    } else {
+        format!("{minutes}:{seconds:02}")
+    }
+}
+
+fn format_date(unix_seconds: u64) -> String {
+    let (year, month, day) = game_logic::civil_date(unix_seconds);
+    format!("{year}-{month:02}-{day:02}")
+}
+
+#[component]
+pub fn ProfilePanel(profiles: Signal<Profiles>, leaderboard: Signal<Leaderboard>) -> Element {
+    let mut new_name = use_signal(String::new);
+    let mut rename = use_signal(|| None::<String>);
+    let mut name_taken = use_signal(|| false);
+    let mut confirm_remove = use_signal(|| false);
+
+    let current = profiles.read().current().clone();
+    let can_remove = profiles.read().list.len() > 1;
+    let input_style = "flex: 1; padding: 6px; font-size: 14px; border: 1px solid var(--border); border-radius: 5px;";
+    let select_style = "padding: 6px 10px; font-size: 14px; border: 1px solid var(--border); border-radius: 5px; cursor: pointer;";
+    let button_style = "padding: 6px 14px; font-size: 14px; background-color: var(--accent-profile); color: white; border: none; border-radius: 5px; cursor: pointer;";
+
+    rsx! {
+        div {
+            style: "background-color: var(--surface); padding: 20px; border-radius: 10px; box-shadow: 0 2px 4px rgba(0,0,0,0.1); margin-top: 20px; text-align: left;",
+
+            h3 {
+                style: "margin-top: 0; margin-bottom: 10px; color: var(--text); font-size: 18px; border-bottom: 2px solid var(--accent-profile); padding-bottom: 8px;",
+                {t!("profile-title")}
+            }
+
+            div {
+                style: "display: flex; flex-wrap: wrap; align-items: center; gap: 8px; font-size: 14px; color: var(--text);",
+                span { style: "font-size: 28px;", "{current.avatar}" }
+                span { {t!("profile-playing-as")} }
+                select {
+                    style: select_style,
+                    onchange: move |event: Event<FormData>| {
+                        if let Ok(index) = event.value().parse::<usize>() {
+                            profiles.write().select(index);
+                            rename.set(None);
+                            confirm_remove.set(false);
+                        }
+                    },
+                    for (index, profile) in profiles.read().list.iter().enumerate() {
+                        option {
+                            value: "{index}",
+                            selected: profiles.read().current_index() == index,
+                            {profile.label()}
+                        }
+                    }
+                }
+            }
+
+            // Avatar
+            div {
+                style: "display: flex; flex-wrap: wrap; gap: 4px; margin-top: 10px;",
+                for avatar in AVATARS {
+                    button {
+                        style: if current.avatar == avatar {
+                            "font-size: 20px; padding: 2px 6px; border: 2px solid var(--accent-profile); border-radius: 5px; background-color: var(--selected); cursor: pointer;"
+                        } else {
+                            "font-size: 20px; padding: 2px 6px; border: 2px solid transparent; border-radius: 5px; background-color: transparent; cursor: pointer;"
+                        },
+                        title: t!("profile-avatar"),
+                        onclick: move |_| profiles.write().set_avatar(avatar),
+                        "{avatar}"
+                    }
+                }
+            }
+
+            // Preferences
+            div {
+                style: "display: flex; flex-wrap: wrap; align-items: center; gap: 8px; margin-top: 10px; font-size: 14px; color: var(--text);",
+                span { {t!("profile-language")} }
+                select {
+                    style: select_style,
+                    onchange: move |event: Event<FormData>| {
+                        let mut preferences = profiles.peek().current().preferences.clone();
+                        preferences.language = event.value();
+                        profiles.write().set_preferences(preferences);
+                    },
+                    for language in LANGUAGES {
+                        option {
+                            value: language,
+                            selected: current.preferences.language == language,
+                            {language_label(language)}
+                        }
+                    }
+                }
+                span { {t!("profile-start-difficulty")} }
+                select {
+                    style: select_style,
+                    onchange: move |event: Event<FormData>| {
+                        if let Some(difficulty) = event.value().parse::<usize>().ok().and_then(|index| Difficulty::all().get(index).cloned()) {
+                            let mut preferences = profiles.peek().current().preferences.clone();
+                            preferences.difficulty = difficulty;
+                            profiles.write().set_preferences(preferences);
+                        }
+                    },
+                    for (index, difficulty) in Difficulty::all().into_iter().enumerate() {
+                        option {
+                            value: "{index}",
+                            selected: current.preferences.difficulty == difficulty,
+                            {difficulty_label(&difficulty)}
+                        }
+                    }
+                }
+                label {
+                    style: "display: flex; align-items: center; gap: 5px; cursor: pointer;",
+                    input {
+                        r#type: "checkbox",
+                        checked: current.preferences.colorblind,
+                        onchange: move |event: Event<FormData>| {
+                            let mut preferences = profiles.peek().current().preferences.clone();
+                            preferences.colorblind = event.checked();
+                            profiles.write().set_preferences(preferences);
+                        },
+                    }
+                    {t!("profile-colorblind")}
+                }
+            }
+
+            // What the board highlights around the selection
+            div {
+                style: "display: flex; flex-wrap: wrap; align-items: center; gap: 8px; margin-top: 10px; font-size: 14px; color: var(--text);",
+                span { {t!("profile-highlight")} }
+                for (label, checked, set) in [
+                    (t!("highlight-peers"), current.preferences.highlighting.peers, (|highlighting: &mut Highlighting, on| highlighting.peers = on) as fn(&mut Highlighting, bool)),
+                    (t!("highlight-notes"), current.preferences.highlighting.notes, |highlighting, on| highlighting.notes = on),
+                    (t!("highlight-candidates"), current.preferences.highlighting.candidates, |highlighting, on| highlighting.candidates = on),
+                ] {
+                    label {
+                        style: "display: flex; align-items: center; gap: 5px; cursor: pointer;",
+                        input {
+                            r#type: "checkbox",
+                            checked: checked,
+                            onchange: move |event: Event<FormData>| {
+                                let mut preferences = profiles.peek().current().preferences.clone();
+                                set(&mut preferences.highlighting, event.checked());
+                                profiles.write().set_preferences(preferences);
+                            },
+                        }
+                        {label}
+                    }
+                }
+            }
+
+            // Rename and remove
+            div {
+                style: "display: flex; flex-wrap: wrap; gap: 8px; margin-top: 10px;",
+                if let Some(name) = rename() {
+                    input {
+                        r#type: "text",
+                        maxlength: "24",
+                        style: input_style,
+                        value: "{name}",
+                        oninput: move |event: Event<FormData>| {
+                            rename.set(Some(event.value()));
+                            name_taken.set(false);
+                        },
+                    }
+                    button {
+                        style: button_style,
+                        onclick: move |_| {
+                            let old = profiles.peek().current().name.clone();
+                            let name = rename().unwrap_or_default();
+                            if profiles.write().rename(&name) {
+                                leaderboard.write().rename_player(&old, &profiles.peek().current().name);
+                                rename.set(None);
+                            } else {
+                                name_taken.set(true);
+                            }
+                        },
+                        {t!("profile-save")}
+                    }
+                    button {
+                        style: "padding: 6px 14px; font-size: 14px; background-color: #9E9E9E; color: white; border: none; border-radius: 5px; cursor: pointer;",
+                        onclick: move |_| {
+                            rename.set(None);
+                            name_taken.set(false);
+                        },
+                        {t!("profile-cancel")}
+                    }
+                } else {
+                    button {
+                        style: button_style,
+                        onclick: move |_| {
+                            rename.set(Some(profiles.peek().current().name.clone()));
+                            name_taken.set(false);
+                        },
+                        {t!("profile-rename")}
+                    }
+                    if can_remove {
+                        button {
+                            style: "padding: 6px 14px; font-size: 14px; background-color: var(--error); color: white; border: none; border-radius: 5px; cursor: pointer;",
+                            onclick: move |_| {
+                                if !confirm_remove() {
+                                    confirm_remove.set(true);
+                                    return;
+                                }
+                                confirm_remove.set(false);
+                                if let Some(removed) = profiles.write().remove_current() {
+                                    stats::remove(removed.id);
+                                    achievements::remove(removed.id);
+                                }
+                            },
+                            if confirm_remove() {
+                                {t!("profile-remove-confirm", name: current.name.clone())}
+                            } else {
+                                {t!("profile-remove")}
+                            }
+                        }
+                    }
+                }
+            }
+
+            div {
+                style: "display: flex; gap: 8px; margin-top: 10px;",
+                input {
+                    r#type: "text",
+                    maxlength: "24",
+                    style: input_style,
+                    placeholder: t!("profile-new-name"),
+                    value: "{new_name}",
+                    oninput: move |event: Event<FormData>| {
+                        new_name.set(event.value());
+                        name_taken.set(false);
+                    },
+                }
+                button {
+                    style: button_style,
+                    onclick: move |_| {
+                        if profiles.write().add(&new_name()) {
+                            new_name.set(String::new());
+                            rename.set(None);
+                        } else {
+                            name_taken.set(true);
+                        }
+                    },
+                    {t!("profile-add")}
+                }
+            }
+
+            if name_taken() {
+                div {
+                    style: "color: var(--error); font-size: 13px; margin-top: 6px;",
+                    {t!("profile-name-invalid")}
+                }
+            }
+        }
+    }
+}
+
+// Language names are shown in their own language
+fn language_label(language: &str) -> &'static str {
+    match language {
+        "zh-CN" => "中文",
+        _ => "English",
+    }
+}
+
+// Which leaderboard is shown
+#[derive(Clone, Copy, PartialEq)]
+enum Board {
+    Difficulty(usize),
+    // The current seeded puzzle
+    ThisPuzzle,
+}
+
+#[component]
+pub fn LeaderboardPanel(game: Signal<SudokuGame>, leaderboard: Signal<Leaderboard>, profiles: Signal<Profiles>) -> Element {
+    // `None` follows the current game
+    let mut board = use_signal(|| None::<Board>);
+    let mut player = use_signal(|| None::<String>);
+    let mut no_hints = use_signal(|| false);
+
+    let generation = game.read().generation.clone();
+    let options = generation
+        .as_ref()
+        .map(|generation| generation.options.clone())
+        .unwrap_or_default();
+    let this_puzzle = generation.as_ref().and_then(|generation| {
+        generation.seed.map(|seed| {
+            let scope = Scope::Puzzle {
+                seed,
+                difficulty: generation.difficulty.clone(),
+                options: options.clone(),
+            };
+            let label = if generation.daily {
+                // Daily seeds are the date written as YYYYMMDD
+                let date = format!("{}-{:02}-{:02}", seed / 10000, seed / 100 % 100, seed % 100);
+                t!("leaderboard-daily", date: date)
+            } else {
+                t!("leaderboard-seed", seed: seed)
+            };
+            (scope, label)
+        })
+    });
+    let current_difficulty = generation
+        .as_ref()
+        .and_then(|generation| Difficulty::all().iter().position(|tier| *tier == generation.difficulty))
+        .unwrap_or(0);
+    let shown = match board() {
+        Some(Board::ThisPuzzle) if this_puzzle.is_none() => Board::Difficulty(current_difficulty),
+        Some(choice) => choice,
+        None if generation.as_ref().is_some_and(|generation| generation.daily) => Board::ThisPuzzle,
+        None => Board::Difficulty(current_difficulty),
+    };
+    let scope = match (shown, &this_puzzle) {
+        (Board::ThisPuzzle, Some((scope, _))) => scope.clone(),
+        (Board::Difficulty(index), _) => Scope::Difficulty {
+            difficulty: Difficulty::all()[index.min(Difficulty::all().len() - 1)].clone(),
+            size: options.size,
+            variant: options.variant,
+        },
+        (Board::ThisPuzzle, None) => unreachable!("the puzzle board is only shown for seeded games"),
+    };
+
+    let current_player = profiles.read().current().name.clone();
+    let leaderboard = leaderboard.read();
+    let ranking = leaderboard.ranking(&Filter {
+        scope: scope.clone(),
+        player: player(),
+        no_hints: no_hints(),
+    });
+    let personal_best = leaderboard
+        .ranking(&Filter {
+            scope,
+            player: Some(current_player.clone()),
+            no_hints: no_hints(),
+        })
+        .first()
+        .map(|entry| entry.seconds);
+    let mut players: Vec<String> = profiles.read().list.iter().map(|profile| profile.name.clone()).collect();
+    for entry in &leaderboard.entries {
+        if !players.contains(&entry.player) {
+            players.push(entry.player.clone());
+        }
+    }
+
+    rsx! {
+        div {
+            style: "background-color: var(--surface); padding: 20px; border-radius: 10px; box-shadow: 0 2px 4px rgba(0,0,0,0.1); margin-top: 20px; text-align: left;",
+
+            h3 {
+                style: "margin-top: 0; margin-bottom: 10px; color: var(--text); font-size: 18px; border-bottom: 2px solid #FFC107; padding-bottom: 8px;",
+                {t!("leaderboard-title")}
+            }
+
+            div {
+                style: "display: flex; flex-wrap: wrap; align-items: center; gap: 8px; margin-bottom: 8px; font-size: 14px; color: var(--text);",
+                select {
+                    style: "padding: 6px 10px; font-size: 14px; border: 1px solid var(--border); border-radius: 5px; cursor: pointer;",
+                    onchange: move |event: Event<FormData>| {
+                        let value = event.value();
+                        board.set(Some(match value.parse::<usize>() {
+                            Ok(index) => Board::Difficulty(index),
+                            Err(_) => Board::ThisPuzzle,
+                        }));
+                    },
+                    for (index, difficulty) in Difficulty::all().into_iter().enumerate() {
+                        option {
+                            value: "{index}",
+                            selected: shown == Board::Difficulty(index),
+                            {format!("{} · {} {}", difficulty_label(&difficulty), board_size_label(options.size), variant_label(options.variant))}
+                        }
+                    }
+                    if let Some((_, label)) = &this_puzzle {
+                        option {
+                            value: "puzzle",
+                            selected: shown == Board::ThisPuzzle,
+                            "{label}"
+                        }
+                    }
+                }
+
+                select {
+                    style: "padding: 6px 10px; font-size: 14px; border: 1px solid var(--border); border-radius: 5px; cursor: pointer;",
+                    onchange: move |event: Event<FormData>| {
+                        let value = event.value();
+                        player.set((!value.is_empty()).then_some(value));
+                    },
+                    option {
+                        value: "",
+                        selected: player().is_none(),
+                        {t!("leaderboard-all-players")}
+                    }
+                    for name in players {
+                        option {
+                            value: "{name}",
+                            selected: player().as_ref() == Some(&name),
+                            "{name}"
+                        }
+                    }
+                }
+
+                label {
+                    style: "display: flex; align-items: center; gap: 5px; cursor: pointer;",
+                    input {
+                        r#type: "checkbox",
+                        checked: no_hints(),
+                        onchange: move |event: Event<FormData>| no_hints.set(event.checked()),
+                    }
+                    {t!("leaderboard-no-hints")}
+                }
+            }
+
+            if let Some(best) = personal_best {
+                div {
+                    style: "font-size: 14px; color: var(--text); margin-bottom: 8px;",
+                    {t!("leaderboard-personal-best", time: format_duration(best))}
+                }
+            }
+
+            if ranking.is_empty() {
+                div {
+                    style: "text-align: center; color: var(--muted); font-style: italic; padding: 12px;",
+                    {t!("leaderboard-empty")}
+                }
+            } else {
+                table {
+                    style: "width: 100%; border-collapse: collapse; font-size: 13px; color: var(--text);",
+                    tr {
+                        for heading in [
+                            "#".to_string(),
+                            t!("leaderboard-player"),
+                            t!("leaderboard-time"),
+                            t!("stats-hints"),
+                            t!("stats-mistakes"),
+                            t!("leaderboard-date"),
+                        ] {
+                            th {
+                                style: "padding: 4px; text-align: left; color: var(--muted);",
+                                "{heading}"
+                            }
+                        }
+                    }
+                    for (rank, entry) in ranking.iter().take(10).enumerate() {
+                        tr {
+                            style: if entry.player == current_player {
+                                "border-top: 1px solid var(--border); background-color: var(--highlight);"
+                            } else {
+                                "border-top: 1px solid var(--border);"
+                            },
+                            td { style: "padding: 4px;", "{rank + 1}" }
+                            td {
+                                style: "padding: 4px;",
+                                {profiles.read().find(&entry.player).map_or(entry.player.clone(), |profile| profile.label())}
+                            }
+                            td { style: "padding: 4px; font-family: 'Courier New', monospace;", {format_duration(entry.seconds)} }
+                            td { style: "padding: 4px;", "{entry.hints}" }
+                            td { style: "padding: 4px;", "{entry.mistakes}" }
+                            td { style: "padding: 4px; color: var(--muted);", {format_date(entry.finished_at)} }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[component]
+pub fn AchievementsPanel(achievements: Signal<Achievements>) -> Element {
+    let achievements = achievements.read();
+
+    rsx! {
+        div {
+            style: "background-color: var(--surface); padding: 20px; border-radius: 10px; box-shadow: 0 2px 4px rgba(0,0,0,0.1); margin-top: 20px; text-align: left;",
+
+            h3 {
+                style: "margin-top: 0; margin-bottom: 10px; color: var(--text); font-size: 18px; border-bottom: 2px solid #9C27B0; padding-bottom: 8px;",
+                {t!("achievements-title", unlocked: achievements.unlocked_count(), total: ACHIEVEMENTS.len())}
+            }
+
+            for (index, achievement) in ACHIEVEMENTS.iter().enumerate() {
+                div {
+                    style: if achievements.is_unlocked(index) {
+                        "display: flex; align-items: center; gap: 10px; padding: 6px 0; border-top: 1px solid var(--border);"
+                    } else {
+                        "display: flex; align-items: center; gap: 10px; padding: 6px 0; border-top: 1px solid var(--border); opacity: 0.55;"
+                    },
+                    span {
+                        style: if achievements.is_unlocked(index) { "font-size: 24px;" } else { "font-size: 24px; filter: grayscale(1);" },
+                        "{achievement.icon}"
+                    }
+                    div {
+                        style: "flex: 1; font-size: 13px; color: var(--text);",
+                        div {
+                            style: "font-weight: bold;",
+                            {achievement_title(achievement)}
+                        }
+                        div {
+                            style: "color: var(--muted);",
+                            {t!(&format!("achievement-{}-description", achievement.key))}
+                        }
+                        if achievement.goal > 1 && !achievements.is_unlocked(index) {
+                            div {
+                                style: "display: flex; align-items: center; gap: 6px; margin-top: 3px;",
+                                div {
+                                    style: "flex: 1; height: 6px; background-color: var(--border); border-radius: 3px; overflow: hidden;",
+                                    div {
+                                        style: "height: 100%; background-color: #9C27B0; width: {achievements.progress[index] * 100 / achievement.goal}%;",
+                                    }
+                                }
+                                span {
+                                    style: "color: var(--muted); font-size: 12px;",
+                                    "{achievements.progress[index]}/{achievement.goal}"
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn achievement_title(achievement: &Achievement) -> String {
+    t!(&format!("achievement-{}", achievement.key))
+}
+
+// Unlocks pop up in a corner and fade away on their own
+#[component]
+pub fn AchievementToasts(unlocked: Signal<Vec<&'static Achievement>>) -> Element {
+    rsx! {
+        style {
+            "@keyframes achievement-toast {{
+                0% {{ opacity: 0; transform: translateY(10px); }}
+                8%, 85% {{ opacity: 1; transform: none; }}
+                100% {{ opacity: 0; visibility: hidden; }}
+            }}"
+        }
+        div {
+            style: "position: fixed; right: 20px; bottom: 20px; display: flex; flex-direction: column; gap: 8px; z-index: 10; pointer-events: none;",
+            for (index, achievement) in unlocked.read().iter().enumerate() {
+                div {
+                    key: "{index}",
+                    style: "display: flex; align-items: center; gap: 10px; padding: 10px 14px; background-color: #4A148C; color: white; border-radius: 8px; box-shadow: 0 4px 12px rgba(0,0,0,0.25); font-size: 14px; text-align: left; animation: achievement-toast 5s ease forwards;",
+                    span { style: "font-size: 24px;", "{achievement.icon}" }
+                    div {
+                        div { style: "font-size: 12px; opacity: 0.8;", {t!("achievement-unlocked")} }
+                        div { style: "font-weight: bold;", {achievement_title(achievement)} }
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn theme_label(theme: &Theme) -> String {
+    match theme {
+        Theme::System => t!("theme-system"),
+        Theme::Light => t!("theme-light"),
+        Theme::Dark => t!("theme-dark"),
+        Theme::HighContrast => t!("theme-high-contrast"),
+        Theme::Custom(name) => name.clone(),
+    }
+}
+
+fn set_theme(mut profiles: Signal<Profiles>, theme: Theme) {
+    let mut preferences = profiles.peek().current().preferences.clone();
+    preferences.theme = theme;
+    profiles.write().set_preferences(preferences);
+}
+
+#[component]
+pub fn ThemeSelector(profiles: Signal<Profiles>, palettes: Signal<CustomPalettes>) -> Element {
+    let current = profiles.read().current().preferences.theme.clone();
+    let themes: Vec<Theme> = Theme::builtin()
+        .into_iter()
+        .chain(palettes.read().list.iter().map(|(name, _)| Theme::Custom(name.clone())))
+        .collect();
+
+    rsx! {
+        select {
+            style: "padding: 8px 10px; font-size: 14px; border: 1px solid var(--border); border-radius: 5px; cursor: pointer;",
+            title: t!("theme"),
+            onchange: move |event: Event<FormData>| set_theme(profiles, Theme::from_key(&event.value())),
+            for theme in themes {
+                option {
+                    value: theme.key(),
+                    selected: theme == current,
+                    {format!("🎨 {}", theme_label(&theme))}
+                }
+            }
+        }
+    }
+}
+
+#[component]
+pub fn PaletteEditor(profiles: Signal<Profiles>, mut palettes: Signal<CustomPalettes>) -> Element {
+    // The palette being edited, shown on the page until it is saved or discarded
+    let mut draft = use_signal(|| None::<Palette>);
+    let mut name = use_signal(String::new);
+    let mut name_missing = use_signal(|| false);
+    let theme = profiles.read().current().preferences.theme.clone();
+    let saved_palette = match &theme {
+        Theme::Custom(name) if palettes.read().find(name).is_some() => Some(name.clone()),
+        _ => None,
+    };
+    let delete_label = saved_palette.as_ref().map(|name| t!("palette-delete", name: name.clone()));
+    let button_style = "padding: 6px 14px; font-size: 14px; background-color: #E91E63; color: white; border: none; border-radius: 5px; cursor: pointer;";
+
+    rsx! {
+        div {
+            style: "background-color: var(--surface); padding: 20px; border-radius: 10px; box-shadow: 0 2px 4px rgba(0,0,0,0.1); margin-top: 20px; text-align: left;",
+
+            h3 {
+                style: "margin-top: 0; margin-bottom: 10px; color: var(--text); font-size: 18px; border-bottom: 2px solid #E91E63; padding-bottom: 8px;",
+                {t!("palette-title")}
+            }
+
+            if let Some(palette) = draft() {
+                // Later rules win, so this previews the draft over the theme
+                style { {palette.stylesheet()} }
+
+                div {
+                    style: "display: grid; grid-template-columns: 1fr 1fr; gap: 4px 12px; font-size: 13px; color: var(--text);",
+                    for color in Color::all() {
+                        label {
+                            style: "display: flex; align-items: center; gap: 6px; cursor: pointer;",
+                            input {
+                                r#type: "color",
+                                style: "width: 32px; height: 22px; padding: 0; border: 1px solid var(--border);",
+                                value: palette.get(color),
+                                oninput: move |event: Event<FormData>| {
+                                    if let Some(draft) = draft.write().as_mut() {
+                                        draft.set(color, &event.value());
+                                    }
+                                },
+                            }
+                            {t!(&format!("palette-color-{}", color.key()))}
+                        }
+                    }
+                }
+
+                div {
+                    style: "display: flex; gap: 8px; margin-top: 12px;",
+                    input {
+                        r#type: "text",
+                        maxlength: "24",
+                        style: "flex: 1; padding: 6px; font-size: 14px; border: 1px solid var(--border); border-radius: 5px;",
+                        placeholder: t!("palette-name"),
+                        value: "{name}",
+                        oninput: move |event: Event<FormData>| {
+                            name.set(event.value());
+                            name_missing.set(false);
+                        },
+                    }
+                    button {
+                        style: button_style,
+                        onclick: move |_| {
+                            let Some(palette) = draft() else {
+                                return;
+                            };
+                            match palettes.write().save_palette(&name(), palette) {
+                                Some(saved) => {
+                                    set_theme(profiles, Theme::Custom(saved));
+                                    draft.set(None);
+                                }
+                                None => name_missing.set(true),
+                            }
+                        },
+                        {t!("palette-save")}
+                    }
+                    button {
+                        style: "padding: 6px 14px; font-size: 14px; background-color: #9E9E9E; color: white; border: none; border-radius: 5px; cursor: pointer;",
+                        onclick: move |_| {
+                            draft.set(None);
+                            name_missing.set(false);
+                        },
+                        {t!("palette-discard")}
+                    }
+                }
+
+                if name_missing() {
+                    div {
+                        style: "color: var(--error); font-size: 13px; margin-top: 6px;",
+                        {t!("palette-name-missing")}
+                    }
+                }
+            } else {
+                div {
+                    style: "color: var(--muted); font-size: 12px; margin-bottom: 8px;",
+                    {t!("palette-help")}
+                }
+                div {
+                    style: "display: flex; gap: 8px;",
+                    button {
+                        style: button_style,
+                        onclick: move |_| {
+                            let theme = profiles.peek().current().preferences.theme.clone();
+                            draft.set(Some(theme.palette(&palettes.peek())));
+                            name.set(match theme {
+                                Theme::Custom(name) => name,
+                                _ => String::new(),
+                            });
+                        },
+                        {t!("palette-customize")}
+                    }
+                    if let (Some(saved), Some(label)) = (saved_palette, delete_label) {
+                        button {
+                            style: "padding: 6px 14px; font-size: 14px; background-color: var(--error); color: white; border: none; border-radius: 5px; cursor: pointer;",
+                            onclick: move |_| {
+                                palettes.write().remove(&saved);
+                                set_theme(profiles, Theme::System);
+                            },
+                            "{label}"
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[component]
+pub fn DifficultySelector(game: Signal<SudokuGame>) -> Element {
+    let mut options = use_signal(GenerationOptions::default);
+    // A seed from a printed sheet or the leaderboard; blank for a new puzzle
+    let mut seed = use_signal(String::new);
+    let seed_is_valid = seed().trim().is_empty() || seed().trim().parse::<u64>().is_ok();
+
+    rsx! {
+            div {
+                style: "display: flex; flex-wrap: wrap; justify-content: center; align-items: center; gap: 10px; margin-bottom: 20px; \
+                       background-color: var(--surface); padding: 15px; border-radius: 10px; \
+                       box-shadow: 0 2px 4px rgba(0,0,0,0.1);",
+
+                span {
+                    style: "font-weight: bold; color: var(--text); margin-right: 10px;",
+                    {t!("difficulty")}
+                }
+
+                button {
+                    style: "padding: 8px 16px; font-size: 14px; background-color: #8BC34A; \
+                           color: white; border: none; border-radius: 5px; cursor: pointer; \
+                           transition: background-color 0.3s;",
+                    onclick: move |_| start_game(game, Difficulty::VeryEasy, options(), &seed()),
+    {t!("very-easy")}
+                }
+
+                button {
+                    style: "padding: 8px 16px; font-size: 14px; background-color: #4CAF50; \
+                           color: white; border: none; border-radius: 5px; cursor: pointer; \
+                           transition: background-color 0.3s;",
+                    onclick: move |_| start_game(game, Difficulty::Easy, options(), &seed()),
+    {t!("easy")}
+                }
+
+                button {
                     style: "padding: 8px 16px; font-size: 14px; background-color: #FF9800; \
                            color: white; border: none; border-radius: 5px; cursor: pointer; \
                            transition: background-color 0.3s;",
-                    onclick: {
-                        let mut game = game.clone();
-                        move |_| {
-                            game.write().reset_with_difficulty(Difficulty::Medium);
-                        }
-                    },
+                    onclick: move |_| start_game(game, Difficulty::Medium, options(), &seed()),
     {t!("medium")}
                 }
 
@@ -368,18 +2349,236 @@ pub fn DifficultySelector(game: Signal<SudokuGame>) -> Element {
                     style: "padding: 8px 16px; font-size: 14px; background-color: #f44336; \
                            color: white; border: none; border-radius: 5px; cursor: pointer; \
                            transition: background-color 0.3s;",
-                    onclick: {
-                        let mut game = game.clone();
-                        move |_| {
-                            game.write().reset_with_difficulty(Difficulty::Hard);
+                    onclick: move |_| start_game(game, Difficulty::Hard, options(), &seed()),
+    {t!("hard")}
+                }
+
+                button {
+                    style: "padding: 8px 16px; font-size: 14px; background-color: #1976D2; \
+                           color: white; border: none; border-radius: 5px; cursor: pointer; \
+                           transition: background-color 0.3s;",
+                    onclick: move |_| game.set(SudokuGame::daily_puzzle()),
+                    {t!("daily-puzzle")}
+                }
+
+                input {
+                    r#type: "text",
+                    inputmode: "numeric",
+                    style: format!(
+                        "width: 110px; padding: 6px 10px; font-size: 14px; border: 1px solid {}; border-radius: 5px;",
+                        if seed_is_valid { "var(--border)" } else { "var(--error)" }
+                    ),
+                    placeholder: t!("seed-placeholder"),
+                    title: t!("seed-title"),
+                    aria_label: t!("seed-title"),
+                    value: "{seed}",
+                    oninput: move |event: Event<FormData>| seed.set(event.value()),
+                }
+
+                {
+                    // Killer cages and Jigsaw regions are drawn for each generated puzzle,
+                    // so there is no fixed layout to enter givens into
+                    let editor_rules = options().fixed_rules();
+                    let can_edit = editor_rules.is_some();
+                    rsx! {
+                        button {
+                            style: format!(
+                                "padding: 8px 16px; font-size: 14px; color: white; border: none; border-radius: 5px; \
+                                 cursor: {}; transition: background-color 0.3s; background-color: {};",
+                                if can_edit { "pointer" } else { "not-allowed" },
+                                if can_edit { "var(--accent-editor)" } else { "var(--disabled)" }
+                            ),
+                            disabled: !can_edit,
+                            onclick: move |_| {
+                                if let Some(rules) = editor_rules.clone() {
+                                    game.write().start_editor(rules);
+                                }
+                            },
+                            {t!("create-puzzle")}
+                        }
+                    }
+                }
+
+                span {
+                    style: "font-weight: bold; color: var(--text); margin-left: 20px;",
+                    {t!("board-size")}
+                }
+
+                select {
+                    style: "padding: 6px 10px; font-size: 14px; border: 1px solid var(--border); border-radius: 5px; cursor: pointer;",
+                    onchange: move |event: Event<FormData>| {
+                        if let Some(size) = event
+                            .value()
+                            .parse::<usize>()
+                            .ok()
+                            .and_then(|index| BoardSize::all().get(index).copied())
+                        {
+                            let mut options = options.write();
+                            options.size = size;
+                            if !options.supports_global_rules() {
+                                options.global_rules.clear();
+                            }
                         }
                     },
-    {t!("hard")}
+                    for (index, size) in BoardSize::all().into_iter().enumerate() {
+                        option {
+                            value: "{index}",
+                            selected: options().size == size,
+                            disabled: !options().variant.supports(size),
+                            {board_size_label(size)}
+                        }
+                    }
+                }
+
+                span {
+                    style: "font-weight: bold; color: var(--text); margin-left: 10px;",
+                    {t!("variant")}
+                }
+
+                select {
+                    style: "padding: 6px 10px; font-size: 14px; border: 1px solid var(--border); border-radius: 5px; cursor: pointer;",
+                    onchange: move |event: Event<FormData>| {
+                        if let Some(variant) = event
+                            .value()
+                            .parse::<usize>()
+                            .ok()
+                            .and_then(|index| Variant::all().get(index).copied())
+                        {
+                            let mut options = options.write();
+                            options.variant = variant;
+                            if !variant.supports(options.size) {
+                                options.size = BoardSize::Nine;
+                            }
+                            if !options.supports_global_rules() {
+                                options.global_rules.clear();
+                            }
+                        }
+                    },
+                    for (index, variant) in Variant::all().into_iter().enumerate() {
+                        option {
+                            value: "{index}",
+                            selected: options().variant == variant,
+                            {variant_label(variant)}
+                        }
+                    }
+                }
+
+                span {
+                    style: "font-weight: bold; color: var(--text); margin-left: 10px;",
+                    {t!("symmetry")}
+                }
+
+                select {
+                    style: "padding: 6px 10px; font-size: 14px; border: 1px solid var(--border); border-radius: 5px; cursor: pointer;",
+                    onchange: move |event: Event<FormData>| {
+                        if let Some(symmetry) = event
+                            .value()
+                            .parse::<usize>()
+                            .ok()
+                            .and_then(|index| Symmetry::all().get(index).copied())
+                        {
+                            options.write().symmetry = symmetry;
+                        }
+                    },
+                    for (index, symmetry) in Symmetry::all().into_iter().enumerate() {
+                        option {
+                            value: "{index}",
+                            selected: options().symmetry == symmetry,
+                            {symmetry_label(symmetry)}
+                        }
+                    }
+                }
+
+                label {
+                    style: "display: flex; align-items: center; gap: 5px; color: var(--text); cursor: pointer;",
+                    input {
+                        r#type: "checkbox",
+                        checked: options().minimal,
+                        onchange: move |event: Event<FormData>| {
+                            options.write().minimal = event.checked();
+                        },
+                    }
+                    {t!("minimal-puzzle")}
+                }
+
+                for rule in GlobalRule::all() {
+                    label {
+                        style: format!(
+                            "display: flex; align-items: center; gap: 5px; color: {}; cursor: {};",
+                            if options().supports_global_rules() { "var(--text)" } else { "var(--disabled-text)" },
+                            if options().supports_global_rules() { "pointer" } else { "not-allowed" }
+                        ),
+                        input {
+                            r#type: "checkbox",
+                            checked: options().global_rules.contains(&rule),
+                            disabled: !options().supports_global_rules(),
+                            onchange: move |event: Event<FormData>| {
+                                let mut options = options.write();
+                                let enabled = event.checked();
+                                // Keep the rules in a fixed order so equal choices compare equal
+                                options.global_rules = GlobalRule::all()
+                                    .into_iter()
+                                    .filter(|&other| {
+                                        if other == rule {
+                                            enabled
+                                        } else {
+                                            options.global_rules.contains(&other)
+                                        }
+                                    })
+                                    .collect();
+                            },
+                        }
+                        {global_rule_label(rule)}
+                    }
                 }
             }
         }
 }
 
+// A puzzle of `difficulty`, made from `seed` when it holds one, so a printed
+// or ranked puzzle can be played again
+fn start_game(mut game: Signal<SudokuGame>, difficulty: Difficulty, options: GenerationOptions, seed: &str) {
+    match seed.trim().parse() {
+        Ok(seed) => game.set(SudokuGame::generate_seeded_puzzle(seed, difficulty, options)),
+        Err(_) => game.write().reset_with_options(difficulty, options),
+    }
+}
+
+fn board_size_label(size: BoardSize) -> String {
+    match size {
+        BoardSize::Samurai => t!("board-samurai"),
+        _ => format!("{0}×{0}", size.side()),
+    }
+}
+
+fn variant_label(variant: Variant) -> String {
+    match variant {
+        Variant::Classic => t!("variant-classic"),
+        Variant::Diagonal => t!("variant-diagonal"),
+        Variant::Killer => t!("variant-killer"),
+        Variant::Jigsaw => t!("variant-jigsaw"),
+        Variant::Sandwich => t!("variant-sandwich"),
+        Variant::XSums => t!("variant-x-sums"),
+    }
+}
+
+fn global_rule_label(rule: GlobalRule) -> String {
+    match rule {
+        GlobalRule::AntiKnight => t!("rule-anti-knight"),
+        GlobalRule::AntiKing => t!("rule-anti-king"),
+        GlobalRule::NonConsecutive => t!("rule-non-consecutive"),
+    }
+}
+
+fn symmetry_label(symmetry: Symmetry) -> String {
+    match symmetry {
+        Symmetry::None => t!("symmetry-none"),
+        Symmetry::Rotational => t!("symmetry-rotational"),
+        Symmetry::Diagonal => t!("symmetry-diagonal"),
+        Symmetry::Mirror => t!("symmetry-mirror"),
+    }
+}
+
 #[component]
 pub fn GameControls(game: Signal<SudokuGame>) -> Element {
     rsx! {
@@ -387,7 +2586,7 @@ pub fn GameControls(game: Signal<SudokuGame>) -> Element {
                 style: "display: flex; justify-content: center; gap: 15px; margin-bottom: 20px;",
 
                 button {
-                    style: "padding: 10px 20px; font-size: 16px; background-color: #FF9800; \
+                    style: "padding: 10px 20px; font-size: 16px; background-color: var(--accent-hint); \
                            color: white; border: none; border-radius: 5px; cursor: pointer; \
                            transition: background-color 0.3s;",
                     onclick: {
@@ -402,12 +2601,149 @@ pub fn GameControls(game: Signal<SudokuGame>) -> Element {
         }
 }
 
+#[component]
+pub fn EditorPanel(game: Signal<SudokuGame>, ambiguity: Memo<Option<AmbiguityReport>>) -> Element {
+    let game_state = game.read();
+    let size = game_state.size;
+    let solution_count = game_state.solution_count();
+    let filled_count = game_state.filled_count();
+
+    let (status, status_color) = match solution_count {
+        0 => (t!("editor-no-solution"), "#d32f2f"),
+        1 => (t!("editor-unique-solution"), "#2e7d32"),
+        _ => (t!("editor-multiple-solutions"), "#ef6c00"),
+    };
+    let can_lock = solution_count > 0 && filled_count > 0;
+
+    rsx! {
+        div {
+            style: "background-color: var(--surface); padding: 15px 20px; border-radius: 10px; \
+                   box-shadow: 0 2px 4px rgba(0,0,0,0.1); margin-bottom: 20px; min-width: 300px;",
+
+            h3 {
+                style: "margin-top: 0; margin-bottom: 10px; color: var(--text); font-size: 18px;",
+                {t!("editor-title")}
+            }
+
+            div {
+                style: "font-size: 16px; font-weight: bold; color: {status_color}; margin-bottom: 8px;",
+                "{status}"
+            }
+
+            div {
+                style: "color: var(--muted); margin-bottom: 8px;",
+                {t!("editor-givens", count: filled_count)}
+            }
+
+            if solution_count == 1 {
+                div {
+                    style: "color: var(--muted); margin-bottom: 8px;",
+                    {t!("editor-difficulty", difficulty: difficulty_label(&game_state.estimated_difficulty()))}
+                }
+            }
+
+            if let Some(report) = ambiguity() {
+                div {
+                    style: "color: var(--muted); font-size: 14px; margin-bottom: 8px;",
+                    {t!("ambiguity-hint")}
+                }
+
+                div {
+                    style: "color: var(--text); font-size: 14px; margin-bottom: 8px;",
+                    {t!("ambiguity-suggestion")}
+                    if !report.complete {
+                        div {
+                            style: "color: var(--muted); font-size: 13px; margin-top: 4px;",
+                            {t!("ambiguity-incomplete")}
+                        }
+                    }
+                    div {
+                        style: "font-family: 'Courier New', monospace; color: var(--accent-editor); margin-top: 4px;",
+                        {
+                            report
+                                .suggested_givens
+                                .iter()
+                                .map(|(row, col, num)| format!("R{}C{}={}", row + 1, col + 1, size.digit_label(*num)))
+                                .collect::<Vec<_>>()
+                                .join(", ")
+                        }
+                    }
+                }
+
+                button {
+                    style: "padding: 8px 16px; font-size: 14px; background-color: var(--accent-editor); \
+                           color: white; border: none; border-radius: 5px; cursor: pointer; \
+                           transition: background-color 0.3s;",
+                    onclick: move |_| {
+                        game.write().add_givens(&report.suggested_givens);
+                    },
+                    {t!("add-suggested-givens")}
+                }
+            }
+
+            div {
+                style: "display: flex; justify-content: center; gap: 15px; margin-top: 15px;",
+
+                button {
+                    style: format!(
+                        "padding: 10px 20px; font-size: 16px; border: none; border-radius: 5px; cursor: {}; transition: all 0.3s; {}",
+                        if can_lock { "pointer" } else { "not-allowed" },
+                        if can_lock {
+                            "background-color: #4CAF50; color: white;"
+                        } else {
+                            "background-color: var(--disabled); color: var(--disabled-text);"
+                        }
+                    ),
+                    disabled: !can_lock,
+                    onclick: move |_| {
+                        game.write().lock_givens();
+                    },
+                    {t!("lock-givens")}
+                }
+
+                button {
+                    style: "padding: 10px 20px; font-size: 16px; background-color: #f44336; \
+                           color: white; border: none; border-radius: 5px; cursor: pointer; \
+                           transition: background-color 0.3s;",
+                    onclick: move |_| {
+                        let rules = game.read().rules.clone();
+                        game.write().start_editor(rules);
+                    },
+                    {t!("clear-grid")}
+                }
+            }
+        }
+    }
+}
+
+#[component]
+pub fn AmbiguityNotice(ambiguity: Memo<Option<AmbiguityReport>>) -> Element {
+    rsx! {
+        if ambiguity.read().is_some() {
+            div {
+                style: "background-color: var(--surface); color: var(--text); padding: 10px 15px; border-radius: 8px; \
+                       border-left: 4px solid var(--accent-editor); margin-bottom: 20px; max-width: 450px; font-size: 14px;",
+                {t!("ambiguity-notice")}
+            }
+        }
+    }
+}
+
+fn difficulty_label(difficulty: &Difficulty) -> String {
+    match difficulty {
+        Difficulty::VeryEasy => t!("very-easy"),
+        Difficulty::Easy => t!("easy"),
+        Difficulty::Medium => t!("medium"),
+        Difficulty::Hard => t!("hard"),
+    }
+}
+
 #[component]
 pub fn Instructions() -> Element {
     rsx! {
         div {
             style: "margin-top: 30px; max-width: 600px; margin-left: auto; margin-right: auto; \
-                   text-align: left; background-color: white; padding: 20px; border-radius: 10px; \
+                   text-align: left; background-color: var(--surface); padding: 20px; border-radius: 10px; \
                    box-shadow: 0 2px 4px rgba(0,0,0,0.1);",
 
             h3 { {t!("instructions-title")} }
@@ -423,23 +2759,19 @@ pub fn Instructions() -> Element {
                 li { {t!("instruction-9")} }
                 li { {t!("instruction-10")} }
                 li { {t!("instruction-11")} }
+                li { {t!("instruction-12")} }
             }
         }
     }
 }
 
 #[component]
-pub fn WinMessage() -> Element {
-    let player_name = use_resource(|| async {
-        std::process::Command::new("whoami")
-            .output()
-            .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
-            .unwrap_or_else(|_| "Player".to_string())
-    });
+pub fn WinMessage(profiles: Signal<Profiles>) -> Element {
+    let player = profiles.read().current().label();
 
     rsx! {
             div {
-                style: "background: linear-gradient(45deg, #FF6B6B, #4ECDC4, #45B7D1, #96CEB4, #FFEAA7); color: white; padding: 30px; border-radius: 15px; margin-bottom: 20px; font-size: 24px; font-weight: bold; text-align: center; box-shadow: 0 8px 32px rgba(0,0,0,0.3); position: relative; overflow: hidden;",
+                style: "background: linear-gradient(45deg, var(--celebration-start), var(--celebration-end)); color: white; padding: 30px; border-radius: 15px; margin-bottom: 20px; font-size: 24px; font-weight: bold; text-align: center; box-shadow: 0 8px 32px rgba(0,0,0,0.3); position: relative; overflow: hidden;",
 
                 div {
                     style: "font-size: 28px; margin-bottom: 10px;",
@@ -448,10 +2780,7 @@ pub fn WinMessage() -> Element {
 
                 div {
                     style: "font-size: 20px; margin-bottom: 10px;",
-                    match player_name.read().as_ref() {
-                        Some(name) => format!("{} {}!", t!("well-done"), name),
-                        None => t!("well-done-anonymous")
-                    }
+                    {format!("{} {}!", t!("well-done"), player)}
                 }
 
                 div {
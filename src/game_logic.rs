@@ -1,28 +1,39 @@
 //! Game logic module containing Sudoku game logic and state management
 
+use crate::constraints::{
+    Cage, Cages, Columns, Constraint, Decoration, Diagonals, Edge, GlobalRule, Regions, Rows,
+    Rules, Sandwich, XSum,
+};
+use crate::puzzle_bank;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, RngCore, SeedableRng};
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicU64, Ordering};
+
 #[cfg(target_arch = "wasm32")]
 use web_time::{SystemTime, UNIX_EPOCH};
 
 #[cfg(not(target_arch = "wasm32"))]
 use std::time::{SystemTime, UNIX_EPOCH};
 
-// Platform-specific time functions
-#[cfg(target_arch = "wasm32")]
-fn get_timestamp_seed() -> usize {
-    SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap()
-        .as_nanos() as usize
+thread_local! {
+    // Puzzle generation draws all of its randomness from here, so seeding it
+    // reproduces a puzzle
+    static GENERATOR_RNG: RefCell<StdRng> = RefCell::new(StdRng::from_entropy());
 }
 
-#[cfg(not(target_arch = "wasm32"))]
-fn get_timestamp_seed() -> usize {
-    SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap()
-        .as_nanos() as usize
+// Tells games apart, so event listeners notice when the game is replaced
+static NEXT_GAME_ID: AtomicU64 = AtomicU64::new(0);
+
+// A random number generator for one generation step, seeded from the
+// generator's
+fn generator_rng() -> StdRng {
+    GENERATOR_RNG.with(|rng| StdRng::seed_from_u64(rng.borrow_mut().next_u64()))
 }
 
+// Platform-specific time functions
 #[cfg(target_arch = "wasm32")]
 fn get_timestamp_string() -> String {
     SystemTime::now()
@@ -41,6 +52,138 @@ fn get_timestamp_string() -> String {
         .to_string()
 }
 
+pub fn unix_seconds() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs())
+}
+
+pub fn unix_millis() -> u128 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_millis())
+}
+
+// Year, month and day (UTC) of a Unix time
+pub fn civil_date(unix_seconds: u64) -> (u64, u64, u64) {
+    // Days are counted in 400-year eras starting on March 1st, so leap days
+    // fall at the end of a year
+    let days = unix_seconds / 86_400 + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days % 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+    (year, month, day)
+}
+
+pub type Grid = Vec<Vec<Option<u8>>>;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub enum BoardSize {
+    Four,
+    Six,
+    #[default]
+    Nine,
+    Sixteen,
+    // Five 9x9 grids: four corner grids each sharing a box with a center grid
+    Samurai,
+}
+
+impl BoardSize {
+    pub fn all() -> [BoardSize; 5] {
+        [
+            BoardSize::Four,
+            BoardSize::Six,
+            BoardSize::Nine,
+            BoardSize::Sixteen,
+            BoardSize::Samurai,
+        ]
+    }
+
+    // Number of rows and columns of the whole board
+    pub fn side(&self) -> usize {
+        match self {
+            BoardSize::Four => 4,
+            BoardSize::Six => 6,
+            BoardSize::Nine => 9,
+            BoardSize::Sixteen => 16,
+            BoardSize::Samurai => 21,
+        }
+    }
+
+    // Number of digits, which is also the side of each grid
+    pub fn numbers(&self) -> usize {
+        match self {
+            BoardSize::Samurai => 9,
+            _ => self.side(),
+        }
+    }
+
+    // Top-left cell of each grid of `numbers()` rows and columns
+    pub fn grids(&self) -> Vec<(usize, usize)> {
+        match self {
+            BoardSize::Samurai => vec![(0, 0), (0, 12), (6, 6), (12, 0), (12, 12)],
+            _ => vec![(0, 0)],
+        }
+    }
+
+    // Whether (row, col) belongs to one of the grids. Samurai boards leave the
+    // gaps between their corner grids out of play.
+    pub fn contains(&self, row: usize, col: usize) -> bool {
+        let numbers = self.numbers();
+        self.grids().into_iter().any(|(top, left)| {
+            (top..top + numbers).contains(&row) && (left..left + numbers).contains(&col)
+        })
+    }
+
+    // Every cell in play, row by row
+    pub fn cells(&self) -> Vec<(usize, usize)> {
+        let side = self.side();
+        (0..side)
+            .flat_map(|row| (0..side).map(move |col| (row, col)))
+            .filter(|&(row, col)| self.contains(row, col))
+            .collect()
+    }
+
+    pub fn cell_count(&self) -> usize {
+        self.cells().len()
+    }
+
+    // Rows and columns of a box: 2x2, 2x3, 3x3 or 4x4
+    pub fn box_dims(&self) -> (usize, usize) {
+        match self {
+            BoardSize::Four => (2, 2),
+            BoardSize::Six => (2, 3),
+            BoardSize::Nine | BoardSize::Samurai => (3, 3),
+            BoardSize::Sixteen => (4, 4),
+        }
+    }
+
+    pub fn empty_grid(&self) -> Grid {
+        vec![vec![None; self.side()]; self.side()]
+    }
+
+    // 16x16 boards show their digits 1-16 as the hex digits 0-F
+    pub fn digit_label(&self, num: u8) -> String {
+        match self {
+            BoardSize::Sixteen => format!("{:X}", num - 1),
+            _ => num.to_string(),
+        }
+    }
+
+    pub fn parse_digit(&self, ch: char) -> Option<u8> {
+        let num = match self {
+            BoardSize::Sixteen => ch.to_digit(16)? + 1,
+            _ => ch.to_digit(10)?,
+        } as u8;
+        (1..=self.numbers() as u8).contains(&num).then_some(num)
+    }
+}
+
 #[derive(Clone, PartialEq, Debug)]
 pub enum MoveType {
     Input,
@@ -67,7 +210,8 @@ pub enum Difficulty {
 }
 
 impl Difficulty {
-    pub fn numbers_to_remove(&self) -> (usize, usize) {
+    // Ranges are for the 81 cells of a 9x9 board
+    fn numbers_to_remove_9x9(&self) -> (usize, usize) {
         match self {
             Difficulty::VeryEasy => (31, 36), // Keep 45-50 numbers (very easy)
             Difficulty::Easy => (36, 41),     // Keep 40-45 numbers (easy)
@@ -75,217 +219,824 @@ impl Difficulty {
             Difficulty::Hard => (46, 51),     // Keep 30-35 numbers (hard)
         }
     }
+
+    // Scale the 9x9 ranges to the number of cells on the board
+    pub fn numbers_to_remove(&self, size: BoardSize) -> (usize, usize) {
+        let (min_remove, max_remove) = self.numbers_to_remove_9x9();
+        let cells = size.cell_count();
+        (min_remove * cells / 81, max_remove * cells / 81)
+    }
+
+    pub fn all() -> [Difficulty; 4] {
+        [
+            Difficulty::VeryEasy,
+            Difficulty::Easy,
+            Difficulty::Medium,
+            Difficulty::Hard,
+        ]
+    }
+
+    // Rate a puzzle by its number of givens, matching the ranges above
+    pub fn from_given_count(given_count: usize, size: BoardSize) -> Difficulty {
+        let cells = size.cell_count();
+        let removed = cells.saturating_sub(given_count);
+        Difficulty::all()
+            .into_iter()
+            .find(|difficulty| removed * 81 <= difficulty.numbers_to_remove_9x9().1 * cells)
+            .unwrap_or(Difficulty::Hard)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub enum Symmetry {
+    #[default]
+    None,
+    Rotational,
+    Diagonal,
+    Mirror,
+}
+
+impl Symmetry {
+    pub fn all() -> [Symmetry; 4] {
+        [
+            Symmetry::None,
+            Symmetry::Rotational,
+            Symmetry::Diagonal,
+            Symmetry::Mirror,
+        ]
+    }
+
+    // The cell that must be kept or removed together with (row, col)
+    fn partner(&self, size: BoardSize, row: usize, col: usize) -> (usize, usize) {
+        let last = size.side() - 1;
+        match self {
+            Symmetry::None => (row, col),
+            Symmetry::Rotational => (last - row, last - col), // 180° around the center
+            Symmetry::Diagonal => (col, row),                 // Mirrored across the main diagonal
+            Symmetry::Mirror => (row, last - col),            // Mirrored left to right
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub enum Variant {
+    #[default]
+    Classic,
+    Diagonal,
+    Killer,
+    Jigsaw,
+    Sandwich,
+    XSums,
+}
+
+impl Variant {
+    pub fn all() -> [Variant; 6] {
+        [
+            Variant::Classic,
+            Variant::Diagonal,
+            Variant::Killer,
+            Variant::Jigsaw,
+            Variant::Sandwich,
+            Variant::XSums,
+        ]
+    }
+
+    // Samurai boards take the classic rules only. Proving a Killer puzzle
+    // unique, or filling a random Jigsaw layout, takes too long on 16x16 boards.
+    pub fn supports(&self, size: BoardSize) -> bool {
+        match self {
+            Variant::Classic => true,
+            Variant::Diagonal => size != BoardSize::Samurai,
+            Variant::Killer | Variant::Jigsaw => size.side() <= 9,
+            Variant::Sandwich | Variant::XSums => size != BoardSize::Samurai,
+        }
+    }
+}
+
+// Why a Killer cage is broken
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum CageProblem {
+    Duplicate,
+    SumExceeded,
+    // Every cell is filled but the numbers don't add up to the sum
+    WrongSum,
+}
+
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct GenerationOptions {
+    pub size: BoardSize,
+    pub variant: Variant,
+    pub symmetry: Symmetry,
+    // Keep removing givens until none can go without losing uniqueness.
+    // With a symmetry, givens are removed in symmetric pairs.
+    pub minimal: bool,
+    // Anti-knight, anti-king and non-consecutive rules on top of the variant,
+    // in the order of `GlobalRule::all()`
+    pub global_rules: Vec<GlobalRule>,
+}
+
+impl GenerationOptions {
+    // The variant's constraints plus the chosen global rules
+    pub fn rules(&self, mut constraints: Vec<Box<dyn Constraint>>) -> Rules {
+        for &rule in &self.global_rules {
+            constraints.push(Box::new(rule));
+        }
+        Rules::new(self.size, constraints)
+    }
+
+    // The rules of variants whose layout doesn't change between puzzles.
+    // Killer cages, Jigsaw regions and outside clues are drawn anew for each
+    // puzzle instead.
+    pub fn fixed_rules(&self) -> Option<Rules> {
+        let mut constraints = Rules::classic_constraints(self.size);
+        match self.variant {
+            Variant::Classic => {}
+            Variant::Diagonal => constraints.push(Box::new(Diagonals(self.size))), // Both main diagonals are extra houses
+            Variant::Killer | Variant::Jigsaw | Variant::Sandwich | Variant::XSums => return None,
+        }
+        Some(self.rules(constraints))
+    }
+
+    // Most other boards have no solution under several global rules, or take
+    // too long to fill, so the rules are offered on 9x9 Classic and Killer boards
+    pub fn supports_global_rules(&self) -> bool {
+        self.size == BoardSize::Nine && matches!(self.variant, Variant::Classic | Variant::Killer)
+    }
+
+    // Constraints of a Jigsaw puzzle over the given regions
+    fn jigsaw_rules(&self, regions: Vec<Vec<Option<usize>>>) -> Rules {
+        let size = self.size;
+        self.rules(vec![Box::new(Rows(size)), Box::new(Columns(size)), Box::new(Regions(regions))])
+    }
+
+    // Classic constraints plus a clue for every row and column of the solution:
+    // sandwich sums from the top and left, X-sums from all four sides
+    fn clue_rules(&self, solution: &Grid) -> Rules {
+        let mut constraints = Rules::classic_constraints(self.size);
+        for index in 0..self.size.side() {
+            if self.variant == Variant::Sandwich {
+                for edge in [Edge::Top, Edge::Left] {
+                    constraints.push(Box::new(Sandwich::from_solution(solution, edge, index)));
+                }
+            } else {
+                for edge in [Edge::Top, Edge::Bottom, Edge::Left, Edge::Right] {
+                    constraints.push(Box::new(XSum::from_solution(solution, edge, index)));
+                }
+            }
+        }
+        self.rules(constraints)
+    }
+
+    // Classic constraints plus the cages of a Killer puzzle
+    fn killer_rules(&self, cages: Vec<Cage>) -> Rules {
+        let mut constraints = Rules::classic_constraints(self.size);
+        let houses: Vec<_> = constraints.iter().flat_map(|constraint| constraint.houses()).collect();
+        constraints.push(Box::new(Cages::new(self.size, cages, &houses)));
+        self.rules(constraints)
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct AmbiguityReport {
+    // Cells whose value differs between two distinct solutions (the "deadly pattern")
+    pub differing_cells: Vec<(usize, usize)>,
+    // Additional givens, taken from one of the solutions, that make the puzzle unique
+    pub suggested_givens: Vec<(usize, usize, u8)>,
+    // False when the search ran out of steps before the suggested givens made
+    // the puzzle unique; they then only rule out some of the solutions
+    pub complete: bool,
+}
+
+// What a game was generated from, for labelling it and making it again
+#[derive(Clone, PartialEq, Debug)]
+pub struct Generation {
+    pub difficulty: Difficulty,
+    pub options: GenerationOptions,
+    // Generating with the same seed and settings gives the same puzzle. Bank
+    // puzzles have none.
+    pub seed: Option<u64>,
+    // The puzzle of the day, whose seed is the date as YYYYMMDD
+    pub daily: bool,
+}
+
+// How a placed number could be found without guessing
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Technique {
+    // The only number left for its cell
+    NakedSingle,
+    // The only cell left for its number in a row, column or box
+    HiddenSingle,
+}
+
+/// Something that happened in a game, as listed by `SudokuGame::events`
+#[derive(Clone, PartialEq, Debug)]
+pub enum GameEvent {
+    Started,
+    Input {
+        row: usize,
+        col: usize,
+        num: u8,
+        // Whether the number broke a rule
+        conflict: bool,
+        technique: Option<Technique>,
+    },
+    Cleared { row: usize, col: usize },
+    Note { row: usize, col: usize, num: u8, added: bool },
+    Hint { row: usize, col: usize, num: u8 },
+    // The cell of the move and what it holds afterwards
+    Undo { row: usize, col: usize, value: Option<u8> },
+    Redo { row: usize, col: usize, value: Option<u8> },
+    // The puzzle was solved for the first time
+    Completed {
+        // `None` for imported and hand-made puzzles
+        difficulty: Option<Difficulty>,
+        size: BoardSize,
+        seconds: u64,
+        hints: usize,
+        mistakes: usize,
+        notes: usize,
+    },
 }
 
 #[derive(Clone, PartialEq)]
 pub struct SudokuGame {
-    pub grid: [[Option<u8>; 9]; 9],
-    pub initial_grid: [[Option<u8>; 9]; 9],
+    pub size: BoardSize,
+    pub rules: Rules,
+    pub grid: Grid,
+    pub initial_grid: Grid,
     pub selected_cell: Option<(usize, usize)>,
     pub highlighted_number: Option<u8>,
     // Notes: track which numbers are noted in each cell
-    pub notes: [[std::collections::HashSet<u8>; 9]; 9],
-    // Optimization: track available numbers for each constraint
-    row_available: [std::collections::HashSet<u8>; 9],
-    col_available: [std::collections::HashSet<u8>; 9],
-    box_available: [std::collections::HashSet<u8>; 9],
+    pub notes: Vec<Vec<HashSet<u8>>>,
+    // Optimization: track available numbers for each house of the rules
+    house_available: Vec<HashSet<u8>>,
     // Undo/Redo system
     pub move_history: Vec<GameMove>,
     pub current_move_index: Option<usize>,
     // Move counter for timestamps
     move_counter: usize,
+    // Editor mode: the player enters givens into an empty grid
+    pub is_editing: bool,
+    // None for imported and hand-entered puzzles
+    pub generation: Option<Generation>,
+    // When the game was created, in seconds since the Unix epoch
+    pub started_at: u64,
+    // Inputs that broke a rule when they were made; undoing them doesn't
+    // take them back
+    pub mistakes: usize,
+    // Hints given; like mistakes, undoing them doesn't take them back
+    pub hints_taken: usize,
+    // Notes added over the whole game
+    notes_taken: usize,
+    completed: bool,
+    id: u64,
+    events: Vec<GameEvent>,
 }
 
 impl SudokuGame {
-    pub fn new() -> Self {
-        Self::generate_random_puzzle(Difficulty::Easy)
-    }
-
     pub fn new_with_difficulty(difficulty: Difficulty) -> Self {
-        Self::generate_random_puzzle(difficulty)
+        Self::new_with_options(difficulty, GenerationOptions::default())
     }
 
-    fn generate_random_puzzle(difficulty: Difficulty) -> Self {
-        // Start with an empty grid
-        let mut grid = [[None; 9]; 9];
+    pub fn new_with_options(difficulty: Difficulty, options: GenerationOptions) -> Self {
+        // The bank only holds 9x9 puzzles made with the default options
+        if options == GenerationOptions::default()
+            && let Some(puzzle) = puzzle_bank::take_unplayed_puzzle(&difficulty)
+        {
+            let mut game = Self::from_puzzle(Rules::classic(BoardSize::Nine), puzzle);
+            game.generation = Some(Generation {
+                difficulty,
+                options,
+                seed: None,
+                daily: false,
+            });
+            return game;
+        }
 
-        // Fill the grid with a valid complete solution
-        Self::fill_grid(&mut grid);
+        // Generate on demand for any other options, or once every bank puzzle
+        // of the difficulty was played
+        Self::generate_seeded_puzzle(rand::random::<u32>() as u64, difficulty, options)
+    }
 
-        // Create the puzzle by removing numbers based on difficulty
-        let initial_grid = Self::create_puzzle_from_solution(grid, difficulty);
+    // The same seed, difficulty and options always give the same puzzle
+    pub fn generate_seeded_puzzle(seed: u64, difficulty: Difficulty, options: GenerationOptions) -> Self {
+        GENERATOR_RNG.with(|rng| *rng.borrow_mut() = StdRng::seed_from_u64(seed));
+        let mut game = Self::generate_random_puzzle(difficulty.clone(), &options);
+        GENERATOR_RNG.with(|rng| *rng.borrow_mut() = StdRng::from_entropy());
+
+        game.generation = Some(Generation {
+            difficulty,
+            options,
+            seed: Some(seed),
+            daily: false,
+        });
+        game
+    }
 
-        let mut game = Self {
-            grid: initial_grid,
-            initial_grid: initial_grid,
-            selected_cell: None,
-            highlighted_number: None,
-            notes: Default::default(),
-            row_available: Default::default(),
-            col_available: Default::default(),
-            box_available: Default::default(),
-            move_history: Vec::new(),
-            current_move_index: None,
-            move_counter: 0,
-        };
-        game.initialize_constraint_sets();
+    // Today's puzzle: the same Medium 9x9 for everyone on a given (UTC) day
+    pub fn daily_puzzle() -> Self {
+        let (year, month, day) = civil_date(unix_seconds());
+        let seed = year * 10_000 + month * 100 + day;
+        let mut game = Self::generate_seeded_puzzle(seed, Difficulty::Medium, GenerationOptions::default());
+        if let Some(generation) = &mut game.generation {
+            generation.daily = true;
+        }
         game
     }
 
-    fn fill_grid(grid: &mut [[Option<u8>; 9]; 9]) -> bool {
-        // Simple backtracking algorithm to fill the grid
-        for row in 0..9 {
-            for col in 0..9 {
-                if grid[row][col].is_none() {
-                    // Try numbers 1-9 in random order
-                    let mut numbers: Vec<u8> = (1..=9).collect();
+    pub fn new_editor(rules: Rules) -> Self {
+        let empty_grid = rules.size().empty_grid();
+        let mut game = Self::from_puzzle(rules, empty_grid);
+        game.is_editing = true;
+        // The game starts once the givens are locked
+        game.events.clear();
+        game
+    }
 
-                    // Simple shuffle using time-based seed for better randomness
-                    let base_seed = get_timestamp_seed();
-                    let seed = (base_seed + row * 13 + col * 7 + row * col * 3) % 1000;
+    pub fn generate_random_puzzle(difficulty: Difficulty, options: &GenerationOptions) -> Self {
+        if options.variant == Variant::Killer {
+            return Self::generate_killer_puzzle(difficulty, options);
+        }
+        let size = options.size;
+
+        // Fill an empty grid with a valid complete solution. Not every Jigsaw
+        // layout has one, so layouts are drawn until one can be filled.
+        let (rules, grid) = loop {
+            let rules = match options.variant {
+                Variant::Sandwich | Variant::XSums => options.rules(Rules::classic_constraints(size)),
+                _ => options
+                    .fixed_rules()
+                    .unwrap_or_else(|| options.jigsaw_rules(Self::random_regions(size))),
+            };
+            let mut grid = size.empty_grid();
+            if Self::fill_grid(&rules, &mut grid) {
+                break (rules, grid);
+            }
+        };
+        // Outside clues are read off the solution
+        let rules = match options.variant {
+            Variant::Sandwich | Variant::XSums => options.clue_rules(&grid),
+            _ => rules,
+        };
 
-                    for i in 0..numbers.len() {
-                        let j = (seed + i * 7) % numbers.len();
-                        numbers.swap(i, j);
-                    }
+        // Create the puzzle by removing numbers based on difficulty
+        let initial_grid = Self::create_puzzle_from_solution(&rules, grid, difficulty, options);
 
-                    for &num in &numbers {
-                        if Self::is_valid_placement(grid, row, col, num) {
-                            grid[row][col] = Some(num);
-                            if Self::fill_grid(grid) {
-                                return true;
-                            }
-                            grid[row][col] = None;
-                        }
-                    }
-                    return false;
-                }
+        Self::from_puzzle(rules, initial_grid)
+    }
+
+    // Killer puzzles try to get by on their cages alone: several cage layouts
+    // are tried for a unique solution without givens before adding givens from
+    // the solution to the last layout. Uniqueness checks get a step budget, as
+    // proving it from cages alone can take the solver a very long time.
+    fn generate_killer_puzzle(difficulty: Difficulty, options: &GenerationOptions) -> Self {
+        const LAYOUT_ATTEMPTS: usize = 5;
+        const SEARCH_STEPS: usize = 2_000;
+        let size = options.size;
+
+        let mut solution = size.empty_grid();
+        let mut rules = options.rules(Rules::classic_constraints(size));
+        while !Self::fill_grid(&rules, &mut solution) {}
+
+        let mut cells = size.cells();
+        cells.shuffle(&mut generator_rng());
+
+        let mut initial_grid = size.empty_grid();
+        for attempt in 1.. {
+            if attempt <= LAYOUT_ATTEMPTS {
+                rules = options.killer_rules(Self::random_cages(&solution, &difficulty));
+            }
+
+            let mut solutions = Vec::new();
+            let finished =
+                Self::find_solutions_within(&rules, &initial_grid, &mut solutions, 2, SEARCH_STEPS);
+            if finished && solutions.len() == 1 {
+                break;
+            }
+            if attempt < LAYOUT_ATTEMPTS {
+                continue;
+            }
+
+            // Give away a cell where two solutions differ, or a random cell
+            // when the search ran out of steps
+            let differing = (solutions.len() == 2)
+                .then(|| Self::differing_cells(&solutions[0], &solutions[1]))
+                .and_then(|cells| cells.choose(&mut generator_rng()).copied());
+            let given = differing.or_else(|| {
+                cells
+                    .iter()
+                    .copied()
+                    .find(|&(row, col)| initial_grid[row][col].is_none())
+            });
+            match given {
+                Some((row, col)) => initial_grid[row][col] = solution[row][col],
+                None => break,
             }
         }
-        true
+
+        Self::from_puzzle(rules, initial_grid)
     }
 
-    fn is_valid_placement(grid: &[[Option<u8>; 9]; 9], row: usize, col: usize, num: u8) -> bool {
-        // Check row
-        for c in 0..9 {
-            if grid[row][c] == Some(num) {
-                return false;
+    // Grows cages of random shape over the solution without repeating a number
+    // in a cage. Harder puzzles get larger cages.
+    fn random_cages(solution: &Grid, difficulty: &Difficulty) -> Vec<Cage> {
+        let side = solution.len();
+        let (min_size, max_size) = match difficulty {
+            Difficulty::VeryEasy => (2, 3),
+            Difficulty::Easy => (2, 4),
+            Difficulty::Medium => (3, 5),
+            Difficulty::Hard => (3, 6),
+        };
+        let mut rng = generator_rng();
+
+        let mut cells: Vec<(usize, usize)> = (0..side)
+            .flat_map(|row| (0..side).map(move |col| (row, col)))
+            .collect();
+        cells.shuffle(&mut rng);
+
+        let mut cage_of = vec![vec![None; side]; side];
+        let mut cages: Vec<Vec<(usize, usize)>> = Vec::new();
+        for (row, col) in cells {
+            if cage_of[row][col].is_some() {
+                continue;
             }
+            let index = cages.len();
+            let target = rng.gen_range(min_size..=max_size);
+            let mut cage = vec![(row, col)];
+            cage_of[row][col] = Some(index);
+
+            while cage.len() < target {
+                let mut neighbors: Vec<(usize, usize)> = cage
+                    .iter()
+                    .flat_map(|&(r, c)| Self::orthogonal_neighbors(side, r, c))
+                    .filter(|&(r, c)| {
+                        cage_of[r][c].is_none()
+                            && cage.iter().all(|&(cr, cc)| solution[cr][cc] != solution[r][c])
+                    })
+                    .collect();
+                neighbors.dedup();
+                let Some(&(r, c)) = neighbors.choose(&mut rng) else {
+                    break;
+                };
+                cage.push((r, c));
+                cage_of[r][c] = Some(index);
+            }
+            cages.push(cage);
         }
 
-        // Check column
-        for r in 0..9 {
-            if grid[r][col] == Some(num) {
-                return false;
+        // Merge leftover single cells into a neighboring cage where possible
+        for index in 0..cages.len() {
+            if cages[index].len() != 1 {
+                continue;
+            }
+            let (row, col) = cages[index][0];
+            let target = Self::orthogonal_neighbors(side, row, col)
+                .into_iter()
+                .filter_map(|(r, c)| cage_of[r][c])
+                .find(|&other| {
+                    other != index
+                        && cages[other].len() < max_size
+                        && cages[other].iter().all(|&(r, c)| solution[r][c] != solution[row][col])
+                });
+            if let Some(other) = target {
+                cages[index].clear();
+                cages[other].push((row, col));
+                cage_of[row][col] = Some(other);
             }
         }
 
-        // Check 3x3 box
-        let box_row = (row / 3) * 3;
-        let box_col = (col / 3) * 3;
-        for r in box_row..box_row + 3 {
-            for c in box_col..box_col + 3 {
-                if grid[r][c] == Some(num) {
-                    return false;
-                }
+        cages
+            .into_iter()
+            .filter(|cells| !cells.is_empty())
+            .map(|mut cells| {
+                cells.sort();
+                let sum = cells
+                    .iter()
+                    .filter_map(|&(row, col)| solution[row][col])
+                    .map(|num| num as u32)
+                    .sum();
+                Cage { cells, sum }
+            })
+            .collect()
+    }
+
+    // Irregular regions of `side` connected cells, made by repeatedly trading
+    // cells between neighboring boxes while every region stays in one piece
+    fn random_regions(size: BoardSize) -> Vec<Vec<Option<usize>>> {
+        let side = size.side();
+        let mut regions: Vec<Vec<usize>> = Regions::boxes(size)
+            .0
+            .into_iter()
+            .map(|cells| cells.into_iter().flatten().collect())
+            .collect();
+        let mut rng = generator_rng();
+
+        for _ in 0..side * side * 8 {
+            let (row, col) = (rng.gen_range(0..side), rng.gen_range(0..side));
+            let from = regions[row][col];
+            let Some(to) = Self::orthogonal_neighbors(side, row, col)
+                .into_iter()
+                .map(|(r, c)| regions[r][c])
+                .filter(|&region| region != from)
+                .collect::<Vec<_>>()
+                .choose(&mut rng)
+                .copied()
+            else {
+                continue;
+            };
+
+            // A cell of the other region bordering this one takes the cell's place
+            let replacements: Vec<(usize, usize)> = (0..side)
+                .flat_map(|r| (0..side).map(move |c| (r, c)))
+                .filter(|&(r, c)| {
+                    regions[r][c] == to
+                        && Self::orthogonal_neighbors(side, r, c)
+                            .into_iter()
+                            .any(|(nr, nc)| regions[nr][nc] == from && (nr, nc) != (row, col))
+                })
+                .collect();
+            let Some(&(swap_row, swap_col)) = replacements.choose(&mut rng) else {
+                continue;
+            };
+
+            regions[row][col] = to;
+            regions[swap_row][swap_col] = from;
+            if !Self::is_connected(&regions, from) || !Self::is_connected(&regions, to) {
+                regions[row][col] = from;
+                regions[swap_row][swap_col] = to;
             }
         }
 
-        true
+        regions
+            .into_iter()
+            .map(|cells| cells.into_iter().map(Some).collect())
+            .collect()
     }
 
-    // Optimized version using constraint sets
-    fn is_valid_placement_fast(&self, row: usize, col: usize, num: u8) -> bool {
-        let box_idx = (row / 3) * 3 + (col / 3);
-        self.row_available[row].contains(&num)
-            && self.col_available[col].contains(&num)
-            && self.box_available[box_idx].contains(&num)
-    }
+    fn is_connected(regions: &[Vec<usize>], region: usize) -> bool {
+        let side = regions.len();
+        let cells: Vec<(usize, usize)> = (0..side)
+            .flat_map(|row| (0..side).map(move |col| (row, col)))
+            .filter(|&(row, col)| regions[row][col] == region)
+            .collect();
+        let Some(&start) = cells.first() else {
+            return true;
+        };
 
-    fn create_puzzle_from_solution(
-        mut solution: [[Option<u8>; 9]; 9],
-        difficulty: Difficulty,
-    ) -> [[Option<u8>; 9]; 9] {
-        // Use time-based seed combined with grid hash for better randomness
-        let time_seed = get_timestamp_seed();
-        let mut seed = time_seed;
-        for row in 0..9 {
-            for col in 0..9 {
-                if let Some(val) = solution[row][col] {
-                    seed = seed
-                        .wrapping_mul(31)
-                        .wrapping_add(val as usize * (row + 1) * (col + 1));
+        let mut seen = HashSet::from([start]);
+        let mut stack = vec![start];
+        while let Some((row, col)) = stack.pop() {
+            for (r, c) in Self::orthogonal_neighbors(side, row, col) {
+                if regions[r][c] == region && seen.insert((r, c)) {
+                    stack.push((r, c));
                 }
             }
         }
-        seed = seed.wrapping_add(12345); // Add some variation
+        seen.len() == cells.len()
+    }
 
-        let mut positions: Vec<(usize, usize)> = Vec::new();
-        for row in 0..9 {
-            for col in 0..9 {
-                positions.push((row, col));
-            }
+    fn orthogonal_neighbors(side: usize, row: usize, col: usize) -> Vec<(usize, usize)> {
+        let mut neighbors = Vec::with_capacity(4);
+        if row > 0 {
+            neighbors.push((row - 1, col));
+        }
+        if row + 1 < side {
+            neighbors.push((row + 1, col));
         }
+        if col > 0 {
+            neighbors.push((row, col - 1));
+        }
+        if col + 1 < side {
+            neighbors.push((row, col + 1));
+        }
+        neighbors
+    }
 
-        // Simple shuffle
-        for i in 0..positions.len() {
-            let j = (seed + i * 13) % positions.len();
-            positions.swap(i, j);
+    pub fn from_puzzle(rules: Rules, initial_grid: Grid) -> Self {
+        let size = rules.size();
+        let side = size.side();
+        let mut game = Self {
+            size,
+            house_available: vec![HashSet::new(); rules.houses().len()],
+            rules,
+            grid: initial_grid.clone(),
+            initial_grid,
+            selected_cell: None,
+            highlighted_number: None,
+            notes: vec![vec![HashSet::new(); side]; side],
+            move_history: Vec::new(),
+            current_move_index: None,
+            move_counter: 0,
+            is_editing: false,
+            generation: None,
+            started_at: unix_seconds(),
+            mistakes: 0,
+            hints_taken: 0,
+            notes_taken: 0,
+            completed: false,
+            id: NEXT_GAME_ID.fetch_add(1, Ordering::Relaxed),
+            events: vec![GameEvent::Started],
+        };
+        game.initialize_constraint_sets();
+        game
+    }
+
+    fn fill_grid(rules: &Rules, grid: &mut Grid) -> bool {
+        // Give up on layouts without a solution instead of searching forever
+        const FILL_STEPS: usize = 10_000;
+
+        // Search for a single solution, trying candidates in random order
+        let mut solver = Solver::new(rules, grid);
+        solver.steps_left = FILL_STEPS;
+        let mut solutions = Vec::new();
+        solver.search(&mut solutions, 1, true);
+
+        match solutions.pop() {
+            Some(solution) => {
+                *grid = solution;
+                true
+            }
+            None => false,
         }
+    }
+
+    // Optimized version using constraint sets
+    fn is_valid_placement_fast(&self, row: usize, col: usize, num: u8) -> bool {
+        self.rules
+            .houses_of(row, col)
+            .iter()
+            .all(|&house| self.house_available[house].contains(&num))
+            && self.rules.restrict(&self.grid, row, col, 1 << num) != 0
+    }
+
+    fn create_puzzle_from_solution(
+        rules: &Rules,
+        mut solution: Grid,
+        difficulty: Difficulty,
+        options: &GenerationOptions,
+    ) -> Grid {
+        let size = rules.size();
+
+        // Drawn from the generator RNG in u64 steps so a seed gives the same
+        // puzzle on every target, including 32-bit wasm
+        let mut rng = generator_rng();
+        let mut positions = size.cells();
+        positions.shuffle(&mut rng);
 
         // Remove numbers one by one, ensuring unique solution
-        let (min_remove, max_remove) = difficulty.numbers_to_remove();
-        let target_remove = min_remove + (seed % (max_remove - min_remove + 1));
+        let (min_remove, max_remove) = difficulty.numbers_to_remove(size);
+        let target_remove = rng.gen_range(min_remove as u64..=max_remove as u64) as usize;
 
         let mut removed_count = 0;
         for &(row, col) in &positions {
-            if removed_count >= target_remove {
+            if removed_count >= target_remove && !options.minimal {
                 break;
             }
 
-            // Try removing this cell
-            let original_value = solution[row][col];
-            solution[row][col] = None;
-
-            // Check if the puzzle still has a unique solution
-            if Self::has_unique_solution(&solution) {
-                removed_count += 1;
-            } else {
-                // Restore the cell if removing it creates multiple solutions
-                solution[row][col] = original_value;
+            // Skip cells already removed as the partner of an earlier cell
+            if solution[row][col].is_none() {
+                continue;
             }
+
+            removed_count +=
+                Self::try_remove_symmetric(rules, &mut solution, row, col, options.symmetry);
         }
 
         solution
     }
 
+    // Removes (row, col) together with its symmetric partner if the puzzle keeps
+    // a unique solution, returning the number of cells removed
+    fn try_remove_symmetric(
+        rules: &Rules,
+        solution: &mut Grid,
+        row: usize,
+        col: usize,
+        symmetry: Symmetry,
+    ) -> usize {
+        let (partner_row, partner_col) = symmetry.partner(rules.size(), row, col);
+        let original_value = solution[row][col];
+        let original_partner = solution[partner_row][partner_col];
+
+        solution[row][col] = None;
+        solution[partner_row][partner_col] = None;
+
+        // Check if the puzzle still has a unique solution
+        if Self::has_unique_solution(rules, solution) {
+            if (partner_row, partner_col) == (row, col) { 1 } else { 2 }
+        } else {
+            // Restore the cells if removing them creates multiple solutions
+            solution[partner_row][partner_col] = original_partner;
+            solution[row][col] = original_value;
+            0
+        }
+    }
+
     pub fn is_valid_move(&self, row: usize, col: usize, num: u8) -> bool {
         // Use optimized constraint checking
         self.is_valid_placement_fast(row, col, num)
     }
 
     pub fn is_complete(&self) -> bool {
-        for row in 0..9 {
-            for col in 0..9 {
-                if self.grid[row][col].is_none() {
-                    return false;
-                }
-            }
+        self.size
+            .cells()
+            .into_iter()
+            .all(|(row, col)| self.grid[row][col].is_some())
+    }
+
+    // Complete without breaking any rule
+    pub fn is_solved(&self) -> bool {
+        self.is_complete() && self.rules.is_consistent(&self.grid)
+    }
+
+    pub fn elapsed_seconds(&self) -> u64 {
+        unix_seconds().saturating_sub(self.started_at)
+    }
+
+    // Different for every game created, but kept by clones
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    // Everything that happened in this game, oldest first
+    pub fn events(&self) -> &[GameEvent] {
+        &self.events
+    }
+
+    // Queues `Completed` the first time the puzzle is solved
+    fn check_completion(&mut self) {
+        if self.completed || self.is_editing || !self.is_solved() {
+            return;
         }
-        true
+        self.completed = true;
+        self.events.push(GameEvent::Completed {
+            difficulty: self.generation.as_ref().map(|generation| generation.difficulty.clone()),
+            size: self.size,
+            seconds: self.elapsed_seconds(),
+            hints: self.hints_taken,
+            mistakes: self.mistakes,
+            notes: self.notes_taken,
+        });
+    }
+
+    // The simplest technique that forces `num` at the empty cell (row, col)
+    fn technique_for(&self, row: usize, col: usize, num: u8) -> Option<Technique> {
+        let numbers = self.size.numbers() as u8;
+        let fits = |r: usize, c: usize, n: u8| self.grid[r][c].is_none() && self.rules.allows(&self.grid, r, c, n);
+        if !fits(row, col, num) {
+            return None;
+        }
+        if (1..=numbers).filter(|&n| fits(row, col, n)).count() == 1 {
+            return Some(Technique::NakedSingle);
+        }
+        // Only houses that hold every number force one of their cells
+        self.rules
+            .houses_of(row, col)
+            .iter()
+            .map(|&house| &self.rules.houses()[house])
+            .filter(|house| house.len() == numbers as usize)
+            .any(|house| {
+                house
+                    .iter()
+                    .all(|&(r, c)| (r, c) == (row, col) || !fits(r, c, num))
+            })
+            .then_some(Technique::HiddenSingle)
+    }
+
+    // Whether the number at (row, col) was placed by a hint and not changed since
+    pub fn is_hint_cell(&self, row: usize, col: usize) -> bool {
+        let Some(current) = self.current_move_index else {
+            return false;
+        };
+        self.move_history[..=current]
+            .iter()
+            .rev()
+            .find(|game_move| (game_move.row, game_move.col) == (row, col))
+            .is_some_and(|game_move| game_move.move_type == MoveType::Hint)
     }
 
     pub fn is_initial_cell(&self, row: usize, col: usize) -> bool {
         self.initial_grid[row][col].is_some()
     }
 
+    // Number of filled cells; for a fresh puzzle this is the number of givens
+    pub fn filled_count(&self) -> usize {
+        self.grid.iter().flatten().filter(|cell| cell.is_some()).count()
+    }
+
     pub fn select_cell(&mut self, row: usize, col: usize) {
-        if !self.is_initial_cell(row, col) {
-            self.selected_cell = Some((row, col));
+        if !self.size.contains(row, col) {
+            return;
         }
+        // Givens can be selected too, so moving with the keyboard does not
+        // stop at them; input and clearing leave them alone
+        self.selected_cell = Some((row, col));
 
-        // Set highlighted number based on the clicked cell's value
-        self.highlighted_number = self.grid[row][col];
+        // Highlight the clicked cell's number. Empty cells keep the number
+        // highlighted, so its notes and free cells stay marked while moving.
+        if let Some(num) = self.grid[row][col] {
+            self.highlighted_number = Some(num);
+        }
     }
 
     pub fn is_cell_highlighted(&self, row: usize, col: usize) -> bool {
@@ -296,10 +1047,42 @@ impl SudokuGame {
         }
     }
 
+    // Whether (row, col) shares a house of the rules with the selected cell,
+    // such as its row, column, box or a Killer cage
+    pub fn is_peer_of_selected(&self, row: usize, col: usize) -> bool {
+        let Some((selected_row, selected_col)) = self.selected_cell else {
+            return false;
+        };
+        let houses = self.rules.houses_of(row, col);
+        (row, col) != (selected_row, selected_col)
+            && self
+                .rules
+                .houses_of(selected_row, selected_col)
+                .iter()
+                .any(|house| houses.contains(house))
+    }
+
+    // Whether the empty cell (row, col) has the highlighted number in its notes
+    pub fn has_highlighted_note(&self, row: usize, col: usize) -> bool {
+        self.highlighted_number
+            .is_some_and(|num| self.grid[row][col].is_none() && self.notes[row][col].contains(&num))
+    }
+
+    // Whether the highlighted number could still go in the empty cell (row, col)
+    pub fn can_hold_highlighted(&self, row: usize, col: usize) -> bool {
+        self.highlighted_number
+            .is_some_and(|num| self.grid[row][col].is_none() && self.is_valid_placement_fast(row, col, num))
+    }
+
     pub fn input_number(&mut self, num: u8) -> bool {
         if let Some((row, col)) = self.selected_cell {
             if !self.is_initial_cell(row, col) {
                 let old_value = self.grid[row][col];
+                let technique = if old_value.is_none() {
+                    self.technique_for(row, col, num)
+                } else {
+                    None
+                };
 
                 // Remove old number from constraint sets if exists
                 if let Some(old_num) = old_value {
@@ -309,12 +1092,25 @@ impl SudokuGame {
                 // Always allow the input, regardless of validity
                 self.grid[row][col] = Some(num);
                 self.add_number_to_constraints(row, col, num);
+                let conflict = self.has_conflicts(row, col);
+                if conflict {
+                    self.mistakes += 1;
+                }
 
                 // Clear notes when a number is filled
                 self.clear_notes(row, col);
+                self.highlighted_number = Some(num);
 
                 // Record the move
                 self.record_move(row, col, old_value, Some(num), MoveType::Input);
+                self.events.push(GameEvent::Input {
+                    row,
+                    col,
+                    num,
+                    conflict,
+                    technique,
+                });
+                self.check_completion();
 
                 return true;
             }
@@ -323,33 +1119,41 @@ impl SudokuGame {
     }
 
     pub fn has_conflicts(&self, row: usize, col: usize) -> bool {
-        if let Some(num) = self.grid[row][col] {
-            // Check for conflicts in the same row
-            for c in 0..9 {
-                if c != col && self.grid[row][c] == Some(num) {
-                    return true;
-                }
-            }
+        // Only numbers that already break a rule count, not ones the solver
+        // could rule out by looking ahead
+        self.rules.is_broken(&self.grid, row, col)
+            || self.grid[row][col].is_some()
+                && self
+                    .cage_at(row, col)
+                    .is_some_and(|cage| self.cage_problem(&cage).is_some())
+    }
 
-            // Check for conflicts in the same column
-            for r in 0..9 {
-                if r != row && self.grid[r][col] == Some(num) {
-                    return true;
-                }
-            }
+    // The Killer cage containing (row, col), if the rules have cages
+    pub fn cage_at(&self, row: usize, col: usize) -> Option<Cage> {
+        self.rules.decorations().into_iter().find_map(|decoration| match decoration {
+            Decoration::Cage(cage) if cage.cells.contains(&(row, col)) => Some(cage),
+            _ => None,
+        })
+    }
 
-            // Check for conflicts in the same 3x3 box
-            let box_row = (row / 3) * 3;
-            let box_col = (col / 3) * 3;
-            for r in box_row..box_row + 3 {
-                for c in box_col..box_col + 3 {
-                    if (r != row || c != col) && self.grid[r][c] == Some(num) {
-                        return true;
-                    }
-                }
-            }
+    // Whether a Killer cage repeats a number or misses its sum
+    pub fn cage_problem(&self, cage: &Cage) -> Option<CageProblem> {
+        let values: Vec<u8> = cage
+            .cells
+            .iter()
+            .filter_map(|&(row, col)| self.grid[row][col])
+            .collect();
+        let sum: u32 = values.iter().map(|&num| num as u32).sum();
+
+        if values.iter().collect::<HashSet<_>>().len() < values.len() {
+            Some(CageProblem::Duplicate)
+        } else if sum > cage.sum {
+            Some(CageProblem::SumExceeded)
+        } else if values.len() == cage.cells.len() && sum != cage.sum {
+            Some(CageProblem::WrongSum)
+        } else {
+            None
         }
-        false
     }
 
     pub fn clear_selected_cell(&mut self) {
@@ -363,96 +1167,135 @@ impl SudokuGame {
 
                 // Record the move
                 self.record_move(row, col, old_value, None, MoveType::Clear);
+                self.events.push(GameEvent::Cleared { row, col });
+            }
+        }
+    }
+
+    pub fn start_editor(&mut self, rules: Rules) {
+        *self = Self::new_editor(rules);
+    }
+
+    // Start a new game from an imported puzzle
+    pub fn load_puzzle(&mut self, rules: Rules, puzzle: Grid) {
+        *self = Self::from_puzzle(rules, puzzle);
+    }
+
+    // Turn the entered numbers into the givens of a new game
+    pub fn lock_givens(&mut self) {
+        *self = Self::from_puzzle(self.rules.clone(), self.grid.clone());
+    }
+
+    // Number of solutions of the current grid, capped at 2
+    pub fn solution_count(&self) -> usize {
+        if !self.rules.is_consistent(&self.grid) {
+            return 0;
+        }
+
+        let mut solution_count = 0;
+        let mut temp_grid = self.grid.clone();
+        Self::count_solutions(&self.rules, &mut temp_grid, &mut solution_count);
+        solution_count
+    }
+
+    // The solution of the givens, if they have one
+    pub fn solution(&self) -> Option<Grid> {
+        let mut solutions = Vec::new();
+        Self::find_solutions(&self.rules, &mut self.puzzle_grid(), &mut solutions, 1);
+        solutions.pop()
+    }
+
+    // The givens of the puzzle; while editing these are the entered numbers
+    pub fn puzzle_grid(&self) -> Grid {
+        if self.is_editing {
+            self.grid.clone()
+        } else {
+            self.initial_grid.clone()
+        }
+    }
+
+    // Fill in givens suggested by an ambiguity report, as undoable inputs
+    pub fn add_givens(&mut self, givens: &[(usize, usize, u8)]) {
+        for &(row, col, num) in givens {
+            if self.is_initial_cell(row, col) {
+                continue;
             }
+
+            let old_value = self.grid[row][col];
+            if let Some(old_num) = old_value {
+                self.remove_number_from_constraints(row, col, old_num);
+            }
+            self.grid[row][col] = Some(num);
+            self.add_number_to_constraints(row, col, num);
+            self.clear_notes(row, col);
+            self.record_move(row, col, old_value, Some(num), MoveType::Input);
         }
     }
 
-    pub fn reset_with_difficulty(&mut self, difficulty: Difficulty) {
-        *self = Self::new_with_difficulty(difficulty);
+    pub fn estimated_difficulty(&self) -> Difficulty {
+        Difficulty::from_given_count(self.filled_count(), self.size)
+    }
+
+    pub fn reset_with_options(&mut self, difficulty: Difficulty, options: GenerationOptions) {
+        *self = Self::new_with_options(difficulty, options);
     }
 
     pub fn solve_one_cell(&mut self) -> bool {
+        let numbers = self.size.numbers();
+
         // First, verify the current puzzle state has a unique solution
-        if !Self::has_unique_solution(&self.grid) {
+        if !Self::has_unique_solution(&self.rules, &self.grid) {
             // Puzzle is invalid - no unique solution exists
             return false;
         }
 
         // Find the first empty cell that can be solved with only one valid number
-        for row in 0..9 {
-            for col in 0..9 {
-                if self.grid[row][col].is_none() && !self.is_initial_cell(row, col) {
-                    let mut valid_numbers = Vec::new();
-
-                    for num in 1..=9 {
-                        if self.is_valid_move(row, col, num) {
-                            // Test if placing this number still leads to a unique solution
-                            let mut temp_grid = self.grid;
-                            temp_grid[row][col] = Some(num);
-
-                            if Self::has_unique_solution(&temp_grid) {
-                                valid_numbers.push(num);
-                            }
+        for (row, col) in self.size.cells() {
+            if self.grid[row][col].is_none() && !self.is_initial_cell(row, col) {
+                let mut valid_numbers = Vec::new();
+
+                for num in 1..=numbers as u8 {
+                    if self.is_valid_move(row, col, num) {
+                        // Test if placing this number still leads to a unique solution
+                        let mut temp_grid = self.grid.clone();
+                        temp_grid[row][col] = Some(num);
+
+                        if Self::has_unique_solution(&self.rules, &temp_grid) {
+                            valid_numbers.push(num);
                         }
                     }
+                }
 
-                    // If there's only one valid number that leads to unique solution, fill it
-                    if valid_numbers.len() == 1 {
-                        let old_value = self.grid[row][col];
-                        self.grid[row][col] = Some(valid_numbers[0]);
-                        self.add_number_to_constraints(row, col, valid_numbers[0]);
-
-                        // Record the hint move
-                        self.record_move(
-                            row,
-                            col,
-                            old_value,
-                            Some(valid_numbers[0]),
-                            MoveType::Hint,
-                        );
-
-                        return true;
-                    }
+                // If there's only one valid number that leads to unique solution, fill it
+                if valid_numbers.len() == 1 {
+                    self.give_hint(row, col, valid_numbers[0]);
+                    return true;
                 }
             }
         }
 
         // If no cell with single solution found, try to find any cell where
         // only one number leads to the unique solution
-        for row in 0..9 {
-            for col in 0..9 {
-                if self.grid[row][col].is_none() && !self.is_initial_cell(row, col) {
-                    let mut solution_preserving_numbers = Vec::new();
-
-                    for num in 1..=9 {
-                        if self.is_valid_move(row, col, num) {
-                            // Check if this number leads to the unique solution
-                            let mut temp_grid = self.grid;
-                            temp_grid[row][col] = Some(num);
-
-                            if Self::has_unique_solution(&temp_grid) {
-                                solution_preserving_numbers.push(num);
-                            }
+        for (row, col) in self.size.cells() {
+            if self.grid[row][col].is_none() && !self.is_initial_cell(row, col) {
+                let mut solution_preserving_numbers = Vec::new();
+
+                for num in 1..=numbers as u8 {
+                    if self.is_valid_move(row, col, num) {
+                        // Check if this number leads to the unique solution
+                        let mut temp_grid = self.grid.clone();
+                        temp_grid[row][col] = Some(num);
+
+                        if Self::has_unique_solution(&self.rules, &temp_grid) {
+                            solution_preserving_numbers.push(num);
                         }
                     }
+                }
 
-                    // If only one number preserves the unique solution, use it as hint
-                    if solution_preserving_numbers.len() == 1 {
-                        let old_value = self.grid[row][col];
-                        self.grid[row][col] = Some(solution_preserving_numbers[0]);
-                        self.add_number_to_constraints(row, col, solution_preserving_numbers[0]);
-
-                        // Record the hint move
-                        self.record_move(
-                            row,
-                            col,
-                            old_value,
-                            Some(solution_preserving_numbers[0]),
-                            MoveType::Hint,
-                        );
-
-                        return true;
-                    }
+                // If only one number preserves the unique solution, use it as hint
+                if solution_preserving_numbers.len() == 1 {
+                    self.give_hint(row, col, solution_preserving_numbers[0]);
+                    return true;
                 }
             }
         }
@@ -461,73 +1304,163 @@ impl SudokuGame {
         false
     }
 
-    fn has_unique_solution(grid: &[[Option<u8>; 9]; 9]) -> bool {
+    // Fills in a hint; it counts towards `hints_taken` even once undone
+    fn give_hint(&mut self, row: usize, col: usize, num: u8) {
+        let old_value = self.grid[row][col];
+        self.grid[row][col] = Some(num);
+        self.add_number_to_constraints(row, col, num);
+        self.record_move(row, col, old_value, Some(num), MoveType::Hint);
+        self.hints_taken += 1;
+        self.events.push(GameEvent::Hint { row, col, num });
+        self.check_completion();
+    }
+
+    fn has_unique_solution(rules: &Rules, grid: &Grid) -> bool {
         let mut solution_count = 0;
-        let mut temp_grid = *grid;
-        Self::count_solutions(&mut temp_grid, &mut solution_count);
+        let mut temp_grid = grid.clone();
+        Self::count_solutions(rules, &mut temp_grid, &mut solution_count);
         solution_count == 1
     }
 
-    fn count_solutions(grid: &mut [[Option<u8>; 9]; 9], count: &mut usize) {
-        if *count > 1 {
-            return; // Early exit if we already found multiple solutions
+    fn count_solutions(rules: &Rules, grid: &mut Grid, count: &mut usize) {
+        // Two solutions are enough to know the puzzle is not unique
+        let mut solutions = Vec::new();
+        Self::find_solutions(rules, grid, &mut solutions, 2);
+        *count = solutions.len();
+    }
+
+    fn find_solutions(rules: &Rules, grid: &mut Grid, solutions: &mut Vec<Grid>, limit: usize) {
+        Solver::new(rules, grid).search(solutions, limit, false);
+    }
+
+    // Like `find_solutions`, but gives up after `steps` search steps. Returns
+    // whether the search finished.
+    fn find_solutions_within(
+        rules: &Rules,
+        grid: &Grid,
+        solutions: &mut Vec<Grid>,
+        limit: usize,
+        steps: usize,
+    ) -> bool {
+        let mut solver = Solver::new(rules, grid);
+        solver.steps_left = steps;
+        solver.search(solutions, limit, false);
+        solutions.len() >= limit || solver.steps_left > 0
+    }
+
+    /// Explains why a puzzle is not unique: finds two distinct solutions and
+    /// reports the cells where they differ, plus additional givens that would
+    /// make the puzzle unique. Returns `None` for puzzles with 0 or 1 solutions,
+    /// or when even the first two solutions take more than the search budget.
+    pub fn analyze_ambiguity(rules: &Rules, puzzle: &Grid) -> Option<AmbiguityReport> {
+        // Runs whenever the givens change, so the whole analysis shares one
+        // budget of search steps
+        const ANALYSIS_STEPS: usize = 2_000;
+
+        if !rules.is_consistent(puzzle) {
+            return None;
         }
 
-        // Find the first empty cell
-        for row in 0..9 {
-            for col in 0..9 {
-                if grid[row][col].is_none() {
-                    // Try each number 1-9
-                    for num in 1..=9 {
-                        if Self::is_valid_placement(grid, row, col, num) {
-                            grid[row][col] = Some(num);
-                            Self::count_solutions(grid, count);
-                            grid[row][col] = None;
+        let mut steps_left = ANALYSIS_STEPS;
+        // Up to two solutions of `grid`, or `None` if the budget ran out first
+        let mut solve = |grid: &Grid| {
+            let mut solver = Solver::new(rules, grid);
+            solver.steps_left = steps_left;
+            let mut solutions = Vec::new();
+            solver.search(&mut solutions, 2, false);
+            steps_left = solver.steps_left;
+            (solutions.len() == 2 || steps_left > 0).then_some(solutions)
+        };
 
-                            if *count > 1 {
-                                return; // Early exit
-                            }
-                        }
-                    }
-                    return; // Backtrack
-                }
+        let solutions = solve(puzzle)?;
+        if solutions.len() < 2 {
+            return None;
+        }
+
+        let target = solutions[0].clone();
+        let differing_cells = Self::differing_cells(&target, &solutions[1]);
+
+        // Pin every cell where the first solution differs from another
+        // solution, until the first solution is the only one left
+        let mut pinned = puzzle.clone();
+        let mut suggested_givens = Vec::new();
+        let mut other = solutions[1].clone();
+        let complete = loop {
+            for (row, col) in Self::differing_cells(&target, &other) {
+                pinned[row][col] = target[row][col];
+                suggested_givens.push((row, col));
             }
+            match solve(&pinned) {
+                Some(solutions) => match solutions.into_iter().find(|solution| *solution != target) {
+                    Some(solution) => other = solution,
+                    None => break true,
+                },
+                None => break false,
+            }
+        };
+
+        // Drop pinned cells that other pins made redundant, while the budget
+        // lasts; the pins left over still make the first solution unique
+        if complete {
+            suggested_givens.retain(|&(row, col)| {
+                pinned[row][col] = None;
+                if solve(&pinned).is_some_and(|solutions| solutions.len() == 1) {
+                    false
+                } else {
+                    pinned[row][col] = target[row][col];
+                    true
+                }
+            });
         }
 
-        // If we reach here, we found a complete solution
-        *count += 1;
+        Some(AmbiguityReport {
+            differing_cells,
+            suggested_givens: suggested_givens
+                .into_iter()
+                .filter_map(|(row, col)| target[row][col].map(|num| (row, col, num)))
+                .collect(),
+            complete,
+        })
+    }
+
+    fn differing_cells(first: &Grid, second: &Grid) -> Vec<(usize, usize)> {
+        let mut cells = Vec::new();
+        for (row, (first_cells, second_cells)) in first.iter().zip(second).enumerate() {
+            for (col, (first_cell, second_cell)) in first_cells.iter().zip(second_cells).enumerate() {
+                if first_cell != second_cell {
+                    cells.push((row, col));
+                }
+            }
+        }
+        cells
     }
 
     fn initialize_constraint_sets(&mut self) {
-        // Initialize all sets with numbers 1-9
-        for i in 0..9 {
-            self.row_available[i] = (1..=9).collect();
-            self.col_available[i] = (1..=9).collect();
-            self.box_available[i] = (1..=9).collect();
+        let numbers = self.size.numbers();
+
+        // Initialize all sets with every number on the board
+        for available in &mut self.house_available {
+            *available = (1..=numbers as u8).collect();
         }
 
         // Remove numbers that are already placed
-        for row in 0..9 {
-            for col in 0..9 {
-                if let Some(num) = self.grid[row][col] {
-                    self.add_number_to_constraints(row, col, num);
-                }
+        for (row, col) in self.size.cells() {
+            if let Some(num) = self.grid[row][col] {
+                self.add_number_to_constraints(row, col, num);
             }
         }
     }
 
     fn add_number_to_constraints(&mut self, row: usize, col: usize, num: u8) {
-        let box_idx = (row / 3) * 3 + (col / 3);
-        self.row_available[row].remove(&num);
-        self.col_available[col].remove(&num);
-        self.box_available[box_idx].remove(&num);
+        for &house in self.rules.houses_of(row, col) {
+            self.house_available[house].remove(&num);
+        }
     }
 
     fn remove_number_from_constraints(&mut self, row: usize, col: usize, num: u8) {
-        let box_idx = (row / 3) * 3 + (col / 3);
-        self.row_available[row].insert(num);
-        self.col_available[col].insert(num);
-        self.box_available[box_idx].insert(num);
+        for &house in self.rules.houses_of(row, col) {
+            self.house_available[house].insert(num);
+        }
     }
 
     // Undo/Redo functionality
@@ -596,6 +1529,11 @@ impl SudokuGame {
                 self.current_move_index = Some(current_index - 1);
             }
 
+            self.events.push(GameEvent::Undo {
+                row: game_move.row,
+                col: game_move.col,
+                value: game_move.old_value,
+            });
             true
         } else {
             false
@@ -624,6 +1562,12 @@ impl SudokuGame {
             }
 
             self.current_move_index = Some(next_index);
+            self.events.push(GameEvent::Redo {
+                row: game_move.row,
+                col: game_move.col,
+                value: game_move.new_value,
+            });
+            self.check_completion();
             true
         } else {
             false
@@ -642,10 +1586,11 @@ impl SudokuGame {
                 };
 
                 let position = format!("R{}C{}", game_move.row + 1, game_move.col + 1);
+                let label = |num: u8| self.size.digit_label(num);
                 let value_change = match (game_move.old_value, game_move.new_value) {
-                    (None, Some(new)) => format!("→ {}", new),
-                    (Some(old), None) => format!("{} → ∅", old),
-                    (Some(old), Some(new)) => format!("{} → {}", old, new),
+                    (None, Some(new)) => format!("→ {}", label(new)),
+                    (Some(old), None) => format!("{} → ∅", label(old)),
+                    (Some(old), Some(new)) => format!("{} → {}", label(old), label(new)),
                     (None, None) => "∅ → ∅".to_string(),
                 };
 
@@ -666,15 +1611,18 @@ impl SudokuGame {
     pub fn toggle_note(&mut self, row: usize, col: usize, num: u8) {
         // Only allow notes in empty cells
         if self.grid[row][col].is_none() {
-            if self.notes[row][col].contains(&num) {
-                self.notes[row][col].remove(&num);
+            let added = self.notes[row][col].insert(num);
+            if added {
+                self.notes_taken += 1;
             } else {
-                self.notes[row][col].insert(num);
+                self.notes[row][col].remove(&num);
             }
+            self.highlighted_number = Some(num);
+            self.events.push(GameEvent::Note { row, col, num, added });
         }
     }
 
-    pub fn get_notes(&self, row: usize, col: usize) -> &std::collections::HashSet<u8> {
+    pub fn get_notes(&self, row: usize, col: usize) -> &HashSet<u8> {
         &self.notes[row][col]
     }
 
@@ -682,3 +1630,268 @@ impl SudokuGame {
         self.notes[row][col].clear();
     }
 }
+
+// Backtracking solver over the houses and cell relationships of a rule set.
+// Before branching it places naked singles (cells with one candidate) and
+// hidden singles (numbers with one spot left in a full-size house), and it
+// branches on the cell with the fewest candidates. This keeps uniqueness
+// checks fast even for sparse 16x16 puzzles.
+struct Solver<'a> {
+    rules: &'a Rules,
+    side: usize,
+    numbers: usize,
+    // Flattened indices of the cells in play
+    cells: Vec<usize>,
+    grid: Grid,
+    // Houses as flattened cell indices (row * side + col)
+    houses: Vec<Vec<usize>>,
+    // Bitmask of the numbers placed in each house
+    house_used: Vec<u32>,
+    // Search steps left before giving up; unlimited unless set
+    steps_left: usize,
+}
+
+impl<'a> Solver<'a> {
+    fn new(rules: &'a Rules, grid: &Grid) -> Self {
+        let size = rules.size();
+        let side = size.side();
+        let houses: Vec<Vec<usize>> = rules
+            .houses()
+            .iter()
+            .map(|house| house.iter().map(|&(row, col)| row * side + col).collect())
+            .collect();
+
+        let mut solver = Self {
+            rules,
+            side,
+            numbers: size.numbers(),
+            cells: size.cells().into_iter().map(|(row, col)| row * side + col).collect(),
+            grid: size.empty_grid(),
+            house_used: vec![0; houses.len()],
+            houses,
+            steps_left: usize::MAX,
+        };
+        for (cell, value) in grid.iter().flatten().enumerate() {
+            if let Some(num) = value {
+                solver.place(cell, *num);
+            }
+        }
+        solver
+    }
+
+    fn value(&self, cell: usize) -> Option<u8> {
+        self.grid[cell / self.side][cell % self.side]
+    }
+
+    fn place(&mut self, cell: usize, num: u8) {
+        let (row, col) = (cell / self.side, cell % self.side);
+        self.grid[row][col] = Some(num);
+        for &house in self.rules.houses_of(row, col) {
+            self.house_used[house] |= 1 << num;
+        }
+    }
+
+    fn unplace(&mut self, cell: usize) {
+        let (row, col) = (cell / self.side, cell % self.side);
+        if let Some(num) = self.grid[row][col].take() {
+            for &house in self.rules.houses_of(row, col) {
+                self.house_used[house] &= !(1 << num);
+            }
+        }
+    }
+
+    fn candidates(&self, cell: usize) -> u32 {
+        let (row, col) = (cell / self.side, cell % self.side);
+        let all = ((1u32 << self.numbers) - 1) << 1;
+        let used = self
+            .rules
+            .houses_of(row, col)
+            .iter()
+            .fold(0, |used, &house| used | self.house_used[house]);
+        self.rules.restrict(&self.grid, row, col, all & !used)
+    }
+
+    fn search(&mut self, solutions: &mut Vec<Grid>, limit: usize, randomize: bool) {
+        if solutions.len() >= limit || self.steps_left == 0 {
+            return; // Early exit if we already found enough solutions or ran out of steps
+        }
+        self.steps_left -= 1;
+
+        let mut placed = Vec::new();
+        let outcome = self.propagate(&mut placed);
+
+        if let Some((cell, mask)) = outcome {
+            let mut numbers: Vec<u8> = (1..=self.numbers as u8)
+                .filter(|&num| mask & (1 << num) != 0)
+                .collect();
+            if randomize {
+                GENERATOR_RNG.with(|rng| numbers.shuffle(&mut *rng.borrow_mut()));
+            }
+
+            for num in numbers {
+                self.place(cell, num);
+                self.search(solutions, limit, randomize);
+                self.unplace(cell);
+
+                if solutions.len() >= limit || self.steps_left == 0 {
+                    break; // Early exit
+                }
+            }
+        } else if self.cells.iter().all(|&cell| self.value(cell).is_some()) {
+            // No empty cell left, so we found a complete solution
+            solutions.push(self.grid.clone());
+        }
+
+        for cell in placed.into_iter().rev() {
+            self.unplace(cell);
+        }
+    }
+
+    // Places forced numbers until none are left. Returns the empty cell with
+    // the fewest candidates to branch on, or `None` if the grid is full or a
+    // contradiction was found.
+    fn propagate(&mut self, placed: &mut Vec<usize>) -> Option<(usize, u32)> {
+        let mut masks = vec![0u32; self.side * self.side];
+        loop {
+            let mut best: Option<(usize, u32)> = None;
+            let mut progress = false;
+
+            // Naked singles
+            for index in 0..self.cells.len() {
+                let cell = self.cells[index];
+                masks[cell] = 0;
+                if self.value(cell).is_some() {
+                    continue;
+                }
+                let mask = self.candidates(cell);
+                match mask.count_ones() {
+                    0 => return None, // Contradiction
+                    1 => {
+                        self.place(cell, mask.trailing_zeros() as u8);
+                        placed.push(cell);
+                        progress = true;
+                    }
+                    count => {
+                        masks[cell] = mask;
+                        if best.is_none_or(|(_, fewest)| count < fewest.count_ones()) {
+                            best = Some((cell, mask));
+                        }
+                    }
+                }
+            }
+            if progress {
+                continue;
+            }
+
+            // Hidden singles, only in houses that must hold every number.
+            // Placing numbers only removes candidates, so the masks from the
+            // pass above may hold extra candidates but never miss one.
+            for house in 0..self.houses.len() {
+                if self.houses[house].len() != self.numbers {
+                    continue;
+                }
+                for num in 1..=self.numbers as u8 {
+                    if self.house_used[house] & (1 << num) != 0 {
+                        continue;
+                    }
+                    let mut spots = self.houses[house]
+                        .iter()
+                        .filter(|&&cell| self.value(cell).is_none() && masks[cell] & (1 << num) != 0);
+                    match (spots.next(), spots.next()) {
+                        (None, _) => return None, // Contradiction
+                        (Some(&cell), None) => {
+                            if self.candidates(cell) & (1 << num) == 0 {
+                                return None; // The only spot was taken away
+                            }
+                            self.place(cell, num);
+                            placed.push(cell);
+                            progress = true;
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            if !progress {
+                return best;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PUZZLE: &str = "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79";
+
+    fn classic_game(puzzle: &str) -> SudokuGame {
+        let size = BoardSize::Nine;
+        let mut grid = size.empty_grid();
+        for (index, ch) in puzzle.chars().enumerate() {
+            grid[index / 9][index % 9] = ch.to_digit(10).map(|num| num as u8);
+        }
+        SudokuGame::from_puzzle(Rules::classic(size), grid)
+    }
+
+    fn reported_hints(game: &SudokuGame) -> Option<usize> {
+        game.events().iter().find_map(|event| match event {
+            GameEvent::Completed { hints, .. } => Some(*hints),
+            _ => None,
+        })
+    }
+
+    #[test]
+    fn undone_hints_still_count() {
+        let mut game = classic_game(PUZZLE);
+        let solution = game.solution().unwrap();
+
+        assert!(game.solve_one_cell());
+        assert!(game.undo());
+        assert!(game.solve_one_cell());
+        assert!(game.solve_one_cell());
+        assert!(game.undo());
+        assert_eq!(game.hints_taken, 3);
+
+        for (row, col) in game.size.cells() {
+            if game.grid[row][col].is_none() {
+                game.select_cell(row, col);
+                game.input_number(solution[row][col].unwrap());
+            }
+        }
+        assert!(game.is_solved());
+        assert_eq!(reported_hints(&game), Some(3));
+        assert_eq!(game.mistakes, 0);
+    }
+
+    #[test]
+    fn hints_finishing_the_puzzle_are_counted() {
+        let mut game = classic_game(PUZZLE);
+        let empty = game.size.cell_count() - game.filled_count();
+        for _ in 0..empty {
+            assert!(game.solve_one_cell());
+        }
+        assert!(game.is_solved());
+        assert_eq!(reported_hints(&game), Some(empty));
+    }
+
+    #[test]
+    fn suggested_givens_make_the_puzzle_unique() {
+        for size in [BoardSize::Nine, BoardSize::Sixteen] {
+            let rules = Rules::classic(size);
+            let mut grid = size.empty_grid();
+            let report = SudokuGame::analyze_ambiguity(&rules, &grid).unwrap();
+            assert!(report.complete);
+            for &(row, col, num) in &report.suggested_givens {
+                grid[row][col] = Some(num);
+            }
+            assert_eq!(SudokuGame::from_puzzle(rules.clone(), grid.clone()).solution_count(), 1);
+            assert_eq!(SudokuGame::analyze_ambiguity(&rules, &grid), None);
+        }
+    }
+
+    #[test]
+    fn unique_puzzles_have_no_ambiguity() {
+        let game = classic_game(PUZZLE);
+        assert_eq!(SudokuGame::analyze_ambiguity(&game.rules, &game.initial_grid), None);
+    }
+}
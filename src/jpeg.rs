@@ -0,0 +1,487 @@
+//! A small JPEG decoder for photo import
+//!
+//! Only the luminance of baseline (and extended sequential) Huffman-coded
+//! JPEGs is decoded, which is what phone cameras and screenshot tools write;
+//! chroma blocks are read past without transforming them. Progressive and
+//! arithmetic-coded files are rejected. The EXIF orientation of photos is
+//! applied so the image comes out upright.
+
+use crate::photo_import::GrayImage;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum JpegError {
+    // Progressive, lossless, arithmetic-coded or 12-bit
+    Unsupported,
+    Corrupt,
+}
+
+// Zigzag position of each coefficient in natural (row-major) order
+const ZIGZAG: [usize; 64] = [
+    0, 1, 8, 16, 9, 2, 3, 10, 17, 24, 32, 25, 18, 11, 4, 5, 12, 19, 26, 33, 40, 48, 41, 34, 27, 20,
+    13, 6, 7, 14, 21, 28, 35, 42, 49, 56, 57, 50, 43, 36, 29, 22, 15, 23, 30, 37, 44, 51, 58, 59,
+    52, 45, 38, 31, 39, 46, 53, 60, 61, 54, 47, 55, 62, 63,
+];
+
+#[derive(Clone, Default)]
+struct Huffman {
+    // For each code length, the largest code of that length (or -1) and the
+    // index of its first value
+    max_code: [i32; 17],
+    first_index: [i32; 17],
+    first_code: [i32; 17],
+    values: Vec<u8>,
+}
+
+impl Huffman {
+    fn new(counts: &[u8], values: Vec<u8>) -> Self {
+        let mut table = Self {
+            max_code: [-1; 17],
+            values,
+            ..Self::default()
+        };
+        let (mut code, mut index) = (0i32, 0i32);
+        for length in 1..=16 {
+            let count = counts[length - 1] as i32;
+            table.first_index[length] = index;
+            table.first_code[length] = code;
+            if count > 0 {
+                table.max_code[length] = code + count - 1;
+            }
+            code = (code + count) << 1;
+            index += count;
+        }
+        table
+    }
+}
+
+struct Component {
+    id: u8,
+    h: usize,
+    v: usize,
+    quant: usize,
+    dc_table: usize,
+    ac_table: usize,
+    dc_pred: i32,
+}
+
+// Reads entropy-coded bits, skipping stuffed zero bytes
+struct BitReader<'a> {
+    data: &'a [u8],
+    position: usize,
+    buffer: u32,
+    bits: u32,
+}
+
+impl BitReader<'_> {
+    fn bit(&mut self) -> Result<u32, JpegError> {
+        if self.bits == 0 {
+            let byte = *self.data.get(self.position).ok_or(JpegError::Corrupt)?;
+            if byte == 0xFF {
+                match self.data.get(self.position + 1) {
+                    Some(0) => self.position += 1,
+                    // A marker ends the data; pad with ones like libjpeg does
+                    _ => {
+                        self.buffer = 0xFF;
+                        self.bits = 8;
+                        return self.bit();
+                    }
+                }
+            }
+            self.position += 1;
+            self.buffer = byte as u32;
+            self.bits = 8;
+        }
+        self.bits -= 1;
+        Ok((self.buffer >> self.bits) & 1)
+    }
+
+    fn receive(&mut self, count: u8) -> Result<i32, JpegError> {
+        let mut value = 0;
+        for _ in 0..count {
+            value = (value << 1) | self.bit()? as i32;
+        }
+        Ok(value)
+    }
+
+    // A `count`-bit value, sign-extended the JPEG way
+    fn receive_extend(&mut self, count: u8) -> Result<i32, JpegError> {
+        if count == 0 {
+            return Ok(0);
+        }
+        let value = self.receive(count)?;
+        Ok(if value < 1 << (count - 1) {
+            value - (1 << count) + 1
+        } else {
+            value
+        })
+    }
+
+    fn decode(&mut self, table: &Huffman) -> Result<u8, JpegError> {
+        let mut code = 0i32;
+        for length in 1..=16 {
+            code = (code << 1) | self.bit()? as i32;
+            if code <= table.max_code[length] {
+                let index = table.first_index[length] + code - table.first_code[length];
+                return table.values.get(index as usize).copied().ok_or(JpegError::Corrupt);
+            }
+        }
+        Err(JpegError::Corrupt)
+    }
+
+    // Skips to the restart marker and past it
+    fn restart(&mut self) {
+        self.bits = 0;
+        while self.position + 1 < self.data.len()
+            && !(self.data[self.position] == 0xFF && (0xD0..=0xD7).contains(&self.data[self.position + 1]))
+        {
+            self.position += 1;
+        }
+        self.position += 2;
+    }
+}
+
+pub fn decode(bytes: &[u8]) -> Result<GrayImage, JpegError> {
+    let mut quant = [[0u16; 64]; 4];
+    let mut dc_tables: [Huffman; 4] = Default::default();
+    let mut ac_tables: [Huffman; 4] = Default::default();
+    let mut components: Vec<Component> = Vec::new();
+    let (mut width, mut height) = (0, 0);
+    let mut restart_interval = 0;
+    let mut orientation = 1;
+
+    let mut position = 2;
+    if bytes.get(..2) != Some(&[0xFF, 0xD8]) {
+        return Err(JpegError::Corrupt);
+    }
+    loop {
+        // Markers may be padded with any number of 0xFF bytes
+        while bytes.get(position) == Some(&0xFF) && bytes.get(position + 1) == Some(&0xFF) {
+            position += 1;
+        }
+        let marker = match bytes.get(position..position + 2) {
+            Some(&[0xFF, marker]) => marker,
+            _ => return Err(JpegError::Corrupt),
+        };
+        position += 2;
+        if marker == 0xD9 {
+            return Err(JpegError::Corrupt);
+        }
+        let length = match bytes.get(position..position + 2) {
+            Some(&[high, low]) => (high as usize) << 8 | low as usize,
+            _ => return Err(JpegError::Corrupt),
+        };
+        let segment = bytes.get(position + 2..position + length).ok_or(JpegError::Corrupt)?;
+        position += length;
+
+        match marker {
+            // Quantization tables, 8 or 16 bits per entry
+            0xDB => {
+                let mut rest = segment;
+                while let Some((&info, tail)) = rest.split_first() {
+                    let wide = info >> 4 != 0;
+                    let table = &mut quant[(info & 3) as usize];
+                    let size = if wide { 128 } else { 64 };
+                    let entries = tail.get(..size).ok_or(JpegError::Corrupt)?;
+                    for (index, &zigzag) in ZIGZAG.iter().enumerate() {
+                        table[zigzag] = if wide {
+                            u16::from_be_bytes([entries[2 * index], entries[2 * index + 1]])
+                        } else {
+                            entries[index] as u16
+                        };
+                    }
+                    rest = &tail[size..];
+                }
+            }
+            0xC4 => {
+                let mut rest = segment;
+                while let Some((&info, tail)) = rest.split_first() {
+                    let counts = tail.get(..16).ok_or(JpegError::Corrupt)?;
+                    let total: usize = counts.iter().map(|&count| count as usize).sum();
+                    let values = tail.get(16..16 + total).ok_or(JpegError::Corrupt)?.to_vec();
+                    let table = Huffman::new(counts, values);
+                    if info >> 4 == 0 {
+                        dc_tables[(info & 3) as usize] = table;
+                    } else {
+                        ac_tables[(info & 3) as usize] = table;
+                    }
+                    rest = &tail[16 + total..];
+                }
+            }
+            0xC0 | 0xC1 => {
+                if segment.first() != Some(&8) || segment.len() < 6 {
+                    return Err(JpegError::Unsupported);
+                }
+                height = u16::from_be_bytes([segment[1], segment[2]]) as usize;
+                width = u16::from_be_bytes([segment[3], segment[4]]) as usize;
+                let count = segment[5] as usize;
+                for index in 0..count {
+                    let data = segment.get(6 + 3 * index..9 + 3 * index).ok_or(JpegError::Corrupt)?;
+                    components.push(Component {
+                        id: data[0],
+                        h: (data[1] >> 4).max(1) as usize,
+                        v: (data[1] & 15).max(1) as usize,
+                        quant: (data[2] & 3) as usize,
+                        dc_table: 0,
+                        ac_table: 0,
+                        dc_pred: 0,
+                    });
+                }
+            }
+            // Every other frame type
+            0xC2 | 0xC3 | 0xC5..=0xC7 | 0xC9..=0xCB | 0xCD..=0xCF => return Err(JpegError::Unsupported),
+            0xDD => {
+                restart_interval = u16::from_be_bytes([
+                    *segment.first().ok_or(JpegError::Corrupt)?,
+                    *segment.get(1).ok_or(JpegError::Corrupt)?,
+                ]) as usize;
+            }
+            0xE1 => orientation = exif_orientation(segment).unwrap_or(orientation),
+            0xDA => {
+                if components.is_empty() || width == 0 || height == 0 {
+                    return Err(JpegError::Corrupt);
+                }
+                let count = *segment.first().ok_or(JpegError::Corrupt)? as usize;
+                let mut scan = Vec::new();
+                for index in 0..count {
+                    let data = segment.get(1 + 2 * index..3 + 2 * index).ok_or(JpegError::Corrupt)?;
+                    let component = components
+                        .iter()
+                        .position(|component| component.id == data[0])
+                        .ok_or(JpegError::Corrupt)?;
+                    components[component].dc_table = (data[1] >> 4 & 3) as usize;
+                    components[component].ac_table = (data[1] & 3) as usize;
+                    scan.push(component);
+                }
+                let mut reader = BitReader {
+                    data: &bytes[position..],
+                    position: 0,
+                    buffer: 0,
+                    bits: 0,
+                };
+                let luma = decode_scan(
+                    &mut reader,
+                    &mut components,
+                    &scan,
+                    (width, height),
+                    restart_interval,
+                    &quant,
+                    (&dc_tables, &ac_tables),
+                )?;
+                return Ok(orient(luma, orientation));
+            }
+            _ => {}
+        }
+    }
+}
+
+// Decodes the first scan, which for a sequential JPEG holds every block, and
+// returns the luminance
+fn decode_scan(
+    reader: &mut BitReader,
+    components: &mut [Component],
+    scan: &[usize],
+    (width, height): (usize, usize),
+    restart_interval: usize,
+    quant: &[[u16; 64]; 4],
+    (dc_tables, ac_tables): (&[Huffman; 4], &[Huffman; 4]),
+) -> Result<GrayImage, JpegError> {
+    let max_h = components.iter().map(|component| component.h).max().unwrap_or(1);
+    let max_v = components.iter().map(|component| component.v).max().unwrap_or(1);
+    let luma = &components[0];
+    let (luma_h, luma_v) = (luma.h, luma.v);
+    // Luminance is stored at its own resolution, which is the full one unless
+    // a chroma component is sampled more finely
+    let plane_width = (width * luma_h).div_ceil(max_h);
+    let plane_height = (height * luma_v).div_ceil(max_v);
+
+    // A single-component scan covers that component's blocks one by one;
+    // otherwise each MCU holds h x v blocks of every component
+    let (mcus_x, mcus_y) = if scan.len() == 1 {
+        (plane_width.div_ceil(8), plane_height.div_ceil(8))
+    } else {
+        (width.div_ceil(8 * max_h), height.div_ceil(8 * max_v))
+    };
+    let stride = if scan.len() == 1 { mcus_x * 8 } else { mcus_x * luma_h * 8 };
+    let rows = if scan.len() == 1 { mcus_y * 8 } else { mcus_y * luma_v * 8 };
+    let mut plane = vec![0u8; stride * rows];
+    let idct = idct_table();
+
+    let mut block = [0i32; 64];
+    for mcu in 0..mcus_x * mcus_y {
+        if restart_interval > 0 && mcu > 0 && mcu % restart_interval == 0 {
+            reader.restart();
+            for component in components.iter_mut() {
+                component.dc_pred = 0;
+            }
+        }
+        let (mcu_x, mcu_y) = (mcu % mcus_x, mcu / mcus_x);
+        for &index in scan {
+            let (h, v) = if scan.len() == 1 {
+                (1, 1)
+            } else {
+                (components[index].h, components[index].v)
+            };
+            for block_y in 0..v {
+                for block_x in 0..h {
+                    let component = &mut components[index];
+                    decode_block(
+                        reader,
+                        component,
+                        &dc_tables[component.dc_table],
+                        &ac_tables[component.ac_table],
+                        &mut block,
+                    )?;
+                    if index != 0 {
+                        continue;
+                    }
+                    let x = (mcu_x * h + block_x) * 8;
+                    let y = (mcu_y * v + block_y) * 8;
+                    let table = &quant[component.quant];
+                    let pixels = inverse_dct(&block, table, &idct);
+                    for row in 0..8 {
+                        let start = (y + row) * stride + x;
+                        plane[start..start + 8].copy_from_slice(&pixels[row * 8..row * 8 + 8]);
+                    }
+                }
+            }
+        }
+    }
+
+    // Crop the block padding, then stretch subsampled luminance to full size
+    let mut pixels = Vec::with_capacity(width * height);
+    for y in 0..height {
+        let source_row = (y * luma_v / max_v).min(rows - 1);
+        for x in 0..width {
+            let source_col = (x * luma_h / max_h).min(stride - 1);
+            pixels.push(plane[source_row * stride + source_col]);
+        }
+    }
+    Ok(GrayImage { width, height, pixels })
+}
+
+// Reads one block's coefficients in natural order, not yet dequantized
+fn decode_block(
+    reader: &mut BitReader,
+    component: &mut Component,
+    dc_table: &Huffman,
+    ac_table: &Huffman,
+    block: &mut [i32; 64],
+) -> Result<(), JpegError> {
+    *block = [0; 64];
+    let size = reader.decode(dc_table)?;
+    component.dc_pred += reader.receive_extend(size)?;
+    block[0] = component.dc_pred;
+
+    let mut index = 1;
+    while index < 64 {
+        let symbol = reader.decode(ac_table)?;
+        let (run, size) = (symbol >> 4, symbol & 15);
+        if size == 0 {
+            if run != 15 {
+                break; // End of block
+            }
+            index += 16;
+            continue;
+        }
+        index += run as usize;
+        if index >= 64 {
+            return Err(JpegError::Corrupt);
+        }
+        block[ZIGZAG[index]] = reader.receive_extend(size)?;
+        index += 1;
+    }
+    Ok(())
+}
+
+// cos((2x + 1) u pi / 16), scaled by the DC normalization for u = 0
+fn idct_table() -> [[f32; 8]; 8] {
+    let mut table = [[0.0; 8]; 8];
+    for (x, row) in table.iter_mut().enumerate() {
+        for (u, value) in row.iter_mut().enumerate() {
+            let scale = if u == 0 { std::f32::consts::FRAC_1_SQRT_2 } else { 1.0 };
+            *value = scale * ((2 * x + 1) as f32 * u as f32 * std::f32::consts::PI / 16.0).cos();
+        }
+    }
+    table
+}
+
+fn inverse_dct(block: &[i32; 64], quant: &[u16; 64], idct: &[[f32; 8]; 8]) -> [u8; 64] {
+    let coefficients: Vec<f32> = block
+        .iter()
+        .zip(quant)
+        .map(|(&value, &step)| (value * step as i32) as f32)
+        .collect();
+
+    // Rows, then columns
+    let mut rows = [0.0f32; 64];
+    for v in 0..8 {
+        for x in 0..8 {
+            rows[v * 8 + x] = (0..8).map(|u| idct[x][u] * coefficients[v * 8 + u]).sum();
+        }
+    }
+    let mut pixels = [0u8; 64];
+    for y in 0..8 {
+        for x in 0..8 {
+            let value: f32 = (0..8).map(|v| idct[y][v] * rows[v * 8 + x]).sum();
+            pixels[y * 8 + x] = (value / 4.0 + 128.0).round().clamp(0.0, 255.0) as u8;
+        }
+    }
+    pixels
+}
+
+// The orientation tag (1 to 8) of an EXIF segment
+fn exif_orientation(segment: &[u8]) -> Option<u16> {
+    let tiff = segment.strip_prefix(b"Exif\0\0")?;
+    let little = match tiff.get(..2)? {
+        b"II" => true,
+        b"MM" => false,
+        _ => return None,
+    };
+    let read16 = |offset: usize| -> Option<u16> {
+        let bytes = [*tiff.get(offset)?, *tiff.get(offset + 1)?];
+        Some(if little { u16::from_le_bytes(bytes) } else { u16::from_be_bytes(bytes) })
+    };
+    let read32 = |offset: usize| -> Option<u32> {
+        let bytes = [*tiff.get(offset)?, *tiff.get(offset + 1)?, *tiff.get(offset + 2)?, *tiff.get(offset + 3)?];
+        Some(if little { u32::from_le_bytes(bytes) } else { u32::from_be_bytes(bytes) })
+    };
+    let directory = read32(4)? as usize;
+    let entries = read16(directory)? as usize;
+    (0..entries)
+        .map(|entry| directory + 2 + 12 * entry)
+        .find(|&offset| read16(offset) == Some(0x0112))
+        .and_then(|offset| read16(offset + 8))
+}
+
+// Turns the image upright. Mirrored orientations are rare enough to ignore.
+fn orient(image: GrayImage, orientation: u16) -> GrayImage {
+    let GrayImage { width, height, pixels } = &image;
+    let (width, height) = (*width, *height);
+    match orientation {
+        3 => GrayImage {
+            width,
+            height,
+            pixels: pixels.iter().rev().copied().collect(),
+        },
+        // Rotated 90 degrees clockwise, or counter-clockwise
+        6 | 8 => {
+            let mut rotated = Vec::with_capacity(pixels.len());
+            for y in 0..width {
+                for x in 0..height {
+                    let (source_x, source_y) = if orientation == 6 {
+                        (y, height - 1 - x)
+                    } else {
+                        (width - 1 - y, x)
+                    };
+                    rotated.push(pixels[source_y * width + source_x]);
+                }
+            }
+            GrayImage {
+                width: height,
+                height: width,
+                pixels: rotated,
+            }
+        }
+        _ => image,
+    }
+}
@@ -0,0 +1,332 @@
+//! Local leaderboards of solved puzzles
+//!
+//! Every solved generated puzzle adds an entry for the current profile.
+//! Entries are ranked by time, then by hints and mistakes, within a scope:
+//! one difficulty on one kind of board, or one seeded puzzle such as the
+//! puzzle of the day.
+
+use crate::constraints::GlobalRule;
+use crate::game_logic::{self, BoardSize, Difficulty, GenerationOptions, SudokuGame, Symmetry, Variant};
+use crate::storage;
+
+const LEADERBOARD_KEY: &str = "leaderboard";
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Entry {
+    pub player: String,
+    pub seconds: u64,
+    pub hints: usize,
+    pub mistakes: usize,
+    pub difficulty: Difficulty,
+    pub options: GenerationOptions,
+    pub seed: Option<u64>,
+    pub daily: bool,
+    // When the puzzle was solved, in seconds since the Unix epoch
+    pub finished_at: u64,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum Scope {
+    // Any puzzle of a difficulty with this board size and variant
+    Difficulty {
+        difficulty: Difficulty,
+        size: BoardSize,
+        variant: Variant,
+    },
+    // The puzzle generated from this seed, difficulty and options
+    Puzzle {
+        seed: u64,
+        difficulty: Difficulty,
+        options: GenerationOptions,
+    },
+}
+
+impl Scope {
+    fn contains(&self, entry: &Entry) -> bool {
+        match self {
+            Scope::Difficulty {
+                difficulty,
+                size,
+                variant,
+            } => entry.difficulty == *difficulty && entry.options.size == *size && entry.options.variant == *variant,
+            Scope::Puzzle {
+                seed,
+                difficulty,
+                options,
+            } => entry.seed == Some(*seed) && entry.difficulty == *difficulty && entry.options == *options,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Filter {
+    pub scope: Scope,
+    // `None` for every player
+    pub player: Option<String>,
+    pub no_hints: bool,
+}
+
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct Leaderboard {
+    pub entries: Vec<Entry>,
+}
+
+impl Leaderboard {
+    pub fn load() -> Self {
+        let entries = storage::load(LEADERBOARD_KEY)
+            .unwrap_or_default()
+            .lines()
+            .filter_map(parse_entry)
+            .collect();
+        Self { entries }
+    }
+
+    /// Adds a solved game under `player`. Imported and hand-made puzzles have
+    /// no difficulty and are left out.
+    pub fn record(&mut self, game: &SudokuGame, player: &str) {
+        let Some(generation) = &game.generation else {
+            return;
+        };
+        self.entries.push(Entry {
+            player: player.to_string(),
+            seconds: game.elapsed_seconds(),
+            hints: game.hints_taken,
+            mistakes: game.mistakes,
+            difficulty: generation.difficulty.clone(),
+            options: generation.options.clone(),
+            seed: generation.seed,
+            daily: generation.daily,
+            finished_at: game_logic::unix_seconds(),
+        });
+        self.save();
+    }
+
+    /// Moves a renamed player's entries to the new name
+    pub fn rename_player(&mut self, old: &str, new: &str) {
+        let mut changed = false;
+        for entry in self.entries.iter_mut().filter(|entry| entry.player == old) {
+            entry.player = new.to_string();
+            changed = true;
+        }
+        if changed {
+            self.save();
+        }
+    }
+
+    /// Entries matching the filter, best first
+    pub fn ranking(&self, filter: &Filter) -> Vec<&Entry> {
+        let mut ranked: Vec<&Entry> = self
+            .entries
+            .iter()
+            .filter(|entry| filter.scope.contains(entry))
+            .filter(|entry| filter.player.as_ref().is_none_or(|player| &entry.player == player))
+            .filter(|entry| !filter.no_hints || entry.hints == 0)
+            .collect();
+        ranked.sort_by_key(|entry| (entry.seconds, entry.hints, entry.mistakes, entry.finished_at));
+        ranked
+    }
+
+    // One tab-separated entry per line
+    fn save(&self) {
+        let data = self
+            .entries
+            .iter()
+            .map(|entry| {
+                [
+                    entry.player.clone(),
+                    entry.seconds.to_string(),
+                    entry.hints.to_string(),
+                    entry.mistakes.to_string(),
+                    index_of(&Difficulty::all(), &entry.difficulty).to_string(),
+                    encode_options(&entry.options),
+                    entry.seed.map(|seed| seed.to_string()).unwrap_or_default(),
+                    (entry.daily as u8).to_string(),
+                    entry.finished_at.to_string(),
+                ]
+                .join("\t")
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        storage::save(LEADERBOARD_KEY, &data);
+    }
+}
+
+fn parse_entry(line: &str) -> Option<Entry> {
+    let fields: Vec<&str> = line.split('\t').collect();
+    let field = |index: usize| fields.get(index).copied();
+    Some(Entry {
+        player: field(0)?.to_string(),
+        seconds: field(1)?.parse().ok()?,
+        hints: field(2)?.parse().ok()?,
+        mistakes: field(3)?.parse().ok()?,
+        difficulty: Difficulty::all().get(field(4)?.parse::<usize>().ok()?)?.clone(),
+        options: decode_options(field(5)?)?,
+        seed: field(6)?.parse().ok(),
+        daily: field(7)? == "1",
+        finished_at: field(8)?.parse().ok()?,
+    })
+}
+
+// Size, variant and symmetry indices, whether minimal, and a bit per global
+// rule, separated by dots
+fn encode_options(options: &GenerationOptions) -> String {
+    let rules: usize = GlobalRule::all()
+        .iter()
+        .enumerate()
+        .filter(|(_, rule)| options.global_rules.contains(rule))
+        .map(|(index, _)| 1 << index)
+        .sum();
+    format!(
+        "{}.{}.{}.{}.{}",
+        index_of(&BoardSize::all(), &options.size),
+        index_of(&Variant::all(), &options.variant),
+        index_of(&Symmetry::all(), &options.symmetry),
+        options.minimal as u8,
+        rules
+    )
+}
+
+fn decode_options(text: &str) -> Option<GenerationOptions> {
+    let parts: Vec<usize> = text.split('.').map(|part| part.parse().ok()).collect::<Option<_>>()?;
+    let &[size, variant, symmetry, minimal, rules] = parts.as_slice() else {
+        return None;
+    };
+    Some(GenerationOptions {
+        size: *BoardSize::all().get(size)?,
+        variant: *Variant::all().get(variant)?,
+        symmetry: *Symmetry::all().get(symmetry)?,
+        minimal: minimal == 1,
+        global_rules: GlobalRule::all()
+            .into_iter()
+            .enumerate()
+            .filter(|(index, _)| rules & (1 << index) != 0)
+            .map(|(_, rule)| rule)
+            .collect(),
+    })
+}
+
+fn index_of<T: PartialEq>(all: &[T], value: &T) -> usize {
+    all.iter().position(|item| item == value).unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_logic::Generation;
+    use crate::puzzle_format::parse_puzzle;
+
+    const PUZZLE: &str = "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79";
+
+    // A seeded game solved after taking two hints, one of them undone
+    fn solved_game() -> SudokuGame {
+        let (rules, grid) = parse_puzzle(&format!("grid {PUZZLE}")).unwrap();
+        let mut game = SudokuGame::from_puzzle(rules, grid);
+        game.generation = Some(Generation {
+            difficulty: Difficulty::Hard,
+            options: GenerationOptions::default(),
+            seed: Some(20261018),
+            daily: true,
+        });
+        let solution = game.solution().unwrap();
+        assert!(game.solve_one_cell());
+        assert!(game.undo());
+        assert!(game.solve_one_cell());
+        for (row, col) in game.size.cells() {
+            if game.grid[row][col].is_none() {
+                game.select_cell(row, col);
+                game.input_number(solution[row][col].unwrap());
+            }
+        }
+        assert!(game.is_solved());
+        game
+    }
+
+    #[test]
+    fn entries_survive_a_reload() {
+        storage::save(LEADERBOARD_KEY, "");
+        let mut leaderboard = Leaderboard::load();
+        assert!(leaderboard.entries.is_empty());
+
+        let mut game = solved_game();
+        leaderboard.record(&game, "Pig");
+        game.generation = None;
+        leaderboard.record(&game, "Pig");
+        assert_eq!(leaderboard.entries.len(), 1);
+        assert_eq!(leaderboard.entries[0].hints, 2);
+        assert_eq!(leaderboard.entries[0].seed, Some(20261018));
+
+        let mut options = GenerationOptions {
+            size: BoardSize::Sixteen,
+            variant: Variant::all()[1],
+            symmetry: Symmetry::all()[1],
+            minimal: true,
+            global_rules: vec![GlobalRule::AntiKing, GlobalRule::NonConsecutive],
+        };
+        leaderboard.entries.push(Entry {
+            player: "Piglet".into(),
+            seconds: 61,
+            hints: 0,
+            mistakes: 3,
+            difficulty: Difficulty::VeryEasy,
+            options: options.clone(),
+            seed: None,
+            daily: false,
+            finished_at: 1_700_000_000,
+        });
+        leaderboard.rename_player("Pig", "Hog");
+        assert_eq!(Leaderboard::load(), leaderboard);
+        assert_eq!(leaderboard.entries[0].player, "Hog");
+
+        options.global_rules.clear();
+        assert_eq!(decode_options(&encode_options(&options)), Some(options));
+        assert_eq!(decode_options("1.2.3"), None);
+        assert_eq!(parse_entry("Pig\t12\tmany"), None);
+    }
+
+    #[test]
+    fn rankings_put_the_fastest_first() {
+        let entry = |player: &str, seconds, hints| Entry {
+            player: player.into(),
+            seconds,
+            hints,
+            mistakes: 0,
+            difficulty: Difficulty::Medium,
+            options: GenerationOptions::default(),
+            seed: Some(7),
+            daily: false,
+            finished_at: 0,
+        };
+        let leaderboard = Leaderboard {
+            entries: vec![entry("a", 300, 0), entry("b", 100, 2), entry("a", 100, 1), entry("c", 50, 0)],
+        };
+        let scope = Scope::Puzzle {
+            seed: 7,
+            difficulty: Difficulty::Medium,
+            options: GenerationOptions::default(),
+        };
+        let players = |filter: Filter| -> Vec<(String, u64)> {
+            leaderboard.ranking(&filter).iter().map(|entry| (entry.player.clone(), entry.seconds)).collect()
+        };
+        let all = Filter {
+            scope: scope.clone(),
+            player: None,
+            no_hints: false,
+        };
+
+        assert_eq!(
+            players(all.clone()),
+            [("c".into(), 50), ("a".into(), 100), ("b".into(), 100), ("a".into(), 300)]
+        );
+        assert_eq!(players(Filter { no_hints: true, ..all.clone() }), [("c".into(), 50), ("a".into(), 300)]);
+        assert_eq!(
+            players(Filter { player: Some("a".into()), ..all.clone() }),
+            [("a".into(), 100), ("a".into(), 300)]
+        );
+        let other = Scope::Puzzle {
+            seed: 8,
+            difficulty: Difficulty::Medium,
+            options: GenerationOptions::default(),
+        };
+        assert!(players(Filter { scope: other, ..all }).is_empty());
+    }
+}
@@ -3,13 +3,33 @@ use dioxus_i18n::prelude::*;
 use dioxus_i18n::t;
 use dioxus_i18n::unic_langid::langid;
 
+mod achievements;
+mod constraints;
+mod digit_recognition;
+mod fpuzzles;
 mod game_logic;
+mod jpeg;
 mod frontend;
+mod leaderboard;
+mod lz_string;
+mod photo_import;
+mod print;
+mod profiles;
+mod puzzle_bank;
+mod puzzle_format;
+mod stats;
+mod storage;
+mod theme;
 
+use achievements::{Achievement, Achievements};
 use game_logic::SudokuGame;
+use leaderboard::Leaderboard;
+use profiles::Profiles;
+use stats::Stats;
+use theme::CustomPalettes;
 use frontend::{
-    DifficultySelector, GameControls, Instructions, MoveLog, NumberPanel, SudokuGrid, UndoRedoControls,
-    WinMessage,
+    AchievementToasts, AchievementsPanel, AmbiguityNotice, CagePanel, DifficultySelector, EditorPanel, GameAnnouncer, GameControls, ImportPanel, Instructions, LeaderboardPanel, PaletteEditor, PhotoImportPanel, PrintPanel, ProfilePanel, MoveLog, NumberPanel, StatsPanel, SudokuGrid, ThemeSelector,
+    UndoRedoControls, WinMessage,
 };
 
 fn main() {
@@ -18,9 +38,20 @@ fn main() {
 
 #[component]
 fn App() -> Element {
-    let current_locale = use_signal(|| langid!("en-US"));
+    // Open in the language the current player prefers
+    let current_locale = use_signal(|| {
+        Profiles::load()
+            .current()
+            .preferences
+            .language
+            .parse()
+            .unwrap_or(langid!("en-US"))
+    });
 
     rsx! {
+        // Phones lay the page out at their own width instead of a zoomed-out
+        // desktop one
+        document::Meta { name: "viewport", content: "width=device-width, initial-scale=1" }
         if current_locale() == langid!("en-US") {
             AppWithLocale {
                 key: "en-US",
@@ -54,24 +85,119 @@ fn AppWithLocale(
             ))
     });
 
-    let game = use_signal(|| SudokuGame::new());
-    let is_complete = game.read().is_complete();
+    let mut profiles = use_signal(Profiles::load);
+    let game = use_signal(|| SudokuGame::new_with_difficulty(profiles.peek().current().preferences.difficulty.clone()));
+    // Only re-analyze uniqueness when the givens change, not on every selection
+    let puzzle = use_memo(move || {
+        let game = game.read();
+        (game.rules.clone(), game.puzzle_grid())
+    });
+    let ambiguity = use_memo(move || {
+        let (rules, puzzle_grid) = puzzle();
+        SudokuGame::analyze_ambiguity(&rules, &puzzle_grid)
+    });
+    let is_editing = game.read().is_editing;
+    let is_complete = game.read().is_complete() && !is_editing;
+
+    // Switching players switches their language and statistics
+    let profile_id = use_memo(move || profiles.read().current().id);
+    let language = use_memo(move || profiles.read().current().preferences.language.clone());
+    use_effect(move || {
+        let language = language();
+        if language != current_locale.peek().to_string()
+            && let Ok(language) = language.parse()
+        {
+            current_locale.set(language);
+        }
+    });
+    let mut stats = use_signal(|| Stats::load(*profile_id.peek()));
+    use_effect(move || stats.set(Stats::load(profile_id())));
+    let mut achievements = use_signal(|| Achievements::load(*profile_id.peek()));
+    use_effect(move || achievements.set(Achievements::load(profile_id())));
+    let mut unlocked = use_signal(Vec::<&'static Achievement>::new);
+    // Pass what happens in the game on to the achievements. The game id and
+    // the number of its events already passed on.
+    let mut events_seen = use_signal(|| None::<(u64, usize)>);
+    use_effect(move || {
+        let game = game.read();
+        let seen = match *events_seen.peek() {
+            Some((id, seen)) if id == game.id() => seen,
+            _ => 0,
+        };
+        for event in &game.events()[seen..] {
+            let newly_unlocked = achievements.write().handle(event);
+            unlocked.write().extend(newly_unlocked);
+        }
+        events_seen.set(Some((game.id(), game.events().len())));
+    });
+    let mut leaderboard = use_signal(Leaderboard::load);
+    let palettes = use_signal(CustomPalettes::load);
+    let stylesheet = use_memo(move || {
+        let profiles = profiles.read();
+        let preferences = &profiles.current().preferences;
+        let mut stylesheet = preferences.theme.stylesheet(&palettes.read());
+        if preferences.colorblind {
+            stylesheet.push(' ');
+            stylesheet.push_str(&preferences.theme.colorblind_stylesheet(&palettes.read()));
+        }
+        stylesheet
+    });
+    let colorblind = profiles.read().current().preferences.colorblind;
+    let highlighting = profiles.read().current().preferences.highlighting;
+    // Shared by the number panel and long presses on the board
+    let is_note_mode = use_signal(|| false);
+    // Set once the current puzzle has been solved, so undoing and solving it
+    // again does not add another leaderboard entry
+    let mut finished = use_signal(|| false);
+    // Count every new puzzle as started, and once solved as completed
+    use_effect(move || {
+        puzzle();
+        finished.set(false);
+        stats.write().record_start(&game.peek());
+    });
+    let solved = use_memo(move || {
+        let game = game.read();
+        game.is_solved() && !game.is_editing
+    });
+    use_effect(move || {
+        if solved() && !*finished.peek() {
+            finished.set(true);
+            stats.write().record_completion(&game.peek());
+            leaderboard.write().record(&game.peek(), &profiles.peek().current().name);
+        }
+    });
 
     rsx! {
+        style { "{stylesheet}" }
         div {
-            style: "text-align: center; padding: 20px; font-family: Arial, sans-serif; background-color: #f0f0f0; min-height: 100vh;",
+            style: "text-align: center; padding: 20px clamp(8px, 3vw, 20px); font-family: Arial, sans-serif; background-color: var(--page); color: var(--text); min-height: 100vh;",
             tabindex: "0",
             onkeydown: {
                 let mut game = game.clone();
                 move |event: Event<KeyboardData>| {
                     use dioxus::events::Key;
                     match event.key() {
+                        // Ctrl+Z undoes, Ctrl+Y and Ctrl+Shift+Z redo
+                        Key::Character(ch) if event.modifiers().ctrl() || event.modifiers().meta() => {
+                            match ch.to_lowercase().as_str() {
+                                "z" if event.modifiers().shift() => {
+                                    game.write().redo();
+                                }
+                                "z" => {
+                                    game.write().undo();
+                                }
+                                "y" => {
+                                    game.write().redo();
+                                }
+                                _ => {}
+                            }
+                        }
                         Key::Character(ch) if ch.len() == 1 => {
                             let ch = ch.chars().next().unwrap();
-                            if ch.is_ascii_digit() && ch != '0' {
-                                if let Some(num) = ch.to_digit(10) {
-                                    game.write().input_number(num as u8);
-                                }
+                            // 16x16 boards take the hex digits 0-F, so 0 only clears on smaller boards
+                            let size = game.read().size;
+                            if let Some(num) = size.parse_digit(ch) {
+                                game.write().input_number(num);
                             } else if ch == '0' {
                                 game.write().clear_selected_cell();
                             }
@@ -101,9 +227,9 @@ fn AppWithLocale(
             },
 
             div {
-                style: "display: flex; justify-content: center; align-items: center; gap: 20px; margin-bottom: 20px;",
+                style: "display: flex; flex-wrap: wrap; justify-content: center; align-items: center; gap: 20px; margin-bottom: 20px;",
                 h1 {
-                    style: "color: #333; margin: 0;",
+                    style: "color: var(--text); margin: 0;",
                     {t!("game-title")}
                 }
                 button {
@@ -114,36 +240,58 @@ fn AppWithLocale(
                          } else {
                              langid!("en-US")
                          };
+                         let mut preferences = profiles.peek().current().preferences.clone();
+                         preferences.language = new_locale.to_string();
+                         profiles.write().set_preferences(preferences);
                          current_locale.set(new_locale);
                      },
                      if current_locale() == langid!("en-US") { "Switch to Chinese" } else { "Switch to English" }
                 }
+                ThemeSelector { profiles: profiles, palettes: palettes }
             }
 
             DifficultySelector { game: game }
 
             if is_complete {
-                WinMessage {}
+                WinMessage { profiles: profiles }
             }
 
+            // The panels move below the board when both don't fit side by side
             div {
-                style: "display: flex; justify-content: center; gap: 40px; align-items: flex-start; max-width: 1200px; margin: 0 auto;",
+                style: "display: flex; flex-wrap: wrap; justify-content: center; gap: 0 40px; align-items: flex-start; max-width: 1200px; margin: 0 auto;",
 
                 div {
-                    style: "display: flex; flex-direction: column; align-items: center;",
-                    SudokuGrid { game: game }
-                    GameControls { game: game }
+                    style: "display: flex; flex-direction: column; align-items: center; flex: 1 1 auto; min-width: 0;",
+                    SudokuGrid { game: game, ambiguity: ambiguity, colorblind: colorblind, highlighting: highlighting, is_note_mode: is_note_mode }
+                    GameAnnouncer { game: game }
+                    if is_editing {
+                        EditorPanel { game: game, ambiguity: ambiguity }
+                    } else {
+                        AmbiguityNotice { ambiguity: ambiguity }
+                        GameControls { game: game }
+                    }
                     UndoRedoControls { game: game }
                 }
 
                 div {
-                    style: "min-width: 300px;",
-                    NumberPanel { game: game }
+                    style: "flex: 1 1 300px; max-width: 480px; min-width: 0;",
+                    NumberPanel { game: game, is_note_mode: is_note_mode }
+                    CagePanel { game: game }
                     MoveLog { game: game }
+                    ProfilePanel { profiles: profiles, leaderboard: leaderboard }
+                    StatsPanel { stats: stats }
+                    AchievementsPanel { achievements: achievements }
+                    PaletteEditor { profiles: profiles, palettes: palettes }
+                    LeaderboardPanel { game: game, leaderboard: leaderboard, profiles: profiles }
+                    ImportPanel { game: game }
+                    PhotoImportPanel { game: game }
+                    PrintPanel { game: game }
                 }
             }
 
             Instructions {}
+
+            AchievementToasts { unlocked: unlocked }
         }
     }
 }
@@ -0,0 +1,381 @@
+//! Reading a puzzle from a photo or screenshot
+//!
+//! The grid is taken to be the largest connected group of dark lines in the
+//! image. Its four corners are found, the board is warped flat into a square
+//! and each cell's digit is read with the embedded classifier. Everything runs
+//! locally; readings the classifier isn't sure of are flagged for the player
+//! to check before the puzzle is loaded.
+
+use crate::digit_recognition;
+use crate::game_logic::{BoardSize, Grid};
+use crate::jpeg::{self, JpegError};
+
+// Side of the flattened board, divisible by every supported size
+const BOARD_SIDE: usize = 432;
+// Longest side the image is shrunk to before looking for the grid
+const DETECT_SIDE: usize = 800;
+// Below this probability a reading is flagged
+const CONFIDENT: f32 = 0.85;
+
+/// Sizes that can be read from a photo, whose digits are all 1 to 9
+pub const PHOTO_SIZES: [BoardSize; 3] = [BoardSize::Four, BoardSize::Six, BoardSize::Nine];
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum PhotoError {
+    // Neither PNG nor JPEG
+    Format,
+    Decode,
+    UnsupportedJpeg,
+    NoGrid,
+}
+
+#[derive(Clone, PartialEq)]
+pub struct GrayImage {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<u8>,
+}
+
+impl GrayImage {
+    fn get(&self, x: usize, y: usize) -> u8 {
+        self.pixels[y * self.width + x]
+    }
+
+    // Bilinear sample, clamped to the image
+    fn sample(&self, x: f32, y: f32) -> f32 {
+        let x = x.clamp(0.0, (self.width - 1) as f32);
+        let y = y.clamp(0.0, (self.height - 1) as f32);
+        let (x0, y0) = (x as usize, y as usize);
+        let (x1, y1) = ((x0 + 1).min(self.width - 1), (y0 + 1).min(self.height - 1));
+        let (fx, fy) = (x - x0 as f32, y - y0 as f32);
+        let top = self.get(x0, y0) as f32 * (1.0 - fx) + self.get(x1, y0) as f32 * fx;
+        let bottom = self.get(x0, y1) as f32 * (1.0 - fx) + self.get(x1, y1) as f32 * fx;
+        top * (1.0 - fy) + bottom * fy
+    }
+
+    // Box-filtered copy whose longest side is at most `side`, with the factor
+    // it was shrunk by
+    fn shrink(&self, side: usize) -> (GrayImage, usize) {
+        let factor = self.width.max(self.height).div_ceil(side).max(1);
+        let (width, height) = (self.width / factor, self.height / factor);
+        let mut pixels = Vec::with_capacity(width * height);
+        for y in 0..height {
+            for x in 0..width {
+                let total: usize = (0..factor)
+                    .flat_map(|dy| (0..factor).map(move |dx| (dx, dy)))
+                    .map(|(dx, dy)| self.get(x * factor + dx, y * factor + dy) as usize)
+                    .sum();
+                pixels.push((total / (factor * factor)) as u8);
+            }
+        }
+        (GrayImage { width, height, pixels }, factor)
+    }
+}
+
+/// One cell as read from the photo
+#[derive(Clone, Copy, PartialEq)]
+pub struct CellReading {
+    pub digit: Option<u8>,
+    pub unsure: bool,
+}
+
+/// A grid found in a photo, flattened into a square board
+#[derive(Clone, PartialEq)]
+pub struct PhotoScan {
+    board: GrayImage,
+    pub size: BoardSize,
+    pub cells: Vec<Vec<CellReading>>,
+}
+
+impl PhotoScan {
+    /// Reads the cells again as a board of another size, for when the size
+    /// was guessed wrong
+    pub fn resize(&mut self, size: BoardSize) {
+        self.size = size;
+        self.cells = read_cells(&self.board, size);
+    }
+
+    pub fn grid(&self) -> Grid {
+        self.cells
+            .iter()
+            .map(|row| row.iter().map(|cell| cell.digit).collect())
+            .collect()
+    }
+}
+
+pub fn scan_photo(bytes: &[u8]) -> Result<PhotoScan, PhotoError> {
+    let mut image = decode_image(bytes)?;
+
+    // Dark-mode screenshots have light lines on a dark background
+    let mut histogram = [0usize; 256];
+    for &pixel in &image.pixels {
+        histogram[pixel as usize] += 1;
+    }
+    let mut seen = 0;
+    let median = histogram
+        .iter()
+        .position(|&count| {
+            seen += count;
+            seen * 2 >= image.pixels.len()
+        })
+        .unwrap_or(255);
+    if median < 100 {
+        image.pixels.iter_mut().for_each(|pixel| *pixel = 255 - *pixel);
+    }
+
+    let corners = find_grid(&image).ok_or(PhotoError::NoGrid)?;
+    let board = flatten(&image, &corners);
+    let size = guess_size(&board);
+    Ok(PhotoScan {
+        cells: read_cells(&board, size),
+        board,
+        size,
+    })
+}
+
+fn decode_image(bytes: &[u8]) -> Result<GrayImage, PhotoError> {
+    if bytes.starts_with(&[0xFF, 0xD8]) {
+        return jpeg::decode(bytes).map_err(|error| match error {
+            JpegError::Unsupported => PhotoError::UnsupportedJpeg,
+            JpegError::Corrupt => PhotoError::Decode,
+        });
+    }
+    if !bytes.starts_with(b"\x89PNG") {
+        return Err(PhotoError::Format);
+    }
+
+    let mut decoder = png::Decoder::new(bytes);
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info().map_err(|_| PhotoError::Decode)?;
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buffer).map_err(|_| PhotoError::Decode)?;
+    let channels = info.color_type.samples();
+    let pixels = buffer[..info.buffer_size()]
+        .chunks(channels)
+        .map(|pixel| {
+            let (luma, alpha) = match pixel {
+                [gray] => (*gray as f32, 255.0),
+                [gray, alpha] => (*gray as f32, *alpha as f32),
+                [r, g, b, rest @ ..] => (
+                    0.299 * *r as f32 + 0.587 * *g as f32 + 0.114 * *b as f32,
+                    rest.first().map_or(255.0, |&alpha| alpha as f32),
+                ),
+                _ => (255.0, 255.0),
+            };
+            // Transparent areas count as white paper
+            (luma * alpha / 255.0 + 255.0 - alpha).round() as u8
+        })
+        .collect();
+    Ok(GrayImage {
+        width: info.width as usize,
+        height: info.height as usize,
+        pixels,
+    })
+}
+
+// Corners of the grid in image coordinates, clockwise from the top left
+fn find_grid(image: &GrayImage) -> Option<[(f32, f32); 4]> {
+    let (small, factor) = image.shrink(DETECT_SIDE);
+    let (width, height) = (small.width, small.height);
+    if width < 20 || height < 20 {
+        return None;
+    }
+
+    // A pixel is dark if it is clearly darker than its neighbourhood, which
+    // copes with uneven lighting across a photo
+    let mut integral = vec![0u64; (width + 1) * (height + 1)];
+    for y in 0..height {
+        let mut row = 0u64;
+        for x in 0..width {
+            row += small.get(x, y) as u64;
+            integral[(y + 1) * (width + 1) + x + 1] = integral[y * (width + 1) + x + 1] + row;
+        }
+    }
+    let radius = (width.max(height) / 40).max(3);
+    let dark: Vec<bool> = (0..width * height)
+        .map(|index| {
+            let (x, y) = (index % width, index / width);
+            let (x0, y0) = (x.saturating_sub(radius), y.saturating_sub(radius));
+            let (x1, y1) = ((x + radius + 1).min(width), (y + radius + 1).min(height));
+            let sum = integral[y1 * (width + 1) + x1] + integral[y0 * (width + 1) + x0]
+                - integral[y0 * (width + 1) + x1]
+                - integral[y1 * (width + 1) + x0];
+            let mean = sum as f32 / ((x1 - x0) * (y1 - y0)) as f32;
+            (small.get(x, y) as f32) < mean - 10.0
+        })
+        .collect();
+
+    // The grid's lines all join up, so it is the dark group spanning the
+    // largest area
+    let mut visited = vec![false; width * height];
+    let mut best: Option<(usize, Vec<usize>)> = None;
+    for start in 0..width * height {
+        if !dark[start] || visited[start] {
+            continue;
+        }
+        visited[start] = true;
+        let mut stack = vec![start];
+        let mut pixels = Vec::new();
+        while let Some(index) = stack.pop() {
+            pixels.push(index);
+            let (x, y) = ((index % width) as isize, (index / width) as isize);
+            for (dx, dy) in [(-1, -1), (0, -1), (1, -1), (-1, 0), (1, 0), (-1, 1), (0, 1), (1, 1)] {
+                let (nx, ny) = (x + dx, y + dy);
+                if nx < 0 || ny < 0 || nx >= width as isize || ny >= height as isize {
+                    continue;
+                }
+                let next = ny as usize * width + nx as usize;
+                if dark[next] && !visited[next] {
+                    visited[next] = true;
+                    stack.push(next);
+                }
+            }
+        }
+        let xs = pixels.iter().map(|index| index % width);
+        let ys = pixels.iter().map(|index| index / width);
+        let span = (xs.clone().max()? - xs.min()?) * (ys.clone().max()? - ys.min()?);
+        if best.as_ref().is_none_or(|(best_span, _)| span > *best_span) {
+            best = Some((span, pixels));
+        }
+    }
+    let (span, pixels) = best?;
+    if span * 10 < width * height {
+        return None;
+    }
+
+    // The extreme points along the diagonals are the corners
+    let point = |index: &usize| ((index % width) as f32, (index / width) as f32);
+    let extreme = |key: fn((f32, f32)) -> f32| {
+        pixels
+            .iter()
+            .map(point)
+            .max_by(|a, b| key(*a).total_cmp(&key(*b)))
+    };
+    let corners = [
+        extreme(|(x, y)| -x - y)?,
+        extreme(|(x, y)| x - y)?,
+        extreme(|(x, y)| x + y)?,
+        extreme(|(x, y)| y - x)?,
+    ];
+    let scale = factor as f32;
+    Some(corners.map(|(x, y)| ((x + 0.5) * scale, (y + 0.5) * scale)))
+}
+
+// Maps the unit square onto the quadrilateral, corners clockwise from the
+// top left
+fn homography(corners: &[(f32, f32); 4]) -> [f32; 8] {
+    let [(x0, y0), (x1, y1), (x2, y2), (x3, y3)] = *corners;
+    let (dx1, dx2, sx) = (x1 - x2, x3 - x2, x0 - x1 + x2 - x3);
+    let (dy1, dy2, sy) = (y1 - y2, y3 - y2, y0 - y1 + y2 - y3);
+    let determinant = dx1 * dy2 - dx2 * dy1;
+    let g = (sx * dy2 - dx2 * sy) / determinant;
+    let h = (dx1 * sy - sx * dy1) / determinant;
+    [x1 - x0 + g * x1, x3 - x0 + h * x3, x0, y1 - y0 + g * y1, y3 - y0 + h * y3, y0, g, h]
+}
+
+fn flatten(image: &GrayImage, corners: &[(f32, f32); 4]) -> GrayImage {
+    let [a, b, c, d, e, f, g, h] = homography(corners);
+    let mut pixels = Vec::with_capacity(BOARD_SIDE * BOARD_SIDE);
+    for y in 0..BOARD_SIDE {
+        for x in 0..BOARD_SIDE {
+            let (u, v) = ((x as f32 + 0.5) / BOARD_SIDE as f32, (y as f32 + 0.5) / BOARD_SIDE as f32);
+            let w = g * u + h * v + 1.0;
+            let source_x = (a * u + b * v + c) / w;
+            let source_y = (d * u + e * v + f) / w;
+            pixels.push(image.sample(source_x, source_y) as u8);
+        }
+    }
+    GrayImage {
+        width: BOARD_SIDE,
+        height: BOARD_SIDE,
+        pixels,
+    }
+}
+
+// Picks the size whose cell borders are darkest compared with the rest of
+// the board
+fn guess_size(board: &GrayImage) -> BoardSize {
+    // Mean darkness of each row and column together
+    let profile: Vec<f32> = (0..BOARD_SIDE)
+        .map(|line| {
+            let total: f32 = (0..BOARD_SIDE)
+                .map(|along| 510.0 - board.get(line, along) as f32 - board.get(along, line) as f32)
+                .sum();
+            total / (2 * BOARD_SIDE) as f32
+        })
+        .collect();
+    let average = profile.iter().sum::<f32>() / BOARD_SIDE as f32;
+
+    PHOTO_SIZES
+        .into_iter()
+        .max_by(|a, b| {
+            let score = |size: &BoardSize| {
+                let cells = size.side();
+                let borders = (1..cells).map(|border| {
+                    let center = border * BOARD_SIDE / cells;
+                    let window = center.saturating_sub(4)..(center + 5).min(BOARD_SIDE);
+                    profile[window].iter().copied().fold(0.0, f32::max)
+                });
+                borders.sum::<f32>() / (cells - 1) as f32 - average
+            };
+            score(a).total_cmp(&score(b))
+        })
+        .unwrap_or_default()
+}
+
+fn read_cells(board: &GrayImage, size: BoardSize) -> Vec<Vec<CellReading>> {
+    let cells = size.side();
+    let cell_side = BOARD_SIDE / cells;
+    // Leave out the borders, which can be a little off in a photo
+    let margin = cell_side * 3 / 20;
+    let inner = cell_side - 2 * margin;
+
+    (0..cells)
+        .map(|row| {
+            (0..cells)
+                .map(|col| {
+                    let (left, top) = (col * cell_side + margin, row * cell_side + margin);
+                    let pixels: Vec<u8> = (top..top + inner)
+                        .flat_map(|y| (left..left + inner).map(move |x| board.get(x, y)))
+                        .collect();
+                    read_cell(&pixels, inner, size.numbers())
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn read_cell(pixels: &[u8], side: usize, numbers: usize) -> CellReading {
+    let empty = CellReading {
+        digit: None,
+        unsure: false,
+    };
+
+    // Ink is measured against the cell's own paper and darkest strokes
+    let mut sorted = pixels.to_vec();
+    sorted.sort_unstable();
+    let paper = sorted[sorted.len() * 9 / 10] as f32;
+    let darkest = sorted[sorted.len() / 50] as f32;
+    if paper - darkest < 50.0 {
+        return empty;
+    }
+    let ink: Vec<f32> = pixels
+        .iter()
+        .map(|&pixel| ((paper - pixel as f32) / (paper - darkest)).clamp(0.0, 1.0))
+        .collect();
+
+    let Some(probabilities) = digit_recognition::classify(&ink, side, side) else {
+        return empty;
+    };
+    // Only digits that fit the board count; a reading that needed a larger
+    // board is kept but flagged
+    let (best, &probability) = probabilities[..numbers]
+        .iter()
+        .enumerate()
+        .max_by(|a, b| a.1.total_cmp(b.1))
+        .unwrap_or((0, &0.0));
+    CellReading {
+        digit: Some(best as u8 + 1),
+        unsure: probability < CONFIDENT,
+    }
+}
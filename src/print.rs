@@ -0,0 +1,797 @@
+//! Printable puzzle sheets as SVG or PDF
+//!
+//! Puzzles are laid out on A4 pages, one to six per page, with an optional
+//! answer key after them. Both formats are drawn from the same list of shapes,
+//! measured in points from the top left of a page. PDFs use the standard
+//! Helvetica fonts, which only cover Latin text, so sheet labels are English.
+//! An SVG holds every page of the sheet, one below the other.
+
+use crate::constraints::{Cell, Decoration, Edge, GlobalRule, Kropki, OutsideClue, Rules};
+use crate::game_logic::{Difficulty, Grid, SudokuGame};
+
+const PAGE_WIDTH: f32 = 595.0;
+const PAGE_HEIGHT: f32 = 842.0;
+const MARGIN: f32 = 40.0;
+// Room above each board for its labels, and below the last row for the page number
+const LABEL_HEIGHT: f32 = 32.0;
+const FOOTER_HEIGHT: f32 = 20.0;
+const HEADING_HEIGHT: f32 = 30.0;
+const SLOT_GAP: f32 = 16.0;
+// Gap between the pages of an SVG
+const SVG_PAGE_GAP: f32 = 24.0;
+const ANSWERS_PER_PAGE: usize = 12;
+
+const BLACK: Color = (0, 0, 0);
+const GREY: Color = (120, 120, 120);
+const LIGHT_GREY: Color = (200, 200, 200);
+const SHADE: Color = (228, 228, 228);
+const WHITE: Color = (255, 255, 255);
+// Numbers the player entered, as opposed to givens
+const ENTRY_BLUE: Color = (33, 90, 180);
+
+type Point = (f32, f32);
+type Color = (u8, u8, u8);
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum PrintFormat {
+    Svg,
+    Pdf,
+}
+
+impl PrintFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            PrintFormat::Svg => "svg",
+            PrintFormat::Pdf => "pdf",
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct PrintOptions {
+    // One of `PER_PAGE`
+    pub per_page: usize,
+    pub difficulty_labels: bool,
+    pub seed_labels: bool,
+    pub answer_key: bool,
+}
+
+impl PrintOptions {
+    pub const PER_PAGE: [usize; 4] = [1, 2, 4, 6];
+}
+
+impl Default for PrintOptions {
+    fn default() -> Self {
+        Self {
+            per_page: 4,
+            difficulty_labels: true,
+            seed_labels: true,
+            answer_key: true,
+        }
+    }
+}
+
+/// A puzzle to print
+#[derive(Clone, PartialEq)]
+pub struct PrintedPuzzle {
+    pub rules: Rules,
+    pub givens: Grid,
+    // The numbers to print: the givens, or the givens with the player's progress
+    pub grid: Grid,
+    pub solution: Option<Grid>,
+    pub difficulty: Option<Difficulty>,
+    pub seed: Option<u64>,
+}
+
+impl PrintedPuzzle {
+    pub fn from_game(game: &SudokuGame, with_progress: bool) -> Self {
+        let givens = game.puzzle_grid();
+        let generation = game.generation.as_ref();
+        Self {
+            rules: game.rules.clone(),
+            grid: if with_progress { game.grid.clone() } else { givens.clone() },
+            solution: game.solution(),
+            givens,
+            difficulty: generation.map(|generation| generation.difficulty.clone()),
+            seed: generation.and_then(|generation| generation.seed),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Anchor {
+    Start,
+    Middle,
+}
+
+enum Shape {
+    Line {
+        from: Point,
+        to: Point,
+        width: f32,
+        color: Color,
+        dashed: bool,
+    },
+    // Drawn with round joins and caps
+    Polyline {
+        points: Vec<Point>,
+        width: f32,
+        color: Color,
+    },
+    Rect {
+        corner: Point,
+        size: Point,
+        fill: Color,
+    },
+    Circle {
+        center: Point,
+        radius: f32,
+        fill: Option<Color>,
+        stroke: Option<Color>,
+    },
+    // `at` is on the baseline
+    Text {
+        at: Point,
+        size: f32,
+        text: String,
+        bold: bool,
+        color: Color,
+        anchor: Anchor,
+    },
+}
+
+pub fn render(puzzles: &[PrintedPuzzle], options: &PrintOptions, format: PrintFormat) -> Vec<u8> {
+    let pages = layout(puzzles, options);
+    match format {
+        PrintFormat::Svg => svg(&pages).into_bytes(),
+        PrintFormat::Pdf => pdf(&pages).into_bytes(),
+    }
+}
+
+fn layout(puzzles: &[PrintedPuzzle], options: &PrintOptions) -> Vec<Vec<Shape>> {
+    let mut pages = Vec::new();
+    for (page_index, chunk) in puzzles.chunks(options.per_page.max(1)).enumerate() {
+        let mut page = Vec::new();
+        for (slot, puzzle) in chunk.iter().enumerate() {
+            let number = page_index * options.per_page + slot + 1;
+            let (corner, extent) = slot_board(options.per_page, slot, 0.0);
+            draw_labels(&mut page, puzzle, number, options, corner, extent);
+            draw_board(&mut page, puzzle, &puzzle.grid, corner, extent, false);
+        }
+        pages.push(page);
+    }
+
+    if options.answer_key {
+        let solved: Vec<(usize, &PrintedPuzzle, &Grid)> = puzzles
+            .iter()
+            .enumerate()
+            .filter_map(|(index, puzzle)| Some((index + 1, puzzle, puzzle.solution.as_ref()?)))
+            .collect();
+        for chunk in solved.chunks(ANSWERS_PER_PAGE) {
+            let mut page = vec![Shape::Text {
+                at: (MARGIN, MARGIN + 18.0),
+                size: 18.0,
+                text: "Answer key".to_string(),
+                bold: true,
+                color: BLACK,
+                anchor: Anchor::Start,
+            }];
+            for (slot, &(number, puzzle, solution)) in chunk.iter().enumerate() {
+                let (corner, extent) = slot_board(ANSWERS_PER_PAGE, slot, HEADING_HEIGHT);
+                page.push(label(
+                    (corner.0, corner.1 - 6.0),
+                    10.0,
+                    format!("#{number}"),
+                    true,
+                ));
+                draw_board(&mut page, puzzle, solution, corner, extent, true);
+            }
+            pages.push(page);
+        }
+    }
+
+    let page_count = pages.len();
+    for (index, page) in pages.iter_mut().enumerate() {
+        page.push(Shape::Text {
+            at: (PAGE_WIDTH / 2.0, PAGE_HEIGHT - MARGIN + 10.0),
+            size: 9.0,
+            text: format!("pigsudoku - page {} of {}", index + 1, page_count),
+            bold: false,
+            color: GREY,
+            anchor: Anchor::Middle,
+        });
+    }
+    pages
+}
+
+// How slots are arranged on a page, as (columns, rows)
+fn slot_grid(per_page: usize) -> (usize, usize) {
+    match per_page {
+        1 => (1, 1),
+        2 => (1, 2),
+        4 => (2, 2),
+        6 => (2, 3),
+        _ => (3, 4),
+    }
+}
+
+// The top left corner and side of the square board in `slot`, below its labels
+fn slot_board(per_page: usize, slot: usize, heading: f32) -> (Point, f32) {
+    let (columns, rows) = slot_grid(per_page);
+    let width = (PAGE_WIDTH - 2.0 * MARGIN) / columns as f32;
+    let height = (PAGE_HEIGHT - 2.0 * MARGIN - heading - FOOTER_HEIGHT) / rows as f32;
+    let extent = (width - SLOT_GAP).min(height - LABEL_HEIGHT - SLOT_GAP);
+    let (column, row) = (slot % columns, slot / columns);
+    let left = MARGIN + column as f32 * width + (width - extent) / 2.0;
+    let top = MARGIN + heading + row as f32 * height + LABEL_HEIGHT;
+    ((left, top), extent)
+}
+
+fn label(at: Point, size: f32, text: String, bold: bool) -> Shape {
+    Shape::Text {
+        at,
+        size,
+        text,
+        bold,
+        color: BLACK,
+        anchor: Anchor::Start,
+    }
+}
+
+// The puzzle number with its difficulty and seed, and the global rules, which
+// the board can't show
+fn draw_labels(
+    page: &mut Vec<Shape>,
+    puzzle: &PrintedPuzzle,
+    number: usize,
+    options: &PrintOptions,
+    corner: Point,
+    extent: f32,
+) {
+    let mut title = format!("#{number}");
+    if options.difficulty_labels
+        && let Some(difficulty) = &puzzle.difficulty
+    {
+        title.push_str(&format!("   {}", difficulty_name(difficulty)));
+    }
+    if options.seed_labels
+        && let Some(seed) = puzzle.seed
+    {
+        // Decimal, as the leaderboard shows it and the seed field takes it
+        title.push_str(&format!("   Seed {seed}"));
+    }
+    let text_size = (extent / 20.0).clamp(9.0, 13.0);
+    page.push(label((corner.0, corner.1 - 18.0), text_size, title, true));
+
+    let rules: Vec<&str> = puzzle
+        .rules
+        .decorations()
+        .into_iter()
+        .filter_map(|decoration| match decoration {
+            Decoration::GlobalRule(rule) => Some(global_rule_name(rule)),
+            _ => None,
+        })
+        .collect();
+    if !rules.is_empty() {
+        page.push(label((corner.0, corner.1 - 5.0), text_size - 2.0, rules.join(", "), false));
+    }
+}
+
+fn difficulty_name(difficulty: &Difficulty) -> &'static str {
+    match difficulty {
+        Difficulty::VeryEasy => "Very Easy",
+        Difficulty::Easy => "Easy",
+        Difficulty::Medium => "Medium",
+        Difficulty::Hard => "Hard",
+    }
+}
+
+fn global_rule_name(rule: GlobalRule) -> &'static str {
+    match rule {
+        GlobalRule::AntiKnight => "Anti-Knight",
+        GlobalRule::AntiKing => "Anti-King",
+        GlobalRule::NonConsecutive => "Non-Consecutive",
+    }
+}
+
+// Draws the board with its decorations and `numbers` into the square at
+// `corner`. Answers print every number that isn't a given in grey.
+fn draw_board(page: &mut Vec<Shape>, puzzle: &PrintedPuzzle, numbers: &Grid, corner: Point, extent: f32, answer: bool) {
+    let size = puzzle.rules.size();
+    let side = size.side();
+    let decorations = puzzle.rules.decorations();
+    let has_clues = decorations
+        .iter()
+        .any(|decoration| matches!(decoration, Decoration::Sandwich(_) | Decoration::XSum(_)));
+    let ring = if has_clues { 1.0 } else { 0.0 };
+    let cell = extent / (side as f32 + 2.0 * ring);
+    let left = corner.0 + ring * cell;
+    let top = corner.1 + ring * cell;
+    let center = |(row, col): Cell| (left + (col as f32 + 0.5) * cell, top + (row as f32 + 0.5) * cell);
+
+    let mut regions = vec![vec![Some(0); side]; side];
+    for decoration in &decorations {
+        match decoration {
+            Decoration::Regions(map) => regions = map.clone(),
+            Decoration::ShadedCells(cells) => {
+                for &(row, col) in cells {
+                    page.push(Shape::Rect {
+                        corner: (left + col as f32 * cell, top + row as f32 * cell),
+                        size: (cell, cell),
+                        fill: SHADE,
+                    });
+                }
+            }
+            Decoration::Thermometer(cells) => {
+                page.push(Shape::Polyline {
+                    points: cells.iter().map(|&cell| center(cell)).collect(),
+                    width: cell * 0.25,
+                    color: LIGHT_GREY,
+                });
+                page.push(Shape::Circle {
+                    center: center(cells[0]),
+                    radius: cell * 0.35,
+                    fill: Some(LIGHT_GREY),
+                    stroke: None,
+                });
+            }
+            Decoration::Arrow(cells) => draw_arrow(page, cells.iter().map(|&cell| center(cell)).collect(), cell),
+            _ => {}
+        }
+    }
+
+    // Thin lines between cells, then thick ones between regions and around
+    // the board. Each cell draws its top and left edges, and its bottom and
+    // right ones where no cell in play follows.
+    let in_play = |row: usize, col: usize| row < side && col < side && regions[row][col].is_some();
+    let mut thick = Vec::new();
+    for (row, col) in size.cells() {
+        let (x, y) = (left + col as f32 * cell, top + row as f32 * cell);
+        let region = regions[row][col];
+        let edges = [
+            ((x, y), (x + cell, y), row.checked_sub(1).map(|above| (above, col))),
+            ((x, y), (x, y + cell), col.checked_sub(1).map(|before| (row, before))),
+            ((x, y + cell), (x + cell, y + cell), Some((row + 1, col))),
+            ((x + cell, y), (x + cell, y + cell), Some((row, col + 1))),
+        ];
+        for (index, (from, to, neighbor)) in edges.into_iter().enumerate() {
+            let neighbor = neighbor.filter(|&(r, c)| in_play(r, c));
+            if index >= 2 && neighbor.is_some() {
+                continue;
+            }
+            if neighbor.is_some_and(|(r, c)| regions[r][c] == region) {
+                page.push(Shape::Line {
+                    from,
+                    to,
+                    width: 0.5,
+                    color: GREY,
+                    dashed: false,
+                });
+            } else {
+                thick.push((from, to));
+            }
+        }
+    }
+
+    for decoration in &decorations {
+        if let Decoration::Cage(cage) = decoration {
+            draw_cage(page, &cage.cells, cage.sum, (left, top), cell);
+        }
+    }
+    for (from, to) in thick {
+        page.push(Shape::Line {
+            from,
+            to,
+            width: (cell * 0.07).clamp(1.5, 3.0),
+            color: BLACK,
+            dashed: false,
+        });
+    }
+
+    for decoration in &decorations {
+        match decoration {
+            Decoration::KropkiDot(first, second, kind) => {
+                let (a, b) = (center(*first), center(*second));
+                page.push(Shape::Circle {
+                    center: ((a.0 + b.0) / 2.0, (a.1 + b.1) / 2.0),
+                    radius: cell * 0.12,
+                    fill: Some(if *kind == Kropki::Black { BLACK } else { WHITE }),
+                    stroke: Some(BLACK),
+                });
+            }
+            Decoration::Sandwich(clue) | Decoration::XSum(clue) => {
+                let (x, y) = clue_center(clue, side, (left, top), cell);
+                page.push(Shape::Text {
+                    at: (x, y + cell * 0.18),
+                    size: cell * 0.5,
+                    text: clue.sum.to_string(),
+                    bold: false,
+                    color: BLACK,
+                    anchor: Anchor::Middle,
+                });
+            }
+            _ => {}
+        }
+    }
+
+    for (row, col) in size.cells() {
+        let Some(num) = numbers[row][col] else {
+            continue;
+        };
+        let given = puzzle.givens[row][col].is_some();
+        let (x, y) = center((row, col));
+        page.push(Shape::Text {
+            at: (x, y + cell * 0.22),
+            size: cell * 0.6,
+            text: size.digit_label(num),
+            bold: given,
+            color: match (given, answer) {
+                (true, _) => BLACK,
+                (false, true) => GREY,
+                (false, false) => ENTRY_BLUE,
+            },
+            anchor: Anchor::Middle,
+        });
+    }
+}
+
+// A circle in the first cell and a line from its edge with a head at the end
+fn draw_arrow(page: &mut Vec<Shape>, mut points: Vec<Point>, cell: f32) {
+    let radius = cell * 0.4;
+    page.push(Shape::Circle {
+        center: points[0],
+        radius,
+        fill: None,
+        stroke: Some(GREY),
+    });
+    let (start, next) = (points[0], points[1]);
+    let length = ((next.0 - start.0).powi(2) + (next.1 - start.1).powi(2)).sqrt();
+    points[0] = (
+        start.0 + (next.0 - start.0) * radius / length,
+        start.1 + (next.1 - start.1) * radius / length,
+    );
+
+    let (tip, before) = (points[points.len() - 1], points[points.len() - 2]);
+    let angle = (tip.1 - before.1).atan2(tip.0 - before.0);
+    let head = cell * 0.25;
+    for side in [-0.5f32, 0.5] {
+        let back = angle + std::f32::consts::PI + side;
+        page.push(Shape::Line {
+            from: tip,
+            to: (tip.0 + head * back.cos(), tip.1 + head * back.sin()),
+            width: 1.2,
+            color: GREY,
+            dashed: false,
+        });
+    }
+    page.push(Shape::Polyline {
+        points,
+        width: 1.2,
+        color: GREY,
+    });
+}
+
+// A dashed outline just inside the cage's outer edges, with the sum in the
+// top left of its first cell
+fn draw_cage(page: &mut Vec<Shape>, cells: &[Cell], sum: u32, (left, top): Point, cell: f32) {
+    let inset = cell * 0.08;
+    for &(row, col) in cells {
+        let contains = |r: Option<usize>, c: Option<usize>| {
+            r.zip(c).is_some_and(|cage_cell| cells.contains(&cage_cell))
+        };
+        let x0 = left + col as f32 * cell + inset;
+        let y0 = top + row as f32 * cell + inset;
+        let (x1, y1) = (x0 + cell - 2.0 * inset, y0 + cell - 2.0 * inset);
+        let edges = [
+            (contains(row.checked_sub(1), Some(col)), (x0, y0), (x1, y0)),
+            (contains(Some(row + 1), Some(col)), (x0, y1), (x1, y1)),
+            (contains(Some(row), col.checked_sub(1)), (x0, y0), (x0, y1)),
+            (contains(Some(row), Some(col + 1)), (x1, y0), (x1, y1)),
+        ];
+        for (shared, from, to) in edges {
+            if !shared {
+                page.push(Shape::Line {
+                    from,
+                    to,
+                    width: 0.8,
+                    color: BLACK,
+                    dashed: true,
+                });
+            }
+        }
+    }
+
+    let &(row, col) = cells.iter().min().unwrap_or(&(0, 0));
+    page.push(Shape::Text {
+        at: (left + col as f32 * cell + inset * 1.5, top + row as f32 * cell + inset + cell * 0.22),
+        size: cell * 0.22,
+        text: sum.to_string(),
+        bold: false,
+        color: BLACK,
+        anchor: Anchor::Start,
+    });
+}
+
+fn clue_center(clue: &OutsideClue, side: usize, (left, top): Point, cell: f32) -> Point {
+    let index = clue.index as f32 + 0.5;
+    match clue.edge {
+        Edge::Top => (left + index * cell, top - cell / 2.0),
+        Edge::Bottom => (left + index * cell, top + (side as f32 + 0.5) * cell),
+        Edge::Left => (left - cell / 2.0, top + index * cell),
+        Edge::Right => (left + (side as f32 + 0.5) * cell, top + index * cell),
+    }
+}
+
+fn svg(pages: &[Vec<Shape>]) -> String {
+    let height = pages.len() as f32 * (PAGE_HEIGHT + SVG_PAGE_GAP) - SVG_PAGE_GAP;
+    let mut out = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{PAGE_WIDTH}pt\" height=\"{height}pt\" \
+         viewBox=\"0 0 {PAGE_WIDTH} {height}\" font-family=\"Helvetica, Arial, sans-serif\">\n"
+    );
+    for (index, page) in pages.iter().enumerate() {
+        let offset = index as f32 * (PAGE_HEIGHT + SVG_PAGE_GAP);
+        out.push_str(&format!(
+            "<g transform=\"translate(0 {offset})\">\n\
+             <rect width=\"{PAGE_WIDTH}\" height=\"{PAGE_HEIGHT}\" fill=\"white\" stroke=\"#ccc\"/>\n"
+        ));
+        for shape in page {
+            out.push_str(&svg_shape(shape));
+            out.push('\n');
+        }
+        out.push_str("</g>\n");
+    }
+    out.push_str("</svg>\n");
+    out
+}
+
+fn svg_color((r, g, b): Color) -> String {
+    format!("rgb({r},{g},{b})")
+}
+
+fn svg_shape(shape: &Shape) -> String {
+    match shape {
+        Shape::Line {
+            from,
+            to,
+            width,
+            color,
+            dashed,
+        } => format!(
+            "<line x1=\"{:.2}\" y1=\"{:.2}\" x2=\"{:.2}\" y2=\"{:.2}\" stroke=\"{}\" stroke-width=\"{width:.2}\"{}/>",
+            from.0,
+            from.1,
+            to.0,
+            to.1,
+            svg_color(*color),
+            if *dashed { " stroke-dasharray=\"3 2\"" } else { "" },
+        ),
+        Shape::Polyline { points, width, color } => format!(
+            "<polyline points=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"{width:.2}\" \
+             stroke-linecap=\"round\" stroke-linejoin=\"round\"/>",
+            points
+                .iter()
+                .map(|(x, y)| format!("{x:.2},{y:.2}"))
+                .collect::<Vec<_>>()
+                .join(" "),
+            svg_color(*color),
+        ),
+        Shape::Rect { corner, size, fill } => format!(
+            "<rect x=\"{:.2}\" y=\"{:.2}\" width=\"{:.2}\" height=\"{:.2}\" fill=\"{}\"/>",
+            corner.0,
+            corner.1,
+            size.0,
+            size.1,
+            svg_color(*fill),
+        ),
+        Shape::Circle {
+            center,
+            radius,
+            fill,
+            stroke,
+        } => format!(
+            "<circle cx=\"{:.2}\" cy=\"{:.2}\" r=\"{radius:.2}\" fill=\"{}\" stroke=\"{}\"/>",
+            center.0,
+            center.1,
+            fill.map_or("none".to_string(), svg_color),
+            stroke.map_or("none".to_string(), svg_color),
+        ),
+        Shape::Text {
+            at,
+            size,
+            text,
+            bold,
+            color,
+            anchor,
+        } => format!(
+            "<text x=\"{:.2}\" y=\"{:.2}\" font-size=\"{size:.2}\" fill=\"{}\"{}{}>{}</text>",
+            at.0,
+            at.1,
+            svg_color(*color),
+            if *bold { " font-weight=\"bold\"" } else { "" },
+            if *anchor == Anchor::Middle { " text-anchor=\"middle\"" } else { "" },
+            text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;"),
+        ),
+    }
+}
+
+// A PDF with one content stream per page. Objects 1 to 4 are the catalog,
+// the page tree and the two fonts; each page adds its page object and content.
+fn pdf(pages: &[Vec<Shape>]) -> String {
+    let kids: Vec<String> = (0..pages.len()).map(|index| format!("{} 0 R", 5 + 2 * index)).collect();
+    let mut objects = vec![
+        "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+        format!("<< /Type /Pages /Kids [{}] /Count {} >>", kids.join(" "), pages.len()),
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>".to_string(),
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica-Bold /Encoding /WinAnsiEncoding >>".to_string(),
+    ];
+    for (index, page) in pages.iter().enumerate() {
+        let content: String = page.iter().map(pdf_shape).collect();
+        objects.push(format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {PAGE_WIDTH} {PAGE_HEIGHT}] \
+             /Resources << /Font << /F1 3 0 R /F2 4 0 R >> >> /Contents {} 0 R >>",
+            6 + 2 * index
+        ));
+        objects.push(format!("<< /Length {} >>\nstream\n{content}endstream", content.len()));
+    }
+
+    let mut out = String::from("%PDF-1.4\n");
+    let mut offsets = Vec::new();
+    for (index, object) in objects.iter().enumerate() {
+        offsets.push(out.len());
+        out.push_str(&format!("{} 0 obj\n{object}\nendobj\n", index + 1));
+    }
+    let xref = out.len();
+    out.push_str(&format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1));
+    for offset in offsets {
+        out.push_str(&format!("{offset:010} 00000 n \n"));
+    }
+    out.push_str(&format!(
+        "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{xref}\n%%EOF\n",
+        objects.len() + 1
+    ));
+    out
+}
+
+fn pdf_color((r, g, b): Color) -> String {
+    format!("{:.3} {:.3} {:.3}", r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0)
+}
+
+// PDF coordinates start at the bottom left of the page
+fn pdf_point((x, y): Point) -> String {
+    format!("{x:.2} {:.2}", PAGE_HEIGHT - y)
+}
+
+fn pdf_shape(shape: &Shape) -> String {
+    match shape {
+        Shape::Line {
+            from,
+            to,
+            width,
+            color,
+            dashed,
+        } => format!(
+            "{width:.2} w {} RG {} {} m {} l S{}\n",
+            pdf_color(*color),
+            if *dashed { "[3 2] 0 d" } else { "" },
+            pdf_point(*from),
+            pdf_point(*to),
+            if *dashed { " [] 0 d" } else { "" },
+        ),
+        Shape::Polyline { points, width, color } => {
+            let mut path = format!("{width:.2} w {} RG 1 J 1 j {} m", pdf_color(*color), pdf_point(points[0]));
+            for &point in &points[1..] {
+                path.push_str(&format!(" {} l", pdf_point(point)));
+            }
+            path.push_str(" S 0 J 0 j\n");
+            path
+        }
+        Shape::Rect { corner, size, fill } => format!(
+            "{} rg {:.2} {:.2} {:.2} {:.2} re f\n",
+            pdf_color(*fill),
+            corner.0,
+            PAGE_HEIGHT - corner.1 - size.1,
+            size.0,
+            size.1,
+        ),
+        Shape::Circle {
+            center,
+            radius,
+            fill,
+            stroke,
+        } => {
+            // Four Bézier quarter arcs
+            let (x, y) = (center.0, PAGE_HEIGHT - center.1);
+            let (r, k) = (*radius, radius * 0.5523);
+            let mut path = String::new();
+            if let Some(fill) = fill {
+                path.push_str(&format!("{} rg ", pdf_color(*fill)));
+            }
+            if let Some(stroke) = stroke {
+                path.push_str(&format!("1 w {} RG ", pdf_color(*stroke)));
+            }
+            path.push_str(&format!(
+                "{:.2} {y:.2} m {:.2} {:.2} {:.2} {:.2} {x:.2} {:.2} c \
+                 {:.2} {:.2} {:.2} {:.2} {:.2} {y:.2} c \
+                 {:.2} {:.2} {:.2} {:.2} {x:.2} {:.2} c \
+                 {:.2} {:.2} {:.2} {:.2} {:.2} {y:.2} c ",
+                x + r,
+                x + r, y + k, x + k, y + r, y + r,
+                x - k, y + r, x - r, y + k, x - r,
+                x - r, y - k, x - k, y - r, y - r,
+                x + k, y - r, x + r, y - k, x + r,
+            ));
+            path.push_str(match (fill, stroke) {
+                (Some(_), Some(_)) => "b\n",
+                (Some(_), None) => "f\n",
+                _ => "s\n",
+            });
+            path
+        }
+        Shape::Text {
+            at,
+            size,
+            text,
+            bold,
+            color,
+            anchor,
+        } => {
+            // Helvetica digits are 0.556 em wide, which is close enough for
+            // centering other text too
+            let x = match anchor {
+                Anchor::Start => at.0,
+                Anchor::Middle => at.0 - text.chars().count() as f32 * size * 0.556 / 2.0,
+            };
+            let text: String = text
+                .chars()
+                .map(|ch| if ch.is_ascii() { ch } else { '?' })
+                .collect::<String>()
+                .replace('\\', "\\\\")
+                .replace('(', "\\(")
+                .replace(')', "\\)");
+            format!(
+                "BT /{} {size:.2} Tf {} rg {} Td ({text}) Tj ET\n",
+                if *bold { "F2" } else { "F1" },
+                pdf_color(*color),
+                pdf_point((x, at.1)),
+            )
+        }
+    }
+}
+
+/// Where an exported sheet ended up. Desktop builds save files, web builds
+/// offer a download.
+#[derive(Clone, PartialEq, Debug)]
+pub enum SavedSheet {
+    // Saved to this path on disk
+    #[cfg_attr(target_arch = "wasm32", allow(dead_code))]
+    File(String),
+    // A link the player follows to download the sheet
+    #[cfg_attr(not(target_arch = "wasm32"), allow(dead_code))]
+    Download { href: String, file_name: String },
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn save_sheet(bytes: &[u8], format: PrintFormat) -> Option<SavedSheet> {
+    let seconds = crate::game_logic::unix_seconds();
+    let dir = dirs::download_dir()
+        .or_else(dirs::document_dir)
+        .or_else(dirs::home_dir)?;
+    let path = dir.join(format!("pigsudoku-{seconds}.{}", format.extension()));
+    std::fs::write(&path, bytes).ok()?;
+    Some(SavedSheet::File(path.display().to_string()))
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn save_sheet(bytes: &[u8], format: PrintFormat) -> Option<SavedSheet> {
+    use base64::Engine;
+
+    let mime_type = match format {
+        PrintFormat::Svg => "image/svg+xml",
+        PrintFormat::Pdf => "application/pdf",
+    };
+    let data = base64::engine::general_purpose::STANDARD.encode(bytes);
+    Some(SavedSheet::Download {
+        href: format!("data:{mime_type};base64,{data}"),
+        file_name: format!("pigsudoku.{}", format.extension()),
+    })
+}
@@ -0,0 +1,255 @@
+//! Player profiles kept on this device
+//!
+//! Several people can share one machine, so results are recorded under the
+//! profile that is currently selected rather than the operating system user.
+//! Each profile has its own avatar, preferences and statistics.
+
+use crate::game_logic::Difficulty;
+use crate::storage;
+use crate::theme::Theme;
+
+const PROFILES_KEY: &str = "profiles";
+const DEFAULT_NAME: &str = "Player";
+
+pub const AVATARS: [&str; 12] = ["🐷", "🐱", "🐶", "🦊", "🐼", "🐸", "🐵", "🦉", "🐯", "🐰", "🐨", "🐧"];
+pub const LANGUAGES: [&str; 2] = ["en-US", "zh-CN"];
+
+/// What the board highlights besides the cells with the selected number
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Highlighting {
+    // The row, column, box and other houses of the selected cell
+    pub peers: bool,
+    // Empty cells with the number in their notes
+    pub notes: bool,
+    // Empty cells where the number can still go
+    pub candidates: bool,
+}
+
+impl Default for Highlighting {
+    fn default() -> Self {
+        Self {
+            peers: true,
+            notes: true,
+            candidates: false,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Preferences {
+    // One of `LANGUAGES`
+    pub language: String,
+    // Used for the puzzle the app opens with
+    pub difficulty: Difficulty,
+    pub theme: Theme,
+    // Patterns and markers besides colors on the board
+    pub colorblind: bool,
+    pub highlighting: Highlighting,
+}
+
+impl Default for Preferences {
+    fn default() -> Self {
+        Self {
+            language: LANGUAGES[0].to_string(),
+            difficulty: Difficulty::Easy,
+            theme: Theme::System,
+            colorblind: false,
+            highlighting: Highlighting::default(),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Profile {
+    // Stays the same when the profile is renamed
+    pub id: u32,
+    pub name: String,
+    pub avatar: String,
+    pub preferences: Preferences,
+}
+
+impl Profile {
+    fn new(id: u32, name: String) -> Self {
+        Self {
+            id,
+            name,
+            avatar: AVATARS[id as usize % AVATARS.len()].to_string(),
+            preferences: Preferences::default(),
+        }
+    }
+
+    /// The avatar followed by the name
+    pub fn label(&self) -> String {
+        format!("{} {}", self.avatar, self.name)
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Profiles {
+    pub list: Vec<Profile>,
+    current: usize,
+}
+
+impl Profiles {
+    // The current profile's index on the first line, then one profile per line
+    pub fn load() -> Self {
+        let data = storage::load(PROFILES_KEY).unwrap_or_default();
+        let mut lines = data.lines();
+        let current = lines.next().and_then(|line| line.trim().parse().ok()).unwrap_or(0);
+        let list: Vec<Profile> = lines
+            .filter(|line| !line.trim().is_empty())
+            .enumerate()
+            .map(|(index, line)| parse_profile(line, index as u32))
+            .collect();
+        if list.is_empty() {
+            return Self {
+                list: vec![Profile::new(0, DEFAULT_NAME.to_string())],
+                current: 0,
+            };
+        }
+        Self {
+            current: current.min(list.len() - 1),
+            list,
+        }
+    }
+
+    pub fn current(&self) -> &Profile {
+        &self.list[self.current]
+    }
+
+    pub fn current_index(&self) -> usize {
+        self.current
+    }
+
+    /// The profile with this name, if any
+    pub fn find(&self, name: &str) -> Option<&Profile> {
+        self.list.iter().find(|profile| profile.name == name)
+    }
+
+    pub fn select(&mut self, index: usize) {
+        if index < self.list.len() {
+            self.current = index;
+            self.save();
+        }
+    }
+
+    /// Adds a profile and switches to it. Returns `false` for a blank or
+    /// taken name.
+    pub fn add(&mut self, name: &str) -> bool {
+        let name = clean_name(name);
+        if !self.is_available(&name) {
+            return false;
+        }
+        let id = self.list.iter().map(|profile| profile.id + 1).max().unwrap_or(0);
+        self.list.push(Profile::new(id, name));
+        self.current = self.list.len() - 1;
+        self.save();
+        true
+    }
+
+    /// Renames the current profile. Returns `false` for a blank or taken name.
+    pub fn rename(&mut self, name: &str) -> bool {
+        let name = clean_name(name);
+        if name == self.current().name {
+            return true;
+        }
+        if !self.is_available(&name) {
+            return false;
+        }
+        self.list[self.current].name = name;
+        self.save();
+        true
+    }
+
+    pub fn set_avatar(&mut self, avatar: &str) {
+        self.list[self.current].avatar = avatar.to_string();
+        self.save();
+    }
+
+    pub fn set_preferences(&mut self, preferences: Preferences) {
+        self.list[self.current].preferences = preferences;
+        self.save();
+    }
+
+    /// Removes the current profile and returns it. The last profile is kept.
+    pub fn remove_current(&mut self) -> Option<Profile> {
+        if self.list.len() < 2 {
+            return None;
+        }
+        let removed = self.list.remove(self.current);
+        self.current = self.current.min(self.list.len() - 1);
+        self.save();
+        Some(removed)
+    }
+
+    fn is_available(&self, name: &str) -> bool {
+        !name.is_empty() && self.find(name).is_none()
+    }
+
+    // Tab-separated id, name, avatar, language, difficulty, theme,
+    // colorblind mode and the three highlighting switches
+    fn save(&self) {
+        let mut data = self.current.to_string();
+        for profile in &self.list {
+            let difficulty = Difficulty::all()
+                .iter()
+                .position(|tier| *tier == profile.preferences.difficulty)
+                .unwrap_or(0);
+            let highlighting = profile.preferences.highlighting;
+            data.push('\n');
+            data.push_str(
+                &[
+                    profile.id.to_string(),
+                    profile.name.clone(),
+                    profile.avatar.clone(),
+                    profile.preferences.language.clone(),
+                    difficulty.to_string(),
+                    profile.preferences.theme.key(),
+                    (profile.preferences.colorblind as u8).to_string(),
+                    (highlighting.peers as u8).to_string(),
+                    (highlighting.notes as u8).to_string(),
+                    (highlighting.candidates as u8).to_string(),
+                ]
+                .join("\t"),
+            );
+        }
+        storage::save(PROFILES_KEY, &data);
+    }
+}
+
+// Earlier versions stored only the name, so missing fields get defaults
+fn parse_profile(line: &str, index: u32) -> Profile {
+    let fields: Vec<&str> = line.split('\t').map(str::trim).collect();
+    let &[id, name, avatar, language, difficulty, ref rest @ ..] = fields.as_slice() else {
+        return Profile::new(index, clean_name(line));
+    };
+    let mut profile = Profile::new(id.parse().unwrap_or(index), name.to_string());
+    if !avatar.is_empty() {
+        profile.avatar = avatar.to_string();
+    }
+    if LANGUAGES.contains(&language) {
+        profile.preferences.language = language.to_string();
+    }
+    if let Some(difficulty) = difficulty
+        .parse::<usize>()
+        .ok()
+        .and_then(|index| Difficulty::all().get(index).cloned())
+    {
+        profile.preferences.difficulty = difficulty;
+    }
+    if let Some(theme) = rest.first() {
+        profile.preferences.theme = Theme::from_key(theme);
+    }
+    profile.preferences.colorblind = rest.get(1) == Some(&"1");
+    let switch = |index: usize, default: bool| rest.get(index).map_or(default, |field| *field == "1");
+    let highlighting = &mut profile.preferences.highlighting;
+    highlighting.peers = switch(2, highlighting.peers);
+    highlighting.notes = switch(3, highlighting.notes);
+    highlighting.candidates = switch(4, highlighting.candidates);
+    profile
+}
+
+// Names are stored one per line and in tab-separated records
+fn clean_name(name: &str) -> String {
+    name.split_whitespace().collect::<Vec<_>>().join(" ")
+}
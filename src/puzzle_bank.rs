@@ -0,0 +1,242 @@
+//! Puzzle bank module serving pre-generated puzzles embedded in the app
+//!
+//! The bank is produced by `cargo run --release --example generate_puzzle_bank`
+//! and packs each puzzle as an 81-bit givens mask followed by one nibble per
+//! given digit, which keeps a puzzle at roughly 30 bytes instead of 81.
+
+use crate::game_logic::{Difficulty, Grid};
+use crate::storage;
+use rand::seq::SliceRandom;
+use std::collections::HashSet;
+use std::sync::OnceLock;
+
+const PUZZLE_BANK: &[u8] = include_bytes!("../assets/puzzle_bank.bin");
+const MAGIC: &[u8; 4] = b"PSB1";
+const MASK_BYTES: usize = 11;
+const PLAYED_KEY: &str = "played_puzzles";
+
+/// Takes a random 9x9 puzzle of the given difficulty that has not been played
+/// yet and records it as played. Returns `None` once the tier is exhausted.
+pub fn take_unplayed_puzzle(difficulty: &Difficulty) -> Option<Grid> {
+    let tier = tier_index(difficulty);
+    let puzzles = bank().get(tier)?;
+    let mut played = load_played();
+
+    let unplayed: Vec<usize> = (0..puzzles.len())
+        .filter(|index| !played[tier].contains(index))
+        .collect();
+    let &index = unplayed.choose(&mut rand::thread_rng())?;
+
+    played[tier].insert(index);
+    save_played(&played);
+    Some(puzzles[index].clone())
+}
+
+#[allow(dead_code)] // Used by the puzzle bank generator example
+pub fn encode_bank(tiers: &[Vec<Grid>]) -> Vec<u8> {
+    let mut bytes = MAGIC.to_vec();
+    bytes.push(tiers.len() as u8);
+
+    for puzzles in tiers {
+        bytes.extend_from_slice(&(puzzles.len() as u16).to_le_bytes());
+        for puzzle in puzzles {
+            encode_puzzle(puzzle, &mut bytes);
+        }
+    }
+
+    bytes
+}
+
+pub fn decode_bank(bytes: &[u8]) -> Option<Vec<Vec<Grid>>> {
+    let rest = bytes.strip_prefix(MAGIC)?;
+    let (&tier_count, mut rest) = rest.split_first()?;
+
+    let mut tiers = Vec::with_capacity(tier_count as usize);
+    for _ in 0..tier_count {
+        let count = u16::from_le_bytes([*rest.first()?, *rest.get(1)?]) as usize;
+        rest = &rest[2..];
+
+        let mut puzzles = Vec::with_capacity(count);
+        for _ in 0..count {
+            let (puzzle, used) = decode_puzzle(rest)?;
+            puzzles.push(puzzle);
+            rest = &rest[used..];
+        }
+        tiers.push(puzzles);
+    }
+
+    Some(tiers)
+}
+
+fn encode_puzzle(puzzle: &Grid, bytes: &mut Vec<u8>) {
+    let mut mask = [0u8; MASK_BYTES];
+    let mut digits = Vec::new();
+
+    for (index, cell) in puzzle.iter().flatten().enumerate() {
+        if let Some(num) = cell {
+            mask[index / 8] |= 1 << (index % 8);
+            digits.push(*num);
+        }
+    }
+
+    bytes.extend_from_slice(&mask);
+    for pair in digits.chunks(2) {
+        let low = pair.get(1).copied().unwrap_or(0);
+        bytes.push((pair[0] << 4) | low);
+    }
+}
+
+fn decode_puzzle(bytes: &[u8]) -> Option<(Grid, usize)> {
+    let mask = bytes.get(..MASK_BYTES)?;
+    let given_count: usize = mask.iter().map(|byte| byte.count_ones() as usize).sum();
+    let digit_bytes = bytes.get(MASK_BYTES..MASK_BYTES + given_count.div_ceil(2))?;
+
+    let mut puzzle = vec![vec![None; 9]; 9];
+    let mut digit_index = 0;
+    for index in 0..81 {
+        if mask[index / 8] & (1 << (index % 8)) != 0 {
+            let byte = digit_bytes[digit_index / 2];
+            let num = if digit_index % 2 == 0 { byte >> 4 } else { byte & 0x0f };
+            if !(1..=9).contains(&num) {
+                return None;
+            }
+            puzzle[index / 9][index % 9] = Some(num);
+            digit_index += 1;
+        }
+    }
+
+    Some((puzzle, MASK_BYTES + digit_bytes.len()))
+}
+
+fn bank() -> &'static [Vec<Grid>] {
+    static BANK: OnceLock<Vec<Vec<Grid>>> = OnceLock::new();
+    BANK.get_or_init(|| decode_bank(PUZZLE_BANK).unwrap_or_default())
+}
+
+fn tier_index(difficulty: &Difficulty) -> usize {
+    Difficulty::all()
+        .iter()
+        .position(|tier| tier == difficulty)
+        .unwrap_or(0)
+}
+
+// The FNV-1a hash of the bank file. Played puzzles are recorded by index, so
+// the records name the bank they index into.
+fn bank_hash() -> u64 {
+    PUZZLE_BANK.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+// Played puzzles are stored as a `bank <hash>` line, then one line of
+// comma-separated indices per tier. Records of another bank, such as one
+// regenerated since, are dropped rather than marking unrelated puzzles played.
+fn load_played() -> Vec<HashSet<usize>> {
+    let mut played = vec![HashSet::new(); Difficulty::all().len()];
+
+    if let Some(data) = storage::load(PLAYED_KEY) {
+        let mut lines = data.lines();
+        if lines.next() != Some(&format!("bank {:016x}", bank_hash())) {
+            return played;
+        }
+        for (tier, line) in lines.enumerate().take(played.len()) {
+            played[tier] = line
+                .split(',')
+                .filter_map(|index| index.trim().parse().ok())
+                .collect();
+        }
+    }
+
+    played
+}
+
+fn save_played(played: &[HashSet<usize>]) {
+    let mut lines = vec![format!("bank {:016x}", bank_hash())];
+    lines.extend(played.iter().map(|indices| {
+        let mut indices: Vec<usize> = indices.iter().copied().collect();
+        indices.sort();
+        indices
+            .iter()
+            .map(|index| index.to_string())
+            .collect::<Vec<_>>()
+            .join(",")
+    }));
+
+    storage::save(PLAYED_KEY, &lines.join("\n"));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constraints::Rules;
+    use crate::game_logic::{BoardSize, SudokuGame};
+
+    fn givens(puzzle: &Grid) -> usize {
+        puzzle.iter().flatten().filter(|cell| cell.is_some()).count()
+    }
+
+    #[test]
+    fn embedded_bank_decodes_into_its_tiers() {
+        let tiers = decode_bank(PUZZLE_BANK).unwrap();
+        assert_eq!(tiers.len(), Difficulty::all().len());
+
+        for (difficulty, puzzles) in Difficulty::all().iter().zip(&tiers) {
+            assert!(!puzzles.is_empty(), "{difficulty:?}");
+            let (_, max_remove) = difficulty.numbers_to_remove(BoardSize::Nine);
+            for puzzle in puzzles {
+                let count = givens(puzzle);
+                assert!(count >= 81 - max_remove, "{difficulty:?} puzzle with {count} givens");
+                assert_eq!(Difficulty::from_given_count(count, BoardSize::Nine), *difficulty);
+            }
+            // Solving every puzzle takes too long for a unit test
+            for puzzle in puzzles.iter().take(5) {
+                let game = SudokuGame::from_puzzle(Rules::classic(BoardSize::Nine), puzzle.clone());
+                assert_eq!(game.solution_count(), 1);
+            }
+        }
+    }
+
+    #[test]
+    fn encodes_and_decodes_puzzles() {
+        let mut full = vec![vec![None; 9]; 9];
+        for (row, cells) in full.iter_mut().enumerate() {
+            for (col, cell) in cells.iter_mut().enumerate() {
+                *cell = Some(((row * 3 + row / 3 + col) % 9 + 1) as u8);
+            }
+        }
+        // An odd number of givens leaves half of the last digit byte unused
+        let mut odd = full.clone();
+        odd[4][4] = None;
+        let empty = vec![vec![None; 9]; 9];
+        let tiers = vec![vec![full, odd], Vec::new(), vec![empty]];
+
+        assert_eq!(decode_bank(&encode_bank(&tiers)), Some(tiers));
+    }
+
+    #[test]
+    fn rejects_malformed_banks() {
+        let mut puzzle = vec![vec![None; 9]; 9];
+        puzzle[0][0] = Some(7);
+        let bytes = encode_bank(&[vec![puzzle]]);
+
+        assert_eq!(decode_bank(b"PSB0\x00"), None);
+        assert_eq!(decode_bank(&bytes[..bytes.len() - 1]), None);
+        let mut zero_digit = bytes.clone();
+        *zero_digit.last_mut().unwrap() = 0x00;
+        assert_eq!(decode_bank(&zero_digit), None);
+    }
+
+    #[test]
+    fn drops_played_records_of_another_bank() {
+        let mut played = vec![HashSet::new(); Difficulty::all().len()];
+        played[1].extend([3, 14]);
+        save_played(&played);
+        assert_eq!(load_played(), played);
+
+        storage::save(PLAYED_KEY, "bank 0000000000000000\n1,2\n3");
+        assert!(load_played().iter().all(HashSet::is_empty));
+        // Records from before the bank was named in them
+        storage::save(PLAYED_KEY, "1,2\n3");
+        assert!(load_played().iter().all(HashSet::is_empty));
+    }
+}
@@ -0,0 +1,230 @@
+//! Puzzle format module for importing puzzles written as text
+//!
+//! A puzzle is one item per line; blank lines and lines starting with `#` are
+//! ignored. The grid lists every cell row by row, using `.` (or `0` on boards
+//! up to 9x9) for empty cells, and its length sets the board size. Variant
+//! rules follow, naming cells as `r<row>c<col>`:
+//!
+//! ```text
+//! grid 4.....8.5.3..........7......2.....6.....8.4......1.......6.3.7.5..2.....1.4......
+//! diagonal
+//! antiknight
+//! cage 15 r1c1 r1c2 r2c1
+//! thermo r3c3 r3c4 r3c5
+//! arrow r5c5 r6c5 r7c5
+//! white r2c2 r2c3
+//! black r8c8 r9c8
+//! ```
+//!
+//! Thermometers start at the bulb and arrows at the circle. The global rules
+//! `antiknight`, `antiking` and `nonconsecutive` apply to the whole board.
+
+use crate::constraints::{
+    Arrow, Cage, Cages, Cell, Constraint, Diagonals, GlobalRule, Kropki, KropkiDot, Rules,
+    Thermometer,
+};
+use crate::game_logic::{BoardSize, Grid};
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum ImportError {
+    MissingGrid,
+    // The grid doesn't have 16, 36, 81 or 256 cells
+    GridLength(usize),
+    Digit(char),
+    Cell(String),
+    UnknownRule(String),
+    // A rule line names too few cells, a malformed cage sum, or a dot
+    // between cells that aren't adjacent
+    RuleCells(String),
+}
+
+pub fn parse_puzzle(text: &str) -> Result<(Rules, Grid), ImportError> {
+    let lines: Vec<&str> = text
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .collect();
+
+    let grid_text = lines
+        .iter()
+        .find_map(|line| line.strip_prefix("grid"))
+        .ok_or(ImportError::MissingGrid)?;
+    let grid_chars: Vec<char> = grid_text.chars().filter(|ch| !ch.is_whitespace()).collect();
+    // Samurai boards can't be imported
+    let size = BoardSize::all()
+        .into_iter()
+        .filter(|size| size.grids().len() == 1)
+        .find(|size| size.cell_count() == grid_chars.len())
+        .ok_or(ImportError::GridLength(grid_chars.len()))?;
+    let grid = parse_grid(size, &grid_chars)?;
+
+    let mut constraints = Rules::classic_constraints(size);
+    let mut cages = Vec::new();
+    for line in lines.iter().filter(|line| !line.starts_with("grid")) {
+        let mut words = line.split_whitespace();
+        let rule = words.next().unwrap_or_default().to_lowercase();
+        let words: Vec<&str> = words.collect();
+
+        let constraint: Box<dyn Constraint> = match rule.as_str() {
+            "diagonal" => Box::new(Diagonals(size)),
+            "antiknight" => Box::new(GlobalRule::AntiKnight),
+            "antiking" => Box::new(GlobalRule::AntiKing),
+            "nonconsecutive" => Box::new(GlobalRule::NonConsecutive),
+            "cage" => {
+                let (sum, cells) = words
+                    .split_first()
+                    .ok_or_else(|| ImportError::RuleCells(line.to_string()))?;
+                let sum = sum
+                    .parse()
+                    .map_err(|_| ImportError::RuleCells(line.to_string()))?;
+                cages.push(Cage {
+                    cells: parse_cells(size, cells, 1, line)?,
+                    sum,
+                });
+                continue;
+            }
+            "thermo" => Box::new(Thermometer(parse_cells(size, &words, 2, line)?)),
+            "arrow" => Box::new(Arrow(parse_cells(size, &words, 2, line)?)),
+            "white" | "black" => {
+                // A dot sits between exactly two adjacent cells
+                if words.len() != 2 {
+                    return Err(ImportError::RuleCells(line.to_string()));
+                }
+                let cells = parse_cells(size, &words, 2, line)?;
+                if !KropkiDot::is_adjacent(cells[0], cells[1]) {
+                    return Err(ImportError::RuleCells(line.to_string()));
+                }
+                let kind = if rule == "white" {
+                    Kropki::White
+                } else {
+                    Kropki::Black
+                };
+                Box::new(KropkiDot(cells[0], cells[1], kind))
+            }
+            _ => return Err(ImportError::UnknownRule(rule)),
+        };
+        constraints.push(constraint);
+    }
+    if !cages.is_empty() {
+        let houses: Vec<Vec<Cell>> = constraints.iter().flat_map(|constraint| constraint.houses()).collect();
+        constraints.push(Box::new(Cages::new(size, cages, &houses)));
+    }
+
+    Ok((Rules::new(size, constraints), grid))
+}
+
+fn parse_grid(size: BoardSize, chars: &[char]) -> Result<Grid, ImportError> {
+    let mut grid = size.empty_grid();
+    for (index, &ch) in chars.iter().enumerate() {
+        let (row, col) = (index / size.side(), index % size.side());
+        grid[row][col] = match size.parse_digit(ch) {
+            Some(num) => Some(num),
+            None if ch == '.' || ch == '0' => None,
+            None => return Err(ImportError::Digit(ch)),
+        };
+    }
+    Ok(grid)
+}
+
+fn parse_cells(
+    size: BoardSize,
+    words: &[&str],
+    min_cells: usize,
+    line: &str,
+) -> Result<Vec<Cell>, ImportError> {
+    if words.len() < min_cells {
+        return Err(ImportError::RuleCells(line.to_string()));
+    }
+    words.iter().map(|word| parse_cell(size, word)).collect()
+}
+
+// Cells are written `r<row>c<col>`, counting from 1
+fn parse_cell(size: BoardSize, word: &str) -> Result<Cell, ImportError> {
+    let lower = word.to_lowercase();
+    let cell = lower
+        .strip_prefix('r')
+        .and_then(|rest| rest.split_once('c'))
+        .and_then(|(row, col)| Some((row.parse::<usize>().ok()?, col.parse::<usize>().ok()?)))
+        .filter(|&(row, col)| (1..=size.side()).contains(&row) && (1..=size.side()).contains(&col));
+
+    match cell {
+        Some((row, col)) => Ok((row - 1, col - 1)),
+        None => Err(ImportError::Cell(word.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constraints::Decoration;
+
+    const GRID: &str = "grid 4.....8.5.3..........7......2.....6.....8.4......1.......6.3.7.5..2.....1.4......";
+
+    fn parse_rule(rule: &str) -> Result<(Rules, Grid), ImportError> {
+        parse_puzzle(&format!("{GRID}\n{rule}"))
+    }
+
+    #[test]
+    fn parses_the_documented_example() {
+        let text = format!(
+            "# A comment\n\n{GRID}\ndiagonal\nantiknight\ncage 15 r1c1 r1c2 r2c1\n\
+             thermo r3c3 r3c4 r3c5\narrow r5c5 r6c5 r7c5\nwhite r2c2 r2c3\nBLACK R8C8 r9c8"
+        );
+        let (rules, grid) = parse_puzzle(&text).unwrap();
+
+        assert_eq!(rules.size(), BoardSize::Nine);
+        assert_eq!(grid[0][0], Some(4));
+        assert_eq!(grid[0][1], None);
+        assert_eq!(grid[0][6], Some(8));
+        let decorations = rules.decorations();
+        for decoration in [
+            Decoration::GlobalRule(GlobalRule::AntiKnight),
+            Decoration::Cage(Cage { cells: vec![(0, 0), (0, 1), (1, 0)], sum: 15 }),
+            Decoration::Thermometer(vec![(2, 2), (2, 3), (2, 4)]),
+            Decoration::Arrow(vec![(4, 4), (5, 4), (6, 4)]),
+            Decoration::KropkiDot((1, 1), (1, 2), Kropki::White),
+            Decoration::KropkiDot((7, 7), (8, 7), Kropki::Black),
+        ] {
+            assert!(decorations.contains(&decoration), "{decoration:?} missing");
+        }
+    }
+
+    #[test]
+    fn reads_small_boards_and_zeros() {
+        let (rules, grid) = parse_puzzle("grid 1 0 . 4 .... .... 0002").unwrap();
+        assert_eq!(rules.size(), BoardSize::Four);
+        assert_eq!(grid[0], [Some(1), None, None, Some(4)]);
+        assert_eq!(grid[3][3], Some(2));
+    }
+
+    #[test]
+    fn reports_grid_errors() {
+        assert_eq!(parse_puzzle("diagonal").unwrap_err(), ImportError::MissingGrid);
+        assert_eq!(parse_puzzle("# grid 1234").unwrap_err(), ImportError::MissingGrid);
+        assert_eq!(parse_puzzle(&GRID[..GRID.len() - 1]).unwrap_err(), ImportError::GridLength(80));
+        assert_eq!(parse_puzzle(&GRID.replacen('8', "x", 1)).unwrap_err(), ImportError::Digit('x'));
+        // 5 is past the numbers of a 4x4 board
+        assert_eq!(parse_puzzle("grid 5...............").unwrap_err(), ImportError::Digit('5'));
+    }
+
+    #[test]
+    fn reports_rule_errors() {
+        assert_eq!(parse_rule("sandwich r1c1").unwrap_err(), ImportError::UnknownRule("sandwich".into()));
+        assert_eq!(parse_rule("thermo r1c1 r10c1").unwrap_err(), ImportError::Cell("r10c1".into()));
+        assert_eq!(parse_rule("arrow r0c1 r1c1").unwrap_err(), ImportError::Cell("r0c1".into()));
+        assert_eq!(parse_rule("white r1c1 1,2").unwrap_err(), ImportError::Cell("1,2".into()));
+
+        for line in [
+            "thermo r1c1",
+            "arrow",
+            "cage",
+            "cage r1c1 r1c2",
+            "cage 15",
+            "white r1c1 r1c3",
+            "black r1c1 r2c2",
+            "black r1c1 r1c2 r1c3",
+        ] {
+            assert_eq!(parse_rule(line).unwrap_err(), ImportError::RuleCells(line.into()));
+        }
+    }
+}
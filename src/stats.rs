@@ -0,0 +1,214 @@
+//! Player statistics kept across sessions
+//!
+//! Only generated puzzles count, since they are the ones with a difficulty.
+//! A game that is left for a new one before it is solved counts as lost and
+//! ends the winning streak of its difficulty. Each player profile has its own
+//! statistics.
+
+use crate::game_logic::{Difficulty, SudokuGame};
+use crate::storage;
+
+const STATS_KEY: &str = "stats";
+
+// Statistics from before profiles existed belong to the first profile
+fn stats_key(profile: u32) -> String {
+    if profile == 0 {
+        STATS_KEY.to_string()
+    } else {
+        format!("{STATS_KEY}-{profile}")
+    }
+}
+
+/// Clears the statistics of a removed profile, whose id may be reused
+pub fn remove(profile: u32) {
+    storage::save(&stats_key(profile), "");
+}
+
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct TierStats {
+    pub started: usize,
+    pub completed: usize,
+    // Over completed games
+    pub total_seconds: u64,
+    pub best_seconds: Option<u64>,
+    pub hints: usize,
+    pub mistakes: usize,
+    pub current_streak: usize,
+    pub best_streak: usize,
+    // Whether the last game started is still unsolved
+    in_progress: bool,
+}
+
+impl TierStats {
+    /// Share of started games that were completed, from 0 to 1
+    pub fn win_rate(&self) -> Option<f64> {
+        (self.started > 0).then(|| self.completed as f64 / self.started as f64)
+    }
+
+    pub fn average_seconds(&self) -> Option<u64> {
+        (self.completed > 0).then(|| self.total_seconds / self.completed as u64)
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Stats {
+    // One per difficulty, in the order of `Difficulty::all()`
+    pub tiers: Vec<TierStats>,
+    profile: u32,
+}
+
+impl Stats {
+    pub fn load(profile: u32) -> Self {
+        let mut tiers = vec![TierStats::default(); Difficulty::all().len()];
+        if let Some(data) = storage::load(&stats_key(profile)) {
+            for (tier, line) in tiers.iter_mut().zip(data.lines()) {
+                *tier = parse_tier(line).unwrap_or_default();
+            }
+        }
+        Self { tiers, profile }
+    }
+
+    pub fn tier(&self, difficulty: &Difficulty) -> &TierStats {
+        &self.tiers[tier_index(difficulty)]
+    }
+
+    /// Counts a newly started game
+    pub fn record_start(&mut self, game: &SudokuGame) {
+        let Some(generation) = &game.generation else {
+            return;
+        };
+        let tier = &mut self.tiers[tier_index(&generation.difficulty)];
+        if tier.in_progress {
+            tier.current_streak = 0;
+        }
+        tier.started += 1;
+        tier.in_progress = true;
+        self.save();
+    }
+
+    /// Counts a solved game. Solving it again after undoing is not counted.
+    pub fn record_completion(&mut self, game: &SudokuGame) {
+        let Some(generation) = &game.generation else {
+            return;
+        };
+        let tier = &mut self.tiers[tier_index(&generation.difficulty)];
+        if !tier.in_progress {
+            return;
+        }
+        let seconds = game.elapsed_seconds();
+        tier.in_progress = false;
+        tier.completed += 1;
+        tier.total_seconds += seconds;
+        tier.best_seconds = Some(tier.best_seconds.map_or(seconds, |best| best.min(seconds)));
+        tier.hints += game.hints_taken;
+        tier.mistakes += game.mistakes;
+        tier.current_streak += 1;
+        tier.best_streak = tier.best_streak.max(tier.current_streak);
+        self.save();
+    }
+
+    // One line of comma-separated counts per difficulty
+    fn save(&self) {
+        let data = self
+            .tiers
+            .iter()
+            .map(|tier| {
+                [
+                    tier.started.to_string(),
+                    tier.completed.to_string(),
+                    tier.total_seconds.to_string(),
+                    tier.best_seconds.map(|best| best.to_string()).unwrap_or_default(),
+                    tier.hints.to_string(),
+                    tier.mistakes.to_string(),
+                    tier.current_streak.to_string(),
+                    tier.best_streak.to_string(),
+                    (tier.in_progress as u8).to_string(),
+                ]
+                .join(",")
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        storage::save(&stats_key(self.profile), &data);
+    }
+}
+
+fn parse_tier(line: &str) -> Option<TierStats> {
+    let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+    let number = |index: usize| fields.get(index)?.parse::<u64>().ok();
+    Some(TierStats {
+        started: number(0)? as usize,
+        completed: number(1)? as usize,
+        total_seconds: number(2)?,
+        best_seconds: number(3),
+        hints: number(4)? as usize,
+        mistakes: number(5)? as usize,
+        current_streak: number(6)? as usize,
+        best_streak: number(7)? as usize,
+        in_progress: number(8)? == 1,
+    })
+}
+
+fn tier_index(difficulty: &Difficulty) -> usize {
+    Difficulty::all()
+        .iter()
+        .position(|tier| tier == difficulty)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_logic::{Generation, GenerationOptions};
+    use crate::puzzle_format::parse_puzzle;
+
+    const PUZZLE: &str = "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79";
+    // Tests share the storage directory, so this one keeps a profile of its own
+    const PROFILE: u32 = 4242;
+
+    fn medium_game() -> SudokuGame {
+        let (rules, grid) = parse_puzzle(&format!("grid {PUZZLE}")).unwrap();
+        let mut game = SudokuGame::from_puzzle(rules, grid);
+        game.generation = Some(Generation {
+            difficulty: Difficulty::Medium,
+            options: GenerationOptions::default(),
+            seed: None,
+            daily: false,
+        });
+        game
+    }
+
+    #[test]
+    fn tiers_survive_a_reload() {
+        remove(PROFILE);
+        let mut stats = Stats::load(PROFILE);
+        assert_eq!(stats.tier(&Difficulty::Medium), &TierStats::default());
+
+        // Abandoning a game ends the streak of its difficulty
+        let mut game = medium_game();
+        stats.record_start(&game);
+        stats.record_start(&game);
+        let solution = game.solution().unwrap();
+        assert!(game.solve_one_cell());
+        assert!(game.undo());
+        assert!(game.solve_one_cell());
+        for (row, col) in game.size.cells() {
+            if game.grid[row][col].is_none() {
+                game.select_cell(row, col);
+                game.input_number(solution[row][col].unwrap());
+            }
+        }
+        stats.record_completion(&game);
+        stats.record_completion(&game);
+
+        let tier = stats.tier(&Difficulty::Medium);
+        assert_eq!((tier.started, tier.completed), (2, 1));
+        assert_eq!(tier.hints, 2);
+        assert_eq!((tier.current_streak, tier.best_streak), (1, 1));
+        assert_eq!(tier.win_rate(), Some(0.5));
+        assert_eq!(stats.tier(&Difficulty::Easy), &TierStats::default());
+        assert_eq!(Stats::load(PROFILE), stats);
+
+        remove(PROFILE);
+        assert_eq!(Stats::load(PROFILE).tier(&Difficulty::Medium), &TierStats::default());
+    }
+}
@@ -0,0 +1,60 @@
+//! Storage module for small pieces of persisted app data
+//!
+//! Values are plain strings addressed by a key. On desktop each key is a file in
+//! the user's local data directory, on Android in the app's private directory;
+//! on the web it is an entry in `localStorage`.
+
+#[cfg(target_arch = "wasm32")]
+pub fn load(key: &str) -> Option<String> {
+    web_sys::window()?
+        .local_storage()
+        .ok()??
+        .get_item(&storage_key(key))
+        .ok()?
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn save(key: &str, value: &str) {
+    if let Some(Ok(Some(storage))) = web_sys::window().map(|window| window.local_storage()) {
+        let _ = storage.set_item(&storage_key(key), value);
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn storage_key(key: &str) -> String {
+    format!("pigsudoku.{}", key)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn load(key: &str) -> Option<String> {
+    std::fs::read_to_string(storage_path(key)?).ok()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn save(key: &str, value: &str) {
+    if let Some(path) = storage_path(key) {
+        if let Some(parent) = path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        let _ = std::fs::write(path, value);
+    }
+}
+
+#[cfg(all(not(test), not(target_arch = "wasm32"), not(target_os = "android")))]
+fn storage_path(key: &str) -> Option<std::path::PathBuf> {
+    Some(dirs::data_local_dir()?.join("pigsudoku").join(format!("{}.txt", key)))
+}
+
+// Android has no local data directory for `dirs` to find, so files go in the
+// app's private directory, named after the bundle identifier in Dioxus.toml
+#[cfg(all(not(test), target_os = "android"))]
+fn storage_path(key: &str) -> Option<std::path::PathBuf> {
+    Some(std::path::PathBuf::from("/data/data/com.pigsudoku/files").join(format!("{}.txt", key)))
+}
+
+// Tests keep their data out of the player's, in a directory of their own
+#[cfg(all(test, not(target_arch = "wasm32")))]
+fn storage_path(key: &str) -> Option<std::path::PathBuf> {
+    let dir = std::env::temp_dir().join(format!("pigsudoku-test-{}", std::process::id()));
+    Some(dir.join(format!("{}.txt", key)))
+}
@@ -0,0 +1,424 @@
+//! Color themes
+//!
+//! Components take their colors from CSS custom properties such as
+//! `var(--cell)`, and a theme is the palette those properties are set to.
+//! Besides the built-in light, dark and high-contrast palettes, players can
+//! save palettes of their own. The "system" theme follows the
+//! `prefers-color-scheme` media query, which the desktop webview answers with
+//! the operating system's setting.
+//!
+//! Colorblind mode draws patterns and markers on the board and uses colors
+//! from the Okabe-Ito palette, which stay apart under the common color vision
+//! deficiencies.
+
+use crate::storage;
+
+const THEMES_KEY: &str = "themes";
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Color {
+    Page,
+    Surface,
+    SurfaceAlt,
+    Text,
+    Muted,
+    Border,
+    Input,
+    GridLine,
+    CellLine,
+    Cell,
+    CellFilled,
+    Given,
+    Entered,
+    Selected,
+    SelectedGiven,
+    Highlight,
+    SelectedHighlight,
+    // Cells sharing a house with the selection
+    Peer,
+    // Empty cells where the highlighted number can still go
+    Candidate,
+    Conflict,
+    ConflictGiven,
+    ConflictText,
+    ConflictBorder,
+    Shaded,
+    Accent,
+    AccentEditor,
+    AccentImport,
+    AccentTools,
+    AccentPhoto,
+    AccentProfile,
+    AccentHint,
+    Success,
+    SuccessBackground,
+    SuccessText,
+    Error,
+    OverlayLine,
+    NoteMode,
+    NoteModeText,
+    Disabled,
+    DisabledText,
+    CelebrationStart,
+    CelebrationEnd,
+}
+
+impl Color {
+    pub fn all() -> [Color; 42] {
+        [
+            Color::Page,
+            Color::Surface,
+            Color::SurfaceAlt,
+            Color::Text,
+            Color::Muted,
+            Color::Border,
+            Color::Input,
+            Color::GridLine,
+            Color::CellLine,
+            Color::Cell,
+            Color::CellFilled,
+            Color::Given,
+            Color::Entered,
+            Color::Selected,
+            Color::SelectedGiven,
+            Color::Highlight,
+            Color::SelectedHighlight,
+            Color::Peer,
+            Color::Candidate,
+            Color::Conflict,
+            Color::ConflictGiven,
+            Color::ConflictText,
+            Color::ConflictBorder,
+            Color::Shaded,
+            Color::Accent,
+            Color::AccentEditor,
+            Color::AccentImport,
+            Color::AccentTools,
+            Color::AccentPhoto,
+            Color::AccentProfile,
+            Color::AccentHint,
+            Color::Success,
+            Color::SuccessBackground,
+            Color::SuccessText,
+            Color::Error,
+            Color::OverlayLine,
+            Color::NoteMode,
+            Color::NoteModeText,
+            Color::Disabled,
+            Color::DisabledText,
+            Color::CelebrationStart,
+            Color::CelebrationEnd,
+        ]
+    }
+
+    // The custom property, without the leading dashes. Also names the color
+    // in saved palettes and locale keys.
+    pub fn key(&self) -> &'static str {
+        match self {
+            Color::Page => "page",
+            Color::Surface => "surface",
+            Color::SurfaceAlt => "surface-alt",
+            Color::Text => "text",
+            Color::Muted => "muted",
+            Color::Border => "border",
+            Color::Input => "input",
+            Color::GridLine => "grid-line",
+            Color::CellLine => "cell-line",
+            Color::Cell => "cell",
+            Color::CellFilled => "cell-filled",
+            Color::Given => "given",
+            Color::Entered => "entered",
+            Color::Selected => "selected",
+            Color::SelectedGiven => "selected-given",
+            Color::Highlight => "highlight",
+            Color::SelectedHighlight => "selected-highlight",
+            Color::Peer => "peer",
+            Color::Candidate => "candidate",
+            Color::Conflict => "conflict",
+            Color::ConflictGiven => "conflict-given",
+            Color::ConflictText => "conflict-text",
+            Color::ConflictBorder => "conflict-border",
+            Color::Shaded => "shaded",
+            Color::Accent => "accent",
+            Color::AccentEditor => "accent-editor",
+            Color::AccentImport => "accent-import",
+            Color::AccentTools => "accent-tools",
+            Color::AccentPhoto => "accent-photo",
+            Color::AccentProfile => "accent-profile",
+            Color::AccentHint => "accent-hint",
+            Color::Success => "success",
+            Color::SuccessBackground => "success-background",
+            Color::SuccessText => "success-text",
+            Color::Error => "error",
+            Color::OverlayLine => "overlay-line",
+            Color::NoteMode => "note-mode",
+            Color::NoteModeText => "note-mode-text",
+            Color::Disabled => "disabled",
+            Color::DisabledText => "disabled-text",
+            Color::CelebrationStart => "celebration-start",
+            Color::CelebrationEnd => "celebration-end",
+        }
+    }
+}
+
+/// A color for every `Color`, as `#rrggbb`
+#[derive(Clone, PartialEq, Debug)]
+pub struct Palette {
+    colors: Vec<String>,
+}
+
+impl Palette {
+    // Colors in the order of `Color::all()`
+    fn from_hex(colors: [&str; 42]) -> Self {
+        Self {
+            colors: colors.iter().map(|color| color.to_string()).collect(),
+        }
+    }
+
+    pub fn light() -> Self {
+        Self::from_hex([
+            "#f0f0f0", "#ffffff", "#f8f9fa", "#333333", "#666666", "#dddddd", "#ffffff", "#333333", "#cccccc",
+            "#ffffff", "#f8f9fa", "#000000", "#1976d2", "#e3f2fd", "#ffecb3", "#fff9c4", "#ffc107", "#f1f4f8",
+            "#e8f5e9", "#ffebee", "#ffcdd2", "#d32f2f", "#f44336", "#ede7f6", "#2196f3", "#9c27b0", "#795548",
+            "#607d8b", "#009688", "#3f51b5", "#ff9800", "#4caf50", "#f1f8e9", "#2e7d32", "#f44336", "#9e9e9e",
+            "#ffc107", "#000000", "#cccccc", "#666666", "#ff6b6b", "#45b7d1",
+        ])
+    }
+
+    pub fn dark() -> Self {
+        Self::from_hex([
+            "#121212", "#1e1e1e", "#262626", "#e0e0e0", "#a0a0a0", "#444444", "#2a2a2a", "#bdbdbd", "#4a4a4a",
+            "#1e1e1e", "#262626", "#f5f5f5", "#64b5f6", "#1e3a5f", "#4a3b12", "#3d3a1c", "#8d6e00", "#262b33",
+            "#1f3322", "#4a1c1c", "#5c2020", "#ff8a80", "#ef5350", "#2d2640", "#1e88e5", "#ba68c8", "#a1887f",
+            "#90a4ae", "#26a69a", "#7986cb", "#fb8c00", "#66bb6a", "#1f3322", "#81c784", "#ef5350", "#8a8a8a",
+            "#ffb300", "#000000", "#3a3a3a", "#8a8a8a", "#8e2430", "#1c5d6e",
+        ])
+    }
+
+    pub fn high_contrast() -> Self {
+        Self::from_hex([
+            "#000000", "#000000", "#000000", "#ffffff", "#ffffff", "#ffffff", "#000000", "#ffffff", "#c0c0c0",
+            "#000000", "#000000", "#ffffff", "#00ffff", "#0000aa", "#5c3d00", "#444400", "#806600", "#262626",
+            "#003300", "#660000", "#800000", "#ffffff", "#ff0000", "#1a1a40", "#0050c0", "#d000d0", "#c06000",
+            "#008080", "#00a090", "#4060ff", "#b35900", "#00a000", "#002200", "#00ff00", "#ff4040", "#c0c0c0",
+            "#ffff00", "#000000", "#333333", "#c0c0c0", "#000000", "#000000",
+        ])
+    }
+
+    // Whether empty cells are darker than mid-gray
+    pub fn is_dark(&self) -> bool {
+        let cell = self.get(Color::Cell);
+        let channel = |index: usize| u8::from_str_radix(&cell[index..index + 2], 16).unwrap_or(255) as u32;
+        channel(1) * 299 + channel(3) * 587 + channel(5) * 114 < 128_000
+    }
+
+    pub fn get(&self, color: Color) -> &str {
+        &self.colors[color as usize]
+    }
+
+    pub fn set(&mut self, color: Color, value: &str) {
+        if is_hex_color(value) {
+            self.colors[color as usize] = value.to_lowercase();
+        }
+    }
+
+    /// A style rule setting the custom properties for the page
+    pub fn stylesheet(&self) -> String {
+        let declarations = Color::all()
+            .iter()
+            .map(|color| format!("--{}: {};", color.key(), self.get(*color)))
+            .collect::<Vec<_>>()
+            .join(" ");
+        format!(":root {{ {declarations} }}")
+    }
+}
+
+// Okabe-Ito blue and vermillion on light boards, their lighter sky blue and
+// orange on dark ones
+fn colorblind_rule(dark: bool) -> String {
+    let (selected, conflict, stripes, dots) = if dark {
+        ("#56b4e9", "#e69f00", "rgba(230, 159, 0, 0.5)", "rgba(86, 180, 233, 0.6)")
+    } else {
+        ("#0072b2", "#d55e00", "rgba(213, 94, 0, 0.45)", "rgba(0, 114, 178, 0.55)")
+    };
+    format!(
+        ":root {{ --conflict-border: {conflict}; --cvd-selected: {selected}; --cvd-conflict: {stripes}; --cvd-highlight: {dots}; }}"
+    )
+}
+
+fn is_hex_color(value: &str) -> bool {
+    value.len() == 7 && value.starts_with('#') && value[1..].chars().all(|ch| ch.is_ascii_hexdigit())
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum Theme {
+    // Light or dark, following the system
+    System,
+    Light,
+    Dark,
+    HighContrast,
+    // A saved palette, by name
+    Custom(String),
+}
+
+impl Theme {
+    pub fn builtin() -> [Theme; 4] {
+        [Theme::System, Theme::Light, Theme::Dark, Theme::HighContrast]
+    }
+
+    // How the theme is stored in preferences
+    pub fn key(&self) -> String {
+        match self {
+            Theme::System => "system".to_string(),
+            Theme::Light => "light".to_string(),
+            Theme::Dark => "dark".to_string(),
+            Theme::HighContrast => "high-contrast".to_string(),
+            Theme::Custom(name) => format!("custom:{name}"),
+        }
+    }
+
+    pub fn from_key(key: &str) -> Self {
+        match key {
+            "light" => Theme::Light,
+            "dark" => Theme::Dark,
+            "high-contrast" => Theme::HighContrast,
+            key => match key.strip_prefix("custom:") {
+                Some(name) => Theme::Custom(name.to_string()),
+                None => Theme::System,
+            },
+        }
+    }
+
+    /// The palette shown, or the light one for the system theme. Unknown
+    /// custom palettes are shown as the system theme.
+    pub fn palette(&self, custom: &CustomPalettes) -> Palette {
+        match self {
+            Theme::Dark => Palette::dark(),
+            Theme::HighContrast => Palette::high_contrast(),
+            Theme::Custom(name) => custom.find(name).cloned().unwrap_or_else(Palette::light),
+            Theme::System | Theme::Light => Palette::light(),
+        }
+    }
+
+    // Unknown custom palettes are shown as the system theme
+    fn follows_system(&self, custom: &CustomPalettes) -> bool {
+        match self {
+            Theme::System => true,
+            Theme::Custom(name) => custom.find(name).is_none(),
+            _ => false,
+        }
+    }
+
+    /// Custom properties for colorblind mode, added after `stylesheet`
+    pub fn colorblind_stylesheet(&self, custom: &CustomPalettes) -> String {
+        if self.follows_system(custom) {
+            format!(
+                "{} @media (prefers-color-scheme: dark) {{ {} }}",
+                colorblind_rule(false),
+                colorblind_rule(true)
+            )
+        } else {
+            colorblind_rule(self.palette(custom).is_dark())
+        }
+    }
+
+    /// The stylesheet that sets the theme's custom properties for the page
+    pub fn stylesheet(&self, custom: &CustomPalettes) -> String {
+        let properties = if self.follows_system(custom) {
+            format!(
+                "{} @media (prefers-color-scheme: dark) {{ {} }}",
+                Palette::light().stylesheet(),
+                Palette::dark().stylesheet()
+            )
+        } else {
+            self.palette(custom).stylesheet()
+        };
+        // The page around the app and form controls keep the browser's colors
+        // otherwise
+        format!(
+            "{properties} body {{ background-color: var(--page); }} \
+             input[type=text], input[type=number], select, textarea \
+             {{ background-color: var(--input); color: var(--text); border-color: var(--border); }}"
+        )
+    }
+}
+
+/// Palettes saved by players, shared by every profile on the device
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct CustomPalettes {
+    pub list: Vec<(String, Palette)>,
+}
+
+impl CustomPalettes {
+    // One palette per line: the name, then `key=#rrggbb` pairs, tab-separated
+    pub fn load() -> Self {
+        let data = storage::load(THEMES_KEY).unwrap_or_default();
+        let list = data
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.split('\t');
+                let name = fields.next()?.trim();
+                if name.is_empty() {
+                    return None;
+                }
+                // Colors missing from older palettes come from the light theme
+                let mut palette = Palette::light();
+                for field in fields {
+                    if let Some((key, value)) = field.split_once('=')
+                        && let Some(color) = Color::all().into_iter().find(|color| color.key() == key)
+                    {
+                        palette.set(color, value);
+                    }
+                }
+                Some((name.to_string(), palette))
+            })
+            .collect();
+        Self { list }
+    }
+
+    pub fn find(&self, name: &str) -> Option<&Palette> {
+        self.list
+            .iter()
+            .find(|(palette_name, _)| palette_name == name)
+            .map(|(_, palette)| palette)
+    }
+
+    /// Saves a palette under `name`, replacing one with the same name.
+    /// Returns the name it was saved under, or `None` for a blank name.
+    pub fn save_palette(&mut self, name: &str, palette: Palette) -> Option<String> {
+        // Names are stored in tab-separated lines
+        let name = name.split_whitespace().collect::<Vec<_>>().join(" ");
+        if name.is_empty() {
+            return None;
+        }
+        match self.list.iter_mut().find(|(palette_name, _)| *palette_name == name) {
+            Some((_, existing)) => *existing = palette,
+            None => self.list.push((name.clone(), palette)),
+        }
+        self.save();
+        Some(name)
+    }
+
+    pub fn remove(&mut self, name: &str) {
+        self.list.retain(|(palette_name, _)| palette_name != name);
+        self.save();
+    }
+
+    fn save(&self) {
+        let data = self
+            .list
+            .iter()
+            .map(|(name, palette)| {
+                let mut fields = vec![name.clone()];
+                fields.extend(
+                    Color::all()
+                        .iter()
+                        .map(|color| format!("{}={}", color.key(), palette.get(*color))),
+                );
+                fields.join("\t")
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        storage::save(THEMES_KEY, &data);
+    }
+}